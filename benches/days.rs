@@ -0,0 +1,107 @@
+use advent_of_code_2022::generate::generate;
+use advent_of_code_2022::solution::Part;
+use advent_of_code_2022::{solution, SOLUTIONS};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+
+/// Days that take long enough per iteration to need fewer samples.
+const HEAVY_DAYS: [u8; 5] = [16, 17, 19, 23, 24];
+
+/// Parts that cannot solve the puzzle example: the day 22 solver only folds
+/// the cube net of the full-size input.
+const UNSUPPORTED_EXAMPLES: [(u8, Part); 1] = [(22, Part::Two)];
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Source {
+    Input,
+    Example,
+}
+
+/// Prefers the real input in `input/2022`, which is not committed, and falls
+/// back to the puzzle example so the suite also runs offline.
+fn input(day: u8) -> (String, Source) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    match fs::read_to_string(root.join(format!("input/2022/day{day}.txt"))) {
+        Ok(input) => (input, Source::Input),
+        Err(_) => (
+            fs::read_to_string(root.join(format!("benches/examples/day{day}.txt")))
+                .expect("every day has an example"),
+            Source::Example,
+        ),
+    }
+}
+
+fn days(c: &mut Criterion) {
+    for solution in SOLUTIONS {
+        let day = solution.day();
+        let (input, source) = input(day);
+        let mut group = c.benchmark_group(format!("day{day:02}"));
+
+        if HEAVY_DAYS.contains(&day) {
+            group.sample_size(10);
+        }
+
+        group.bench_function("parse", |b| {
+            b.iter(|| solution.parse(black_box(&input)).unwrap())
+        });
+
+        let parsed_input = solution.parse(&input).unwrap();
+
+        for part in Part::ALL {
+            if source == Source::Example && UNSUPPORTED_EXAMPLES.contains(&(day, part)) {
+                continue;
+            }
+
+            for &variant in solution.variants(part) {
+                group.bench_function(format!("part{part}/{variant}"), |b| {
+                    b.iter(|| parsed_input.solve(part, variant))
+                });
+            }
+        }
+
+        group.finish();
+    }
+}
+
+/// Generated inputs for the days whose running time grows with the input size.
+const SCALED: [(u8, [usize; 3]); 8] = [
+    (1, [1_000, 10_000, 100_000]),
+    (6, [4_096, 65_536, 1_048_576]),
+    (8, [100, 200, 400]),
+    (9, [2_000, 20_000, 200_000]),
+    (17, [10_000, 40_000, 160_000]),
+    (20, [1_000, 2_500, 5_000]),
+    (23, [20, 35, 50]),
+    (24, [50, 100, 150]),
+];
+
+fn scaled(c: &mut Criterion) {
+    for (day, sizes) in SCALED {
+        let solution = solution(day).unwrap();
+        let mut group = c.benchmark_group(format!("day{day:02}_scaled"));
+        group.sample_size(10);
+
+        for size in sizes {
+            let input = generate(day, size, u64::from(day)).unwrap();
+            let parsed_input = solution.parse(&input).unwrap();
+
+            for part in Part::ALL {
+                for &variant in solution.variants(part) {
+                    group.bench_with_input(
+                        BenchmarkId::new(format!("part{part}/{variant}"), size),
+                        &parsed_input,
+                        |b, parsed_input| b.iter(|| parsed_input.solve(part, variant)),
+                    );
+                }
+            }
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, days, scaled);
+criterion_main!(benches);
@@ -0,0 +1,175 @@
+//! The integers of the days whose numbers grow with their input: the worry
+//! levels of day 11, the tuning frequency of day 15, the monkey math of day 21
+//! and the SNAFU sums of day 25.
+//!
+//! By default they are 64-bit integers with the usual arithmetic, which wraps
+//! around on overflow in release builds. With the `checked` feature every
+//! operation is checked, and an overflow makes the solver answer
+//! [`Answer::Overflow`](crate::solution::Answer::Overflow). With the `bigint`
+//! feature they are arbitrary-precision integers that never overflow.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "bigint")]
+pub type Integer = num_bigint::BigInt;
+#[cfg(not(feature = "bigint"))]
+pub type Integer = i64;
+
+/// A non-negative [`Integer`].
+#[cfg(feature = "bigint")]
+pub type Natural = num_bigint::BigUint;
+#[cfg(not(feature = "bigint"))]
+pub type Natural = u64;
+
+#[cfg(feature = "bigint")]
+pub fn integer(value: i64) -> Integer {
+    Integer::from(value)
+}
+
+#[cfg(not(feature = "bigint"))]
+pub fn integer(value: i64) -> Integer {
+    value
+}
+
+#[cfg(feature = "bigint")]
+pub fn natural(value: u64) -> Natural {
+    Natural::from(value)
+}
+
+#[cfg(not(feature = "bigint"))]
+pub fn natural(value: u64) -> Natural {
+    value
+}
+
+/// The result of an operation did not fit in its type, or was a division by zero.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Overflow;
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "arithmetic overflow")
+    }
+}
+
+impl Error for Overflow {}
+
+/// The four operations, failing with [`Overflow`] when the `checked` feature is on.
+pub trait Arithmetic: Sized {
+    fn try_add(&self, other: &Self) -> Result<Self, Overflow>;
+
+    fn try_sub(&self, other: &Self) -> Result<Self, Overflow>;
+
+    fn try_mul(&self, other: &Self) -> Result<Self, Overflow>;
+
+    fn try_div(&self, other: &Self) -> Result<Self, Overflow>;
+}
+
+/// The remainder of a division, failing with [`Overflow`] when the `checked` feature is on.
+pub trait Remainder: Sized {
+    fn try_rem(&self, other: &Self) -> Result<Self, Overflow>;
+}
+
+#[cfg(any(feature = "checked", feature = "bigint"))]
+mod implementation {
+    use super::{Arithmetic, Overflow, Remainder};
+    use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+    use std::ops::Rem;
+
+    impl<T: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv> Arithmetic for T {
+        fn try_add(&self, other: &Self) -> Result<Self, Overflow> {
+            self.checked_add(other).ok_or(Overflow)
+        }
+
+        fn try_sub(&self, other: &Self) -> Result<Self, Overflow> {
+            self.checked_sub(other).ok_or(Overflow)
+        }
+
+        fn try_mul(&self, other: &Self) -> Result<Self, Overflow> {
+            self.checked_mul(other).ok_or(Overflow)
+        }
+
+        fn try_div(&self, other: &Self) -> Result<Self, Overflow> {
+            self.checked_div(other).ok_or(Overflow)
+        }
+    }
+
+    // Big integers have no `CheckedRem`, but a remainder fails exactly when the
+    // quotient does.
+    impl<T: CheckedDiv> Remainder for T
+    where
+        for<'a> &'a T: Rem<&'a T, Output = T>,
+    {
+        fn try_rem(&self, other: &Self) -> Result<Self, Overflow> {
+            self.checked_div(other).ok_or(Overflow)?;
+            Ok(self % other)
+        }
+    }
+}
+
+#[cfg(not(any(feature = "checked", feature = "bigint")))]
+mod implementation {
+    use super::{Arithmetic, Overflow, Remainder};
+    use std::ops::{Add, Div, Mul, Rem, Sub};
+
+    impl<T> Arithmetic for T
+    where
+        T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+    {
+        fn try_add(&self, other: &Self) -> Result<Self, Overflow> {
+            Ok(self.clone() + other.clone())
+        }
+
+        fn try_sub(&self, other: &Self) -> Result<Self, Overflow> {
+            Ok(self.clone() - other.clone())
+        }
+
+        fn try_mul(&self, other: &Self) -> Result<Self, Overflow> {
+            Ok(self.clone() * other.clone())
+        }
+
+        fn try_div(&self, other: &Self) -> Result<Self, Overflow> {
+            Ok(self.clone() / other.clone())
+        }
+    }
+
+    impl<T: Clone + Rem<Output = T>> Remainder for T {
+        fn try_rem(&self, other: &Self) -> Result<Self, Overflow> {
+            Ok(self.clone() % other.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations() {
+        assert_eq!(integer(6).try_add(&integer(7)), Ok(integer(13)));
+        assert_eq!(integer(6).try_sub(&integer(7)), Ok(integer(-1)));
+        assert_eq!(integer(6).try_mul(&integer(7)), Ok(integer(42)));
+        assert_eq!(integer(-7).try_div(&integer(2)), Ok(integer(-3)));
+        assert_eq!(natural(79).try_rem(&natural(23)), Ok(natural(10)));
+    }
+
+    #[cfg(all(feature = "checked", not(feature = "bigint")))]
+    #[test]
+    fn checked_overflow() {
+        assert_eq!(integer(i64::MAX).try_add(&integer(1)), Err(Overflow));
+        assert_eq!(natural(1 << 40).try_mul(&natural(1 << 40)), Err(Overflow));
+        assert_eq!(integer(1).try_div(&integer(0)), Err(Overflow));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_does_not_overflow() {
+        assert_eq!(
+            integer(i64::MAX)
+                .try_mul(&integer(i64::MAX))
+                .unwrap()
+                .to_string(),
+            "85070591730234615847396907784232501249"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Cycle detection for simulations run for far more steps than can be
+//! simulated one by one: once the states start repeating, the value after any
+//! number of steps follows from one turn of the cycle.
+//!
+//! The state of a simulation often holds more than what decides its future,
+//! e.g. the height of a tower, so the detectors compare states by a `key` and
+//! extrapolate a `value` that grows by the same amount every turn of the cycle.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::hash::Hash;
+
+/// A value that grows by the same amount every turn of a cycle.
+pub trait Growth {
+    /// Returns `self` plus `turns` times the growth from `from` to `to`.
+    fn grow(&self, from: &Self, to: &Self, turns: usize) -> Self;
+}
+
+macro_rules! impl_growth {
+    ($($integer:ty),*) => {
+        $(
+            impl Growth for $integer {
+                fn grow(&self, from: &Self, to: &Self, turns: usize) -> Self {
+                    self + (to - from) * turns as $integer
+                }
+            }
+        )*
+    };
+}
+
+impl_growth!(usize, u64, i64);
+
+/// Grows every element on its own, e.g. counts per monkey.
+impl<T: Growth> Growth for Vec<T> {
+    fn grow(&self, from: &Self, to: &Self, turns: usize) -> Self {
+        self.iter()
+            .zip(from.iter().zip(to))
+            .map(|(value, (from, to))| value.grow(from, to, turns))
+            .collect()
+    }
+}
+
+/// Returns the value after `steps` steps from `initial`, remembering the key
+/// of every state until one repeats.
+pub fn hashing<S, K: Hash + Eq, V: Growth>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+    value: impl Fn(&S) -> V,
+    steps: usize,
+) -> V {
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut values: Vec<V> = Vec::new();
+    let mut state = initial;
+
+    for index in 0..steps {
+        match seen.entry(key(&state)) {
+            Entry::Occupied(entry) => {
+                let start = *entry.get();
+                let length = index - start;
+                let (turns, rest) = ((steps - start) / length, (steps - start) % length);
+
+                return values[start + rest].grow(&values[start], &value(&state), turns);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+            }
+        }
+
+        values.push(value(&state));
+        state = step(&state);
+    }
+
+    value(&state)
+}
+
+/// Returns the value after `steps` steps from `initial` with Brent's
+/// algorithm, which keeps only two states but steps about three times as often.
+pub fn brent<S: Clone, K: Eq, V: Growth>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    key: impl Fn(&S) -> K,
+    value: impl Fn(&S) -> V,
+    steps: usize,
+) -> V {
+    if steps == 0 {
+        return value(&initial);
+    }
+
+    // Finds the length of the cycle: the tortoise waits at powers of two for
+    // the hare to come round.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    let mut hare_index = 1;
+
+    while key(&tortoise) != key(&hare) {
+        if hare_index == steps {
+            return value(&hare);
+        }
+
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+
+        hare = step(&hare);
+        hare_index += 1;
+        length += 1;
+    }
+
+    // Finds the start of the cycle: a hare `length` steps ahead meets the
+    // tortoise there.
+    let mut tortoise = initial;
+    let mut hare = tortoise.clone();
+    let mut start = 0;
+
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let (turns, rest) = ((steps - start) / length, (steps - start) % length);
+    let (from, to) = (value(&tortoise), value(&hare));
+
+    for _ in 0..rest {
+        tortoise = step(&tortoise);
+    }
+
+    value(&tortoise).grow(&from, &to, turns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// A position on a track of `tail` squares leading into a loop of
+    /// `length` squares, and the number of laps of the loop.
+    fn walk(tail: u64, length: u64) -> impl Fn(&(u64, u64)) -> (u64, u64) {
+        move |&(position, laps)| {
+            if position + 1 == tail + length {
+                (tail, laps + 1)
+            } else {
+                (position + 1, laps)
+            }
+        }
+    }
+
+    fn laps(tail: u64, length: u64, steps: usize) -> u64 {
+        (steps as u64).saturating_sub(tail) / length
+    }
+
+    #[test]
+    fn extrapolates() {
+        let steps = 1_000_000_000_000;
+
+        assert_eq!(
+            hashing((0, 0), walk(7, 5), |state| state.0, |state| state.1, steps),
+            laps(7, 5, steps)
+        );
+        assert_eq!(
+            brent((0, 0), walk(7, 5), |state| state.0, |state| state.1, steps),
+            laps(7, 5, steps)
+        );
+    }
+
+    #[test]
+    fn grows_vectors() {
+        assert_eq!(
+            vec![5_usize, 2].grow(&vec![1, 1], &vec![3, 2], 10),
+            vec![25, 12]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn detectors_count_laps(tail in 0..20u64, length in 1..20u64, steps in 0..200usize) {
+            let expected = laps(tail, length, steps);
+
+            prop_assert_eq!(
+                hashing((0, 0), walk(tail, length), |state| state.0, |state| state.1, steps),
+                expected
+            );
+            prop_assert_eq!(
+                brent((0, 0), walk(tail, length), |state| state.0, |state| state.1, steps),
+                expected
+            );
+        }
+    }
+}
@@ -1,47 +1,329 @@
+//! Day 1: Calorie Counting.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead};
+use std::num::NonZeroU64;
+
+const DAY: u8 = 1;
 
+/// Parses the elves' inventories, separated by blank lines, into item calories.
 #[aoc_generator(day1)]
-fn parse_input(input: &str) -> Vec<Vec<u32>> {
-    input
-        .split("\n\n")
-        .map(|inventory| {
-            inventory
-                .lines()
-                .map(|item| item.parse().unwrap())
-                .collect()
-        })
-        .collect()
+pub fn parse(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let mut calories = Vec::new();
+    let mut inventory = Vec::new();
+
+    for (line_index, item) in input.lines().enumerate() {
+        if item.is_empty() {
+            if !inventory.is_empty() {
+                calories.push(inventory);
+                inventory = Vec::new();
+            }
+
+            continue;
+        }
+
+        inventory.push(item.parse().map_err(|_| {
+            ParseError::at_token(DAY, line_index, item, item, "a number of calories")
+        })?);
+    }
+
+    if !inventory.is_empty() {
+        calories.push(inventory);
+    }
+
+    Ok(calories)
+}
+
+/// An elf and the total calories of its inventory.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Elf {
+    /// Position of the elf's inventory in the input, from 0.
+    pub index: usize,
+    pub total: u64,
+}
+
+/// Elves carrying more come first, and of two carrying the same, the one listed first.
+impl Ord for Elf {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .total
+            .cmp(&self.total)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for Elf {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn elves(calories: &[Vec<u32>]) -> impl Iterator<Item = Elf> + '_ {
+    calories.iter().enumerate().map(|(index, inventory)| Elf {
+        index,
+        total: inventory.iter().copied().map(u64::from).sum(),
+    })
+}
+
+/// Returns the `k` elves carrying the most calories, the one carrying the most first.
+///
+/// Only `k` elves are kept at a time, in a heap whose top is the one to drop next.
+pub fn top_elves(calories: &[Vec<u32>], k: usize) -> Vec<Elf> {
+    let mut top = TopElves::new(k);
+    elves(calories).for_each(|elf| top.push(elf));
+
+    top.into_vec()
+}
+
+struct TopElves {
+    k: usize,
+    heap: BinaryHeap<Elf>,
+}
+
+impl TopElves {
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    fn push(&mut self, elf: Elf) {
+        self.heap.push(elf);
+
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    fn into_vec(self) -> Vec<Elf> {
+        self.heap.into_sorted_vec()
+    }
+}
+
+/// The spread of the total calories of all the elves.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Statistics {
+    pub elves: usize,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub median: f64,
+    /// Number of elves per range of `bucket_width` totals, keyed by the start of
+    /// the range. Empty ranges are left out.
+    pub distribution: BTreeMap<u64, usize>,
+}
+
+/// Returns the statistics of the totals, or `None` without any elves.
+pub fn statistics(calories: &[Vec<u32>], bucket_width: NonZeroU64) -> Option<Statistics> {
+    let mut totals: Vec<u64> = elves(calories).map(|elf| elf.total).collect();
+    totals.sort_unstable();
+
+    let (&min, &max) = (totals.first()?, totals.last()?);
+    let middle = totals.len() / 2;
+    let median = if totals.len().is_multiple_of(2) {
+        (totals[middle - 1] as f64 + totals[middle] as f64) / 2.0
+    } else {
+        totals[middle] as f64
+    };
+
+    let mut distribution = BTreeMap::new();
+
+    for &total in &totals {
+        *distribution
+            .entry(total - total % bucket_width)
+            .or_default() += 1;
+    }
+
+    Some(Statistics {
+        elves: totals.len(),
+        min,
+        max,
+        mean: totals.iter().map(|&total| total as f64).sum::<f64>() / totals.len() as f64,
+        median,
+        distribution,
+    })
+}
+
+/// A failure to read a streamed inventory list.
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "{error}"),
+            StreamError::Parse(error) => write!(f, "{error}"),
+        }
+    }
 }
 
+impl Error for StreamError {}
+
+/// The total calories of each elf, summed while reading the inventories a line
+/// at a time, so that lists too large to hold in memory can be read.
+///
+/// Lines are normalized as [`preprocess::normalize`] does. Iteration stops
+/// after the first error.
+pub struct Totals<R> {
+    reader: R,
+    line: String,
+    line_index: usize,
+    failed: bool,
+}
+
+/// Returns the totals of the inventories read from `reader`.
+pub fn totals<R: BufRead>(reader: R) -> Totals<R> {
+    Totals {
+        reader,
+        line: String::new(),
+        line_index: 0,
+        failed: false,
+    }
+}
+
+impl<R: BufRead> Totals<R> {
+    /// Reads the next line, returning whether there was one.
+    fn read_line(&mut self) -> Result<bool, StreamError> {
+        self.line.clear();
+
+        let length = self
+            .reader
+            .read_line(&mut self.line)
+            .map_err(StreamError::Io)?;
+
+        if length == 0 {
+            return Ok(false);
+        }
+
+        if self.line_index == 0 && self.line.starts_with('\u{feff}') {
+            self.line.remove(0);
+        }
+
+        let length = self.line.trim_end().len();
+        self.line.truncate(length);
+
+        Ok(true)
+    }
+
+    fn next_total(&mut self) -> Result<Option<u64>, StreamError> {
+        let mut total = None;
+
+        while self.read_line()? {
+            let line_index = self.line_index;
+            self.line_index += 1;
+
+            let item = self.line.as_str();
+
+            if item.is_empty() {
+                if total.is_some() {
+                    break;
+                }
+
+                continue;
+            }
+
+            let error = |expected| {
+                StreamError::Parse(ParseError::at_token(DAY, line_index, item, item, expected))
+            };
+            let calories: u64 = item.parse().map_err(|_| error("a number of calories"))?;
+
+            total = Some(
+                total
+                    .unwrap_or(0u64)
+                    .checked_add(calories)
+                    .ok_or_else(|| error("calories that keep the total within 64 bits"))?,
+            );
+        }
+
+        Ok(total)
+    }
+}
+
+impl<R: BufRead> Iterator for Totals<R> {
+    type Item = Result<u64, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let total = self.next_total().transpose();
+        self.failed = matches!(total, Some(Err(_)));
+
+        total
+    }
+}
+
+/// Returns the `k` elves carrying the most calories, like [`top_elves`], reading
+/// the inventories from `reader` without keeping them.
+pub fn read_top_elves(reader: impl BufRead, k: usize) -> Result<Vec<Elf>, StreamError> {
+    let mut top = TopElves::new(k);
+
+    for (index, total) in totals(reader).enumerate() {
+        top.push(Elf {
+            index,
+            total: total?,
+        });
+    }
+
+    Ok(top.into_vec())
+}
+
+/// Returns the total calories carried by the elf carrying the most.
 #[aoc(day1, part1)]
-fn part1(calories: &[Vec<u32>]) -> u32 {
-    calories
-        .iter()
-        .map(|inventory| inventory.iter().sum())
-        .max()
-        .unwrap()
+pub fn part1(calories: &[Vec<u32>]) -> Option<u64> {
+    top_elves(calories, 1).first().map(|elf| elf.total)
 }
 
+/// Returns the total calories carried by the top three elves.
 #[aoc(day1, part2)]
-fn part2(calories: &[Vec<u32>]) -> u32 {
-    calories
-        .iter()
-        .map(|inventory| inventory.iter().sum())
-        .fold(vec![0, 0, 0], |mut top_three, calories| {
-            if calories > top_three[0] {
-                top_three[0] = calories;
-                top_three.sort_unstable();
-            }
+pub fn part2(calories: &[Vec<u32>]) -> u64 {
+    top_elves(calories, 3).iter().map(|elf| elf.total).sum()
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<Vec<u32>>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Calorie Counting"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
 
-            top_three
-        })
-        .iter()
-        .sum()
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_INPUT: &str = r"1000
 2000
@@ -60,11 +342,140 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 24_000);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), Some(24_000));
+    }
+
+    #[test]
+    fn no_elves() {
+        assert_eq!(part1(&parse("").unwrap()), None);
+        assert_eq!(part1(&parse("\n\n").unwrap()), None);
+        assert_eq!(part2(&parse("").unwrap()), 0);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            Some(24_000)
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 45_000);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 45_000);
+    }
+
+    #[test]
+    fn top_elves_example() {
+        let calories = parse(TEST_INPUT).unwrap();
+
+        assert_eq!(
+            top_elves(&calories, 2),
+            [
+                Elf {
+                    index: 3,
+                    total: 24_000
+                },
+                Elf {
+                    index: 2,
+                    total: 11_000
+                }
+            ]
+        );
+        assert_eq!(top_elves(&calories, 10).len(), 5);
+        assert!(top_elves(&calories, 0).is_empty());
+    }
+
+    #[test]
+    fn top_elves_beyond_the_elf_count() {
+        let calories = parse(TEST_INPUT).unwrap();
+        let all = top_elves(&calories, usize::MAX);
+
+        assert_eq!(
+            all.iter().map(|elf| elf.index).collect::<Vec<_>>(),
+            [3, 2, 4, 0, 1]
+        );
+        assert_eq!(
+            read_top_elves(TEST_INPUT.as_bytes(), usize::MAX).unwrap(),
+            all
+        );
+    }
+
+    #[test]
+    fn statistics_example() {
+        assert_eq!(
+            statistics(&parse(TEST_INPUT).unwrap(), NonZeroU64::new(5_000).unwrap()),
+            Some(Statistics {
+                elves: 5,
+                min: 4_000,
+                max: 24_000,
+                mean: 11_000.0,
+                median: 10_000.0,
+                distribution: BTreeMap::from([(0, 1), (5_000, 1), (10_000, 2), (20_000, 1)]),
+            })
+        );
+        assert_eq!(statistics(&[], NonZeroU64::new(5_000).unwrap()), None);
+    }
+
+    #[test]
+    fn streaming_example() {
+        assert_eq!(
+            totals(TEST_INPUT.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [6_000, 4_000, 11_000, 24_000, 10_000]
+        );
+        assert_eq!(
+            read_top_elves(preprocess::windows(TEST_INPUT).as_bytes(), 3).unwrap(),
+            top_elves(&parse(TEST_INPUT).unwrap(), 3)
+        );
+    }
+
+    #[test]
+    fn streaming_large_totals() {
+        let input = "18000000000000000000\n400000000000000000\n\n1\n";
+
+        assert_eq!(
+            totals(input.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [18_400_000_000_000_000_000, 1]
+        );
+    }
+
+    #[test]
+    fn streaming_errors() {
+        let errors = |input: &str| {
+            totals(input.as_bytes())
+                .filter_map(|total| match total {
+                    Err(StreamError::Parse(error)) => Some(error.to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors("1000\n\n20x0\n3000\n"),
+            ["day 1, line 3, column 1: expected a number of calories, found \"20x0\""]
+        );
+        assert_eq!(
+            errors("18446744073709551615\n1\n"),
+            ["day 1, line 2, column 1: expected calories that keep the total within 64 bits, found \"1\""]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn top_elves_are_the_first_sorted(
+            totals in prop::collection::vec(0..100u32, 0..50),
+            k in 0..60usize,
+        ) {
+            let calories: Vec<_> = totals.iter().map(|&total| vec![total]).collect();
+            let mut sorted: Vec<_> = elves(&calories).collect();
+            sorted.sort();
+            sorted.truncate(k);
+
+            prop_assert_eq!(top_elves(&calories, k), sorted);
+        }
     }
 }
@@ -1,92 +1,81 @@
+//! Day 2: Rock Paper Scissors.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 
+const DAY: u8 = 2;
+
+/// The opponent's column of the strategy guide: `A`, `B` or `C`.
 #[derive(Copy, Clone)]
-enum FirstColumnRecord {
+pub enum FirstColumnRecord {
     A,
     B,
     C,
 }
 
-impl From<char> for FirstColumnRecord {
-    fn from(first_column_record: char) -> Self {
+impl TryFrom<char> for FirstColumnRecord {
+    type Error = ();
+
+    fn try_from(first_column_record: char) -> Result<Self, Self::Error> {
         use FirstColumnRecord::*;
 
         match first_column_record {
-            'A' => A,
-            'B' => B,
-            'C' => C,
-            _ => unreachable!(),
+            'A' => Ok(A),
+            'B' => Ok(B),
+            'C' => Ok(C),
+            _ => Err(()),
         }
     }
 }
 
+/// Your column of the strategy guide: `X`, `Y` or `Z`.
 #[derive(Copy, Clone)]
-enum SecondColumnRecord {
+pub enum SecondColumnRecord {
     X,
     Y,
     Z,
 }
 
-impl From<char> for SecondColumnRecord {
-    fn from(second_column_record: char) -> Self {
+impl TryFrom<char> for SecondColumnRecord {
+    type Error = ();
+
+    fn try_from(second_column_record: char) -> Result<Self, Self::Error> {
         use SecondColumnRecord::*;
 
         match second_column_record {
-            'X' => X,
-            'Y' => Y,
-            'Z' => Z,
-            _ => unreachable!(),
+            'X' => Ok(X),
+            'Y' => Ok(Y),
+            'Z' => Ok(Z),
+            _ => Err(()),
         }
     }
 }
 
-struct RoundRecord(FirstColumnRecord, SecondColumnRecord);
-
-enum Shape {
-    Rock,
-    Paper,
-    Scissors,
-}
-
-impl From<FirstColumnRecord> for Shape {
-    fn from(first_column_record: FirstColumnRecord) -> Self {
-        use FirstColumnRecord::*;
-        use Shape::*;
-
-        match first_column_record {
-            A => Rock,
-            B => Paper,
-            C => Scissors,
-        }
+impl FirstColumnRecord {
+    /// The shape the opponent plays: `A` is the first shape of the game.
+    pub fn shape(self) -> Shape {
+        self as Shape
     }
 }
 
-impl From<SecondColumnRecord> for Shape {
-    fn from(second_column_record: SecondColumnRecord) -> Self {
-        use SecondColumnRecord::*;
-        use Shape::*;
-
-        match second_column_record {
-            X => Rock,
-            Y => Paper,
-            Z => Scissors,
-        }
+impl SecondColumnRecord {
+    /// The shape you play when the column is a shape: `X` is the first shape of the game.
+    pub fn shape(self) -> Shape {
+        self as Shape
     }
 }
 
-impl Shape {
-    fn score(&self) -> u32 {
-        use Shape::*;
+/// One line of the strategy guide.
+#[derive(Copy, Clone)]
+pub struct RoundRecord(pub FirstColumnRecord, pub SecondColumnRecord);
 
-        match self {
-            Rock => 1,
-            Paper => 2,
-            Scissors => 3,
-        }
-    }
-}
+/// A shape of a [`Game`], by its position in the game's list of shapes.
+pub type Shape = usize;
 
-enum RoundOutcome {
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RoundOutcome {
     Loss,
     Draw,
     Win,
@@ -105,95 +94,297 @@ impl From<SecondColumnRecord> for RoundOutcome {
     }
 }
 
-impl RoundOutcome {
-    fn score(&self) -> u32 {
-        use RoundOutcome::*;
+/// A game like rock paper scissors: every shape beats some of the others, and
+/// a round scores the shape you play plus the outcome for you.
+#[derive(Clone, Debug)]
+pub struct Game {
+    shape_scores: Vec<u32>,
+    /// The scores of a loss, a draw and a win.
+    outcome_scores: [u32; 3],
+    /// The outcome for you, indexed by your shape and then the opponent's.
+    outcomes: Vec<Vec<RoundOutcome>>,
+}
 
-        match self {
-            Loss => 0,
-            Draw => 3,
-            Win => 6,
-        }
+impl Game {
+    /// Returns the game where shape `a` beats shape `b` when `beats(a, b)`, and
+    /// shapes that do not beat each other draw, or `None` if a shape beats
+    /// itself or two shapes beat each other.
+    pub fn new(
+        shape_scores: Vec<u32>,
+        outcome_scores: [u32; 3],
+        beats: impl Fn(Shape, Shape) -> bool,
+    ) -> Option<Self> {
+        let shapes = shape_scores.len();
+        let outcomes = (0..shapes)
+            .map(|yours| {
+                (0..shapes)
+                    .map(
+                        |theirs| match (beats(yours, theirs), beats(theirs, yours)) {
+                            (true, true) => None,
+                            (true, false) => Some(RoundOutcome::Win),
+                            (false, true) => Some(RoundOutcome::Loss),
+                            (false, false) => Some(RoundOutcome::Draw),
+                        },
+                    )
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self {
+            shape_scores,
+            outcome_scores,
+            outcomes,
+        })
+    }
+
+    /// Returns the game where every shape beats the shapes `offsets` places
+    /// before it in `shape_scores`, counting round from the end.
+    pub fn cyclic(
+        shape_scores: Vec<u32>,
+        outcome_scores: [u32; 3],
+        offsets: &[usize],
+    ) -> Option<Self> {
+        let shapes = shape_scores.len();
+
+        Self::new(shape_scores, outcome_scores, |a, b| {
+            offsets.contains(&((a + shapes - b) % shapes))
+        })
+    }
+
+    /// Rock, paper and scissors, scoring 1, 2 and 3, with 0, 3 and 6 for a loss, a draw and a win.
+    pub fn rock_paper_scissors() -> Self {
+        Self::cyclic(vec![1, 2, 3], [0, 3, 6], &[1]).unwrap()
+    }
+
+    /// Rock, paper, scissors, Spock and lizard, scoring 1 to 5: each shape
+    /// beats the one before it and the one three before it.
+    pub fn rock_paper_scissors_spock_lizard() -> Self {
+        Self::cyclic(vec![1, 2, 3, 4, 5], [0, 3, 6], &[1, 3]).unwrap()
+    }
+
+    pub fn shapes(&self) -> usize {
+        self.shape_scores.len()
+    }
+
+    pub fn outcome(&self, yours: Shape, theirs: Shape) -> RoundOutcome {
+        self.outcomes[yours][theirs]
+    }
+
+    /// Returns the shape to play against `theirs` for the `outcome`, the highest
+    /// scoring if there are several, or `None` if no shape gives it.
+    pub fn shape_for(&self, theirs: Shape, outcome: RoundOutcome) -> Option<Shape> {
+        (0..self.shapes())
+            .filter(|&yours| self.outcome(yours, theirs) == outcome)
+            .max_by_key(|&yours| self.shape_scores[yours])
+    }
+
+    pub fn score(&self, yours: Shape, outcome: RoundOutcome) -> u32 {
+        self.shape_scores[yours] + self.outcome_scores[outcome as usize]
+    }
+
+    /// Returns your score for a round where you play `yours` against `theirs`.
+    pub fn play(&self, yours: Shape, theirs: Shape) -> u32 {
+        self.score(yours, self.outcome(yours, theirs))
+    }
+
+    /// Returns your score for a round against `theirs` that ends in `outcome`.
+    pub fn play_for(&self, theirs: Shape, outcome: RoundOutcome) -> Option<u32> {
+        self.shape_for(theirs, outcome)
+            .map(|yours| self.score(yours, outcome))
     }
 }
 
-fn round_outcome(opponent_shape: &Shape, your_shape: &Shape) -> RoundOutcome {
-    use RoundOutcome::*;
-    use Shape::*;
+/// What `X`, `Y` and `Z` stand for in a [`Decoding`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SecondColumn {
+    Shapes([Shape; 3]),
+    Outcomes([RoundOutcome; 3]),
+}
 
-    match (your_shape, opponent_shape) {
-        (Rock, Rock) => Draw,
-        (Rock, Paper) => Loss,
-        (Rock, Scissors) => Win,
-        (Paper, Rock) => Win,
-        (Paper, Paper) => Draw,
-        (Paper, Scissors) => Loss,
-        (Scissors, Rock) => Loss,
-        (Scissors, Paper) => Win,
-        (Scissors, Scissors) => Draw,
+/// An interpretation of the strategy guide: the shapes `A`, `B` and `C` stand
+/// for, and what `X`, `Y` and `Z` stand for.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Decoding {
+    pub first_column: [Shape; 3],
+    pub second_column: SecondColumn,
+}
+
+impl Decoding {
+    /// Returns the total score of the guide, or `None` if one of its rounds
+    /// asks for an outcome no shape of the game gives.
+    pub fn score(&self, game: &Game, strategy_guide: &[RoundRecord]) -> Option<u32> {
+        strategy_guide
+            .iter()
+            .map(|&RoundRecord(opponent, you)| {
+                let theirs = self.first_column[opponent.shape()];
+
+                match self.second_column {
+                    SecondColumn::Shapes(shapes) => Some(game.play(shapes[you as usize], theirs)),
+                    SecondColumn::Outcomes(outcomes) => {
+                        game.play_for(theirs, outcomes[you as usize])
+                    }
+                }
+            })
+            .sum()
     }
 }
 
-fn your_shape(opponent_shape: &Shape, round_outcome: &RoundOutcome) -> Shape {
+/// Returns the ways to give three letters distinct values out of `values`.
+fn arrangements<T: Copy>(values: &[T]) -> Vec<[T; 3]> {
+    let indices = 0..values.len();
+
+    indices
+        .clone()
+        .flat_map(|a| indices.clone().map(move |b| (a, b)))
+        .flat_map(|(a, b)| indices.clone().map(move |c| [a, b, c]))
+        .filter(|[a, b, c]| a != b && b != c && a != c)
+        .map(|indices| indices.map(|index| values[index]))
+        .collect()
+}
+
+/// Returns every decoding of the guide for `game`: the letters of each column
+/// stand for distinct shapes, or for distinct outcomes in the second column.
+pub fn decodings(game: &Game) -> Vec<Decoding> {
     use RoundOutcome::*;
-    use Shape::*;
 
-    match (opponent_shape, round_outcome) {
-        (Rock, Loss) => Scissors,
-        (Rock, Draw) => Rock,
-        (Rock, Win) => Paper,
-        (Paper, Loss) => Rock,
-        (Paper, Draw) => Paper,
-        (Paper, Win) => Scissors,
-        (Scissors, Loss) => Paper,
-        (Scissors, Draw) => Scissors,
-        (Scissors, Win) => Rock,
-    }
+    let shapes: Vec<Shape> = (0..game.shapes()).collect();
+    let second_columns: Vec<SecondColumn> = arrangements(&shapes)
+        .into_iter()
+        .map(SecondColumn::Shapes)
+        .chain(
+            arrangements(&[Loss, Draw, Win])
+                .into_iter()
+                .map(SecondColumn::Outcomes),
+        )
+        .collect();
+
+    arrangements(&shapes)
+        .into_iter()
+        .flat_map(|first_column| {
+            second_columns.iter().map(move |&second_column| Decoding {
+                first_column,
+                second_column,
+            })
+        })
+        .collect()
 }
 
-fn total_score(your_shape: &Shape, round_outcome: &RoundOutcome) -> u32 {
-    your_shape.score() + round_outcome.score()
+/// Returns the total score of the guide under every decoding for `game` that
+/// can be played, in the order of [`decodings`].
+pub fn decoding_scores(game: &Game, strategy_guide: &[RoundRecord]) -> Vec<(Decoding, u32)> {
+    decodings(game)
+        .into_iter()
+        .filter_map(|decoding| {
+            decoding
+                .score(game, strategy_guide)
+                .map(|score| (decoding, score))
+        })
+        .collect()
 }
 
+/// Parses the encrypted strategy guide.
 #[aoc_generator(day2)]
-fn parse_input(input: &str) -> Vec<RoundRecord> {
+pub fn parse(input: &str) -> Result<Vec<RoundRecord>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|round_record| {
-            RoundRecord(
-                round_record.chars().next().unwrap().into(),
-                round_record.chars().nth(2).unwrap().into(),
-            )
+        .enumerate()
+        .map(|(line_index, round_record)| {
+            let mut columns = round_record.split_ascii_whitespace();
+
+            let first_column = columns.next().ok_or_else(|| {
+                ParseError::at_end_of_line(DAY, line_index, round_record, "one of A, B, C")
+            })?;
+            let second_column = columns.next().ok_or_else(|| {
+                ParseError::at_end_of_line(DAY, line_index, round_record, "one of X, Y, Z")
+            })?;
+
+            if let Some(token) = columns.next() {
+                return Err(ParseError::at_token(
+                    DAY,
+                    line_index,
+                    round_record,
+                    token,
+                    "end of line",
+                ));
+            }
+
+            Ok(RoundRecord(
+                parse_column(first_column, line_index, round_record, "one of A, B, C")?,
+                parse_column(second_column, line_index, round_record, "one of X, Y, Z")?,
+            ))
         })
         .collect()
 }
 
+fn parse_column<T: TryFrom<char>>(
+    token: &str,
+    line_index: usize,
+    line: &str,
+    expected: &str,
+) -> Result<T, ParseError> {
+    let mut chars = token.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(column_record), None) => column_record
+            .try_into()
+            .map_err(|_| ParseError::at_token(DAY, line_index, line, token, expected)),
+        _ => Err(ParseError::at_token(DAY, line_index, line, token, expected)),
+    }
+}
+
+/// Returns the total score when the second column is the shape you play.
 #[aoc(day2, part1)]
-fn part1(strategy_guide: &[RoundRecord]) -> u32 {
+pub fn part1(strategy_guide: &[RoundRecord]) -> u32 {
+    let game = Game::rock_paper_scissors();
+
     strategy_guide
         .iter()
-        .map(|round_record| {
-            let opponent_shape: Shape = round_record.0.into();
-            let your_shape: Shape = round_record.1.into();
-
-            total_score(&your_shape, &round_outcome(&opponent_shape, &your_shape))
-        })
+        .map(|RoundRecord(opponent, you)| game.play(you.shape(), opponent.shape()))
         .sum()
 }
 
+/// Returns the total score when the second column is the round outcome you need.
 #[aoc(day2, part2)]
-fn part2(strategy_guide: &[RoundRecord]) -> u32 {
+pub fn part2(strategy_guide: &[RoundRecord]) -> u32 {
+    let game = Game::rock_paper_scissors();
+
     strategy_guide
         .iter()
-        .map(|round_record| {
-            let opponent_shape: Shape = round_record.0.into();
-            let round_outcome: RoundOutcome = round_record.1.into();
-
-            total_score(&your_shape(&opponent_shape, &round_outcome), &round_outcome)
+        .map(|RoundRecord(opponent, you)| {
+            game.play_for(opponent.shape(), (*you).into())
+                .expect("every outcome has a shape in rock paper scissors")
         })
         .sum()
 }
 
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<RoundRecord>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,11 +395,112 @@ C Z";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 15);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 15);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 15);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 12);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 12);
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            parse("A Y\nB Q\nC Z").err(),
+            Some(ParseError::new(2, 2, 3, "Q", "one of X, Y, Z"))
+        );
+        assert_eq!(
+            parse("A Y\nB").err(),
+            Some(ParseError::new(2, 2, 2, "", "one of X, Y, Z"))
+        );
+    }
+
+    #[test]
+    fn rock_paper_scissors() {
+        use RoundOutcome::*;
+
+        let game = Game::rock_paper_scissors();
+        let (rock, paper, scissors) = (0, 1, 2);
+
+        assert_eq!(game.outcome(paper, rock), Win);
+        assert_eq!(game.outcome(scissors, paper), Win);
+        assert_eq!(game.outcome(rock, scissors), Win);
+        assert_eq!(game.outcome(rock, paper), Loss);
+        assert_eq!(game.outcome(scissors, scissors), Draw);
+        assert_eq!(game.shape_for(rock, Loss), Some(scissors));
+    }
+
+    #[test]
+    fn rock_paper_scissors_spock_lizard() {
+        use RoundOutcome::*;
+
+        let game = Game::rock_paper_scissors_spock_lizard();
+        let (rock, paper, scissors, spock, lizard) = (0, 1, 2, 3, 4);
+
+        for (winner, loser) in [
+            (scissors, paper),
+            (paper, rock),
+            (rock, lizard),
+            (lizard, spock),
+            (spock, scissors),
+            (scissors, lizard),
+            (lizard, paper),
+            (paper, spock),
+            (spock, rock),
+            (rock, scissors),
+        ] {
+            assert_eq!(game.outcome(winner, loser), Win);
+            assert_eq!(game.outcome(loser, winner), Loss);
+        }
+
+        assert_eq!(game.shape_for(rock, Win), Some(spock));
+        assert_eq!(game.play_for(rock, Win), Some(10));
+        assert_eq!(game.play(lizard, lizard), 8);
+    }
+
+    #[test]
+    fn invalid_games() {
+        assert!(Game::cyclic(vec![1, 2, 3], [0, 3, 6], &[0]).is_none());
+        assert!(Game::cyclic(vec![1, 2, 3, 4], [0, 3, 6], &[1, 3]).is_none());
+        assert!(Game::new(vec![1, 2], [0, 3, 6], |_, _| false).is_some());
+    }
+
+    #[test]
+    fn decoding_scores_example() {
+        use RoundOutcome::*;
+
+        let game = Game::rock_paper_scissors();
+        let scores = decoding_scores(&game, &parse(TEST_INPUT).unwrap());
+        let score = |second_column| {
+            scores.iter().find_map(|&(decoding, score)| {
+                (decoding
+                    == Decoding {
+                        first_column: [0, 1, 2],
+                        second_column,
+                    })
+                .then_some(score)
+            })
+        };
+
+        assert_eq!(scores.len(), 72);
+        assert_eq!(score(SecondColumn::Shapes([0, 1, 2])), Some(15));
+        assert_eq!(score(SecondColumn::Outcomes([Loss, Draw, Win])), Some(12));
+        assert_eq!(scores.iter().map(|&(_, score)| score).max(), Some(24));
+        assert_eq!(scores.iter().map(|&(_, score)| score).min(), Some(6));
+    }
+
+    #[test]
+    fn decodings_of_larger_games() {
+        let game = Game::rock_paper_scissors_spock_lizard();
+        let scores = decoding_scores(&game, &parse(TEST_INPUT).unwrap());
+
+        assert_eq!(decodings(&game).len(), 60 * (60 + 6));
+        assert_eq!(scores.len(), decodings(&game).len());
+        assert_eq!(scores.iter().map(|&(_, score)| score).max(), Some(30));
     }
 }
@@ -1,11 +1,22 @@
+//! Day 3: Rucksack Reorganization.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashSet;
 
-type ItemType = char;
-struct Rucksack(Vec<ItemType>);
+const DAY: u8 = 3;
+
+pub type ItemType = char;
+/// The item types in a rucksack, first compartment followed by the second one.
+pub struct Rucksack(pub Vec<ItemType>);
 
 const LOWERCASE_A_PRIORITY: u32 = 1;
 const UPPERCASE_A_PRIORITY: u32 = 27;
+const PRIORITIES: u32 = 52;
+
+/// The size of the groups whose badges part 2 looks for.
+pub const GROUP_SIZE: usize = 3;
 
 fn priority(item_type: &ItemType) -> u32 {
     if item_type.is_ascii_lowercase() {
@@ -15,58 +26,214 @@ fn priority(item_type: &ItemType) -> u32 {
     }
 }
 
+fn item_type(priority: u32) -> ItemType {
+    let (first, first_priority) = if priority < UPPERCASE_A_PRIORITY {
+        ('a', LOWERCASE_A_PRIORITY)
+    } else {
+        ('A', UPPERCASE_A_PRIORITY)
+    };
+
+    char::from_u32(first as u32 + priority - first_priority).unwrap()
+}
+
+/// A set of item types, with one bit per item type: bit 0 for `a` up to bit 51 for `Z`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub const EMPTY: Self = Self(0);
+    pub const ALL: Self = Self((1 << PRIORITIES) - 1);
+
+    fn bit(item_type: ItemType) -> Option<u64> {
+        item_type
+            .is_ascii_alphabetic()
+            .then(|| 1 << (priority(&item_type) - 1))
+    }
+
+    /// Adds `item_type`, or returns `false` if it is not an item type letter.
+    pub fn insert(&mut self, item_type: ItemType) -> bool {
+        match Self::bit(item_type) {
+            Some(bit) => {
+                self.0 |= bit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, item_type: ItemType) -> bool {
+        Self::bit(item_type).is_some_and(|bit| self.0 & bit != 0)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the item types in order of priority.
+    pub fn iter(&self) -> impl Iterator<Item = ItemType> + '_ {
+        (0..PRIORITIES)
+            .filter(|bit| self.0 & 1 << bit != 0)
+            .map(|bit| item_type(bit + 1))
+    }
+
+    pub fn priority_sum(&self) -> u32 {
+        (0..PRIORITIES)
+            .filter(|bit| self.0 & 1 << bit != 0)
+            .map(|bit| bit + 1)
+            .sum()
+    }
+}
+
+/// Collects the item type letters, leaving out any other characters.
+impl FromIterator<ItemType> for ItemSet {
+    fn from_iter<T: IntoIterator<Item = ItemType>>(item_types: T) -> Self {
+        let mut item_set = Self::EMPTY;
+
+        for item_type in item_types {
+            item_set.insert(item_type);
+        }
+
+        item_set
+    }
+}
+
+impl Rucksack {
+    pub fn item_types(&self) -> ItemSet {
+        self.0.iter().copied().collect()
+    }
+
+    pub fn compartments(&self) -> (ItemSet, ItemSet) {
+        let (first, second) = self.0.split_at(self.0.len() / 2);
+
+        (
+            first.iter().copied().collect(),
+            second.iter().copied().collect(),
+        )
+    }
+}
+
+/// Parses one rucksack per line.
 #[aoc_generator(day3)]
-fn parse_input(input: &str) -> Vec<Rucksack> {
+pub fn parse(input: &str) -> Result<Vec<Rucksack>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|rucksack| Rucksack(rucksack.chars().collect()))
+        .enumerate()
+        .map(|(line_index, rucksack)| {
+            rucksack
+                .chars()
+                .enumerate()
+                .map(|(char_index, item_type)| {
+                    if item_type.is_ascii_alphabetic() {
+                        Ok(item_type)
+                    } else {
+                        Err(ParseError::at_char(
+                            DAY,
+                            line_index,
+                            char_index,
+                            item_type,
+                            "an item type letter",
+                        ))
+                    }
+                })
+                .collect::<Result<_, _>>()
+                .map(Rucksack)
+        })
         .collect()
 }
 
-#[aoc(day3, part1)]
-fn part1(rucksacks: &[Rucksack]) -> u32 {
+/// Returns the item types found in both compartments of each rucksack.
+pub fn misplaced_item_types(rucksacks: &[Rucksack]) -> Vec<ItemSet> {
     rucksacks
         .iter()
         .map(|rucksack| {
-            let first_compartment_item_types: HashSet<_> =
-                rucksack.0[..rucksack.0.len() / 2].iter().copied().collect();
-            let second_compartment_item_types: HashSet<_> =
-                rucksack.0[rucksack.0.len() / 2..].iter().copied().collect();
-
-            priority(
-                first_compartment_item_types
-                    .intersection(&second_compartment_item_types)
-                    .next()
-                    .unwrap(),
-            )
+            let (first, second) = rucksack.compartments();
+            first.intersection(&second)
         })
-        .sum()
+        .collect()
 }
 
-#[aoc(day3, part2)]
-fn part2(rucksacks: &[Rucksack]) -> u32 {
-    rucksacks
-        .chunks_exact(3)
+/// Returns the item types carried by every elf of each group of `group_size`
+/// consecutive elves, or `None` if `group_size` is 0. Elves left over after
+/// the last full group are not counted.
+pub fn badges(rucksacks: &[Rucksack], group_size: usize) -> Option<Vec<ItemSet>> {
+    if group_size == 0 {
+        return None;
+    }
+
+    let badges = rucksacks
+        .chunks_exact(group_size)
         .map(|group| {
-            priority(
-                group
-                    .iter()
-                    .map(|rucksack| rucksack.0.iter().copied().collect::<HashSet<_>>())
-                    .reduce(|common_item_types, rucksack_item_types| {
-                        common_item_types
-                            .intersection(&rucksack_item_types)
-                            .copied()
-                            .collect()
-                    })
-                    .unwrap()
-                    .iter()
-                    .next()
-                    .unwrap(),
-            )
+            group
+                .iter()
+                .map(Rucksack::item_types)
+                .fold(ItemSet::ALL, |common, item_types| {
+                    common.intersection(&item_types)
+                })
         })
+        .collect();
+
+    Some(badges)
+}
+
+/// Returns the sum of priorities of the item types found in both compartments.
+#[aoc(day3, part1)]
+pub fn part1(rucksacks: &[Rucksack]) -> u32 {
+    misplaced_item_types(rucksacks)
+        .iter()
+        .map(ItemSet::priority_sum)
+        .sum()
+}
+
+/// Returns the sum of priorities of the badges of each group of three elves.
+#[aoc(day3, part2)]
+pub fn part2(rucksacks: &[Rucksack]) -> u32 {
+    badges(rucksacks, GROUP_SIZE)
+        .expect("groups are not empty")
+        .iter()
+        .map(ItemSet::priority_sum)
         .sum()
 }
 
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Vec<Rucksack>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,11 +247,68 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 157);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 157);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            157
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 70);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 70);
+    }
+
+    #[test]
+    fn item_sets() {
+        let first: ItemSet = "vJrwpWtwJgWr".chars().collect();
+        let second: ItemSet = "hcsFMMfFFhFp".chars().collect();
+
+        assert_eq!(first.len(), 8);
+        assert!(first.contains('J') && !first.contains('h') && !first.contains('1'));
+        assert_eq!(first.intersection(&second).iter().collect::<String>(), "p");
+        assert_eq!(
+            first.union(&second).iter().collect::<String>(),
+            "cfghprstvwFJMW"
+        );
+        assert_eq!(ItemSet::ALL.len(), 52);
+        assert_eq!(ItemSet::ALL.priority_sum(), (1..=52).sum::<u32>());
+        assert_eq!(ItemSet::ALL.iter().collect::<ItemSet>(), ItemSet::ALL);
+        assert!(ItemSet::EMPTY.is_empty());
+    }
+
+    #[test]
+    fn other_characters_are_not_item_types() {
+        let mut item_set = ItemSet::EMPTY;
+
+        assert!(item_set.insert('k'));
+        assert!(!item_set.insert('1'));
+        assert!(!item_set.insert('!'));
+        assert!(!item_set.insert('~'));
+        assert_eq!(item_set, "k".chars().collect());
+        assert_eq!("a1~!Z".chars().collect::<ItemSet>().len(), 2);
+    }
+
+    #[test]
+    fn badges_example() {
+        let rucksacks = parse(TEST_INPUT).unwrap();
+        let badges = |group_size| {
+            badges(&rucksacks, group_size).map(|badges| {
+                badges
+                    .iter()
+                    .map(|badges| badges.iter().collect::<String>())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(badges(3).unwrap(), ["r", "Z"]);
+        assert_eq!(badges(2).unwrap(), ["frsFM", "qvwBT", "GJZ"]);
+        assert_eq!(badges(6).unwrap(), [""]);
+        assert!(badges(7).unwrap().is_empty());
+        assert_eq!(badges(0), None);
     }
 }
@@ -1,35 +1,64 @@
+//! Day 4: Camp Cleanup.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 
-struct Assignment {
-    start_section_id: u32,
-    end_section_id: u32,
+const DAY: u8 = 4;
+
+/// An inclusive range of section IDs assigned to an elf.
+pub struct Assignment {
+    pub start_section_id: u32,
+    pub end_section_id: u32,
 }
 
+/// Parses one pair of section assignments per line.
 #[aoc_generator(day4)]
-fn parse_input(input: &str) -> Vec<(Assignment, Assignment)> {
+pub fn parse(input: &str) -> Result<Vec<(Assignment, Assignment)>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|assignment_pair| {
-            let assignment_pair = assignment_pair.split_once(',').unwrap();
-            let first_assignment = assignment_pair.0.split_once('-').unwrap();
-            let second_assignment = assignment_pair.1.split_once('-').unwrap();
-
-            (
-                Assignment {
-                    start_section_id: first_assignment.0.parse().unwrap(),
-                    end_section_id: first_assignment.1.parse().unwrap(),
-                },
-                Assignment {
-                    start_section_id: second_assignment.0.parse().unwrap(),
-                    end_section_id: second_assignment.1.parse().unwrap(),
-                },
-            )
+        .enumerate()
+        .map(|(line_index, assignment_pair)| {
+            let (first_assignment, second_assignment) =
+                assignment_pair.split_once(',').ok_or_else(|| {
+                    ParseError::at_end_of_line(DAY, line_index, assignment_pair, "','")
+                })?;
+
+            Ok((
+                parse_assignment(first_assignment, line_index, assignment_pair)?,
+                parse_assignment(second_assignment, line_index, assignment_pair)?,
+            ))
         })
         .collect()
 }
 
+fn parse_assignment(
+    assignment: &str,
+    line_index: usize,
+    line: &str,
+) -> Result<Assignment, ParseError> {
+    let (start_section_id, end_section_id) = assignment.split_once('-').ok_or_else(|| {
+        ParseError::at_token(DAY, line_index, line, assignment, "a section range")
+    })?;
+
+    let parse_section_id = |section_id: &str| {
+        section_id
+            .parse()
+            .map_err(|_| ParseError::at_token(DAY, line_index, line, section_id, "a section ID"))
+    };
+
+    Ok(Assignment {
+        start_section_id: parse_section_id(start_section_id)?,
+        end_section_id: parse_section_id(end_section_id)?,
+    })
+}
+
+/// Returns the number of pairs where one assignment fully contains the other.
 #[aoc(day4, part1)]
-fn part1(assignment_pairs: &[(Assignment, Assignment)]) -> usize {
+pub fn part1(assignment_pairs: &[(Assignment, Assignment)]) -> usize {
     assignment_pairs
         .iter()
         .filter(|&assignment_pair| {
@@ -41,8 +70,9 @@ fn part1(assignment_pairs: &[(Assignment, Assignment)]) -> usize {
         .count()
 }
 
+/// Returns the number of pairs with overlapping assignments.
 #[aoc(day4, part2)]
-fn part2(assignment_pairs: &[(Assignment, Assignment)]) -> usize {
+pub fn part2(assignment_pairs: &[(Assignment, Assignment)]) -> usize {
     assignment_pairs
         .iter()
         .filter(|&assignment_pair| {
@@ -52,6 +82,32 @@ fn part2(assignment_pairs: &[(Assignment, Assignment)]) -> usize {
         .count()
 }
 
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Vec<(Assignment, Assignment)>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Camp Cleanup"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,11 +121,16 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 2);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 2);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 2);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 4);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 4);
     }
 }
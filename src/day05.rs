@@ -1,25 +1,61 @@
+//! Day 5: Supply Stacks.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 
-type Crate = char;
-type Stack = Vec<Crate>;
+const DAY: u8 = 5;
+
+pub type Crate = char;
+pub type Stack = Vec<Crate>;
 
-struct RearrangementStep {
-    moved_count: usize,
-    from: usize,
-    to: usize,
+/// Moves `moved_count` crates from stack `from` to stack `to`, numbered from 1.
+pub struct RearrangementStep {
+    pub moved_count: usize,
+    pub from: usize,
+    pub to: usize,
 }
 
+fn is_stack_numbers(line: &str) -> bool {
+    !line.trim().is_empty()
+        && line
+            .split_ascii_whitespace()
+            .all(|token| token.parse::<usize>().is_ok())
+}
+
+/// Parses the stacks drawing and the rearrangement procedure.
+///
+/// Stacks are indexed by their number, so the stack at index 0 is always empty.
 #[aoc_generator(day5)]
-fn parse_input(input: &str) -> (Vec<Stack>, Vec<RearrangementStep>) {
-    let (stacks, rearrangement_procedure) = input.split_once("\n\n").unwrap();
+pub fn parse(input: &str) -> Result<(Vec<Stack>, Vec<RearrangementStep>), ParseError> {
+    let input = &preprocess::normalize_drawing(input);
 
-    let layers: Vec<_> = stacks.lines().collect();
+    let lines: Vec<_> = input.lines().collect();
+    let separator_index = match lines.iter().position(|line| line.is_empty()) {
+        Some(separator_index) => separator_index,
+        // Without a rearrangement procedure the input ends with the stack numbers.
+        None if lines.last().is_some_and(|line| is_stack_numbers(line)) => lines.len(),
+        None => {
+            return Err(ParseError::at_end_of_line(
+                DAY,
+                lines.len().saturating_sub(1),
+                lines.last().unwrap_or(&""),
+                "a blank line after the stacks drawing",
+            ))
+        }
+    };
 
-    let last_stack_number = layers[0].len() / 4 + 1;
-    let mut stacks: Vec<Stack> = vec![Vec::with_capacity(layers.len() - 1); last_stack_number + 1];
+    if separator_index == 0 {
+        return Err(ParseError::at_end_of_line(DAY, 0, "", "a stacks drawing"));
+    }
+
+    let layers = &lines[..separator_index - 1];
+    let last_stack_number = lines[separator_index - 1].split_ascii_whitespace().count();
+    let mut stacks: Vec<Stack> = vec![Vec::with_capacity(layers.len()); last_stack_number + 1];
 
-    for layer_number in (0..layers.len() - 1).rev() {
-        let layer = layers[layer_number];
+    for (layer_index, layer) in layers.iter().enumerate().rev() {
+        let layer: Vec<_> = layer.chars().collect();
 
         for (stack_number, stack) in stacks
             .iter_mut()
@@ -27,34 +63,97 @@ fn parse_input(input: &str) -> (Vec<Stack>, Vec<RearrangementStep>) {
             .skip(1)
             .take(last_stack_number)
         {
-            let supply_crate = layer.chars().nth((stack_number - 1) * 4 + 1).unwrap();
+            let char_index = (stack_number - 1) * 4 + 1;
 
-            if !supply_crate.is_ascii_whitespace() {
-                stack.push(supply_crate);
+            match layer.get(char_index) {
+                Some(supply_crate) if supply_crate.is_ascii_alphabetic() => {
+                    stack.push(*supply_crate)
+                }
+                Some(supply_crate) if !supply_crate.is_ascii_whitespace() => {
+                    return Err(ParseError::at_char(
+                        DAY,
+                        layer_index,
+                        char_index,
+                        *supply_crate,
+                        "a crate letter",
+                    ))
+                }
+                _ => (),
             }
         }
     }
 
-    let rearrangement_procedure = rearrangement_procedure
-        .lines()
-        .map(|rearrangement_step| {
-            let mut numbers = rearrangement_step
-                .split_ascii_whitespace()
-                .filter_map(|token| token.parse().ok());
-
-            RearrangementStep {
-                moved_count: numbers.next().unwrap(),
-                from: numbers.next().unwrap(),
-                to: numbers.next().unwrap(),
+    let rearrangement_procedure = lines
+        .iter()
+        .enumerate()
+        .skip(separator_index + 1)
+        .filter(|(_, rearrangement_step)| !rearrangement_step.is_empty())
+        .map(|(line_index, rearrangement_step)| {
+            let mut tokens = rearrangement_step.split_ascii_whitespace();
+            let mut number_after = |keyword: &str| {
+                match tokens.next() {
+                    Some(token) if token == keyword => (),
+                    Some(token) => {
+                        return Err(ParseError::at_token(
+                            DAY,
+                            line_index,
+                            rearrangement_step,
+                            token,
+                            format!("{keyword:?}"),
+                        ))
+                    }
+                    None => {
+                        return Err(ParseError::at_end_of_line(
+                            DAY,
+                            line_index,
+                            rearrangement_step,
+                            format!("{keyword:?}"),
+                        ))
+                    }
+                }
+
+                let token = tokens.next().ok_or_else(|| {
+                    ParseError::at_end_of_line(DAY, line_index, rearrangement_step, "a number")
+                })?;
+
+                token
+                    .parse::<usize>()
+                    .map(|number| (token, number))
+                    .map_err(|_| {
+                        ParseError::at_token(DAY, line_index, rearrangement_step, token, "a number")
+                    })
+            };
+
+            let (_, moved_count) = number_after("move")?;
+            let from = number_after("from")?;
+            let to = number_after("to")?;
+
+            for (token, stack_number) in [from, to] {
+                if !(1..=last_stack_number).contains(&stack_number) {
+                    return Err(ParseError::at_token(
+                        DAY,
+                        line_index,
+                        rearrangement_step,
+                        token,
+                        format!("a stack number from 1 to {last_stack_number}"),
+                    ));
+                }
             }
+
+            Ok(RearrangementStep {
+                moved_count,
+                from: from.1,
+                to: to.1,
+            })
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    (stacks, rearrangement_procedure)
+    Ok((stacks, rearrangement_procedure))
 }
 
+/// Returns the top crates after moving crates one at a time.
 #[aoc(day5, part1)]
-fn part1((stacks, rearrangement_procedure): &(Vec<Stack>, Vec<RearrangementStep>)) -> String {
+pub fn part1((stacks, rearrangement_procedure): &(Vec<Stack>, Vec<RearrangementStep>)) -> String {
     let mut stacks = stacks.to_owned();
 
     for rearrangement_step in rearrangement_procedure {
@@ -70,8 +169,9 @@ fn part1((stacks, rearrangement_procedure): &(Vec<Stack>, Vec<RearrangementStep>
         .collect()
 }
 
+/// Returns the top crates after moving crates several at once.
 #[aoc(day5, part2)]
-fn part2((stacks, rearrangement_procedure): &(Vec<Stack>, Vec<RearrangementStep>)) -> String {
+pub fn part2((stacks, rearrangement_procedure): &(Vec<Stack>, Vec<RearrangementStep>)) -> String {
     let mut stacks = stacks.to_owned();
 
     for rearrangement_step in rearrangement_procedure {
@@ -88,6 +188,32 @@ fn part2((stacks, rearrangement_procedure): &(Vec<Stack>, Vec<RearrangementStep>
         .collect()
 }
 
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = (Vec<Stack>, Vec<RearrangementStep>);
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Supply Stacks"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,11 +230,36 @@ move 1 from 1 to 2";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), "CMZ".to_string());
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), "CMZ".to_string());
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            "CMZ".to_string()
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), "MCD".to_string());
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), "MCD".to_string());
+    }
+
+    #[test]
+    fn no_rearrangement_procedure() {
+        let drawing = &TEST_INPUT[..TEST_INPUT.find("\n\n").unwrap()];
+
+        assert_eq!(part1(&parse(drawing).unwrap()), "NDP".to_string());
+        assert_eq!(
+            parse("    [D]    \n[N] [C]    ").err(),
+            Some(ParseError::new(
+                5,
+                2,
+                12,
+                "",
+                "a blank line after the stacks drawing"
+            ))
+        );
     }
 }
@@ -1,10 +1,21 @@
-use aoc_runner_derive::aoc;
+//! Day 6: Tuning Trouble.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
+use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashMap;
 
+const DAY: u8 = 6;
+
 const START_OF_PACKET_MARKER_LEN: usize = 4;
 const START_OF_MESSAGE_MARKER_LEN: usize = 14;
 
 fn find_marker(datastream_buffer: &str, marker_len: usize) -> Option<usize> {
+    if datastream_buffer.len() < marker_len {
+        return None;
+    }
+
     let mut character_frequencies: HashMap<_, _> = HashMap::with_capacity(marker_len);
 
     for index in 0..marker_len {
@@ -42,16 +53,81 @@ fn find_marker(datastream_buffer: &str, marker_len: usize) -> Option<usize> {
     Some(index)
 }
 
+/// Checks that the datastream buffer is a single line of lowercase letters.
+#[aoc_generator(day6)]
+pub fn parse(input: &str) -> Result<String, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let mut lines = input.lines().enumerate();
+    let (line_index, datastream_buffer) = lines
+        .next()
+        .ok_or_else(|| ParseError::at_end_of_line(DAY, 0, "", "a datastream buffer"))?;
+
+    if let Some((line_index, line)) = lines.find(|(_, line)| !line.is_empty()) {
+        return Err(ParseError::at_token(
+            DAY,
+            line_index,
+            line,
+            line,
+            "end of input",
+        ));
+    }
+
+    if let Some((char_index, character)) = datastream_buffer
+        .chars()
+        .enumerate()
+        .find(|(_, character)| !character.is_ascii_lowercase())
+    {
+        return Err(ParseError::at_char(
+            DAY,
+            line_index,
+            char_index,
+            character,
+            "a lowercase letter",
+        ));
+    }
+
+    Ok(datastream_buffer.to_string())
+}
+
+/// Returns the number of characters processed before the first start-of-packet marker.
 #[aoc(day6, part1)]
-fn part1(datastream_buffer: &str) -> Option<usize> {
+pub fn part1(datastream_buffer: &str) -> Option<usize> {
     find_marker(datastream_buffer, START_OF_PACKET_MARKER_LEN)
 }
 
+/// Returns the number of characters processed before the first start-of-message marker.
 #[aoc(day6, part2)]
-fn part2(datastream_buffer: &str) -> Option<usize> {
+pub fn part2(datastream_buffer: &str) -> Option<usize> {
     find_marker(datastream_buffer, START_OF_MESSAGE_MARKER_LEN)
 }
 
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = String;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Tuning Trouble"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,6 +147,14 @@ mod tests {
         assert_eq!(part1(TEST_INPUT_5), Some(11));
     }
 
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT_1)).unwrap()),
+            Some(7)
+        );
+    }
+
     #[test]
     fn part2_example() {
         assert_eq!(part2(TEST_INPUT_1), Some(19));
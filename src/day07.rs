@@ -1,67 +1,94 @@
+//! Day 7: No Space Left On Device.
+
 use crate::day07::CdArgument::*;
 use crate::day07::Command::*;
 use crate::day07::FileSystemObject::*;
 use crate::day07::OutputLine::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashMap;
 
-type FileSystemName = String;
+pub type FileSystemName = String;
 type Path = Vec<FileSystemName>;
 
+const DAY: u8 = 7;
+
 static ROOT: &str = "/";
 const SMALL_DIR_SIZE_LIMIT: u32 = 100_000;
 const TOTAL_DISK_SPACE: u32 = 70_000_000;
 const UNUSED_DISK_SPACE_REQUIRED: u32 = 30_000_000;
 
+/// An entry listed by `ls`.
 #[derive(Clone)]
-enum FileSystemObject {
+pub enum FileSystemObject {
     Dir,
     File { size: u32 },
 }
 
-enum CdArgument {
+pub enum CdArgument {
     In(FileSystemName),
     Out,
 }
 
-enum Command {
+pub enum Command {
     List,
     ChangeDirectory(CdArgument),
 }
 
-enum OutputLine {
+/// A line of the terminal output: either a command or a line it printed.
+pub enum OutputLine {
     ExecutedCommand(Command),
     ResultLine(FileSystemObject),
 }
 
+/// Parses the terminal output, one line at a time.
 #[aoc_generator(day7)]
-fn parse_input(input: &str) -> Vec<OutputLine> {
+pub fn parse(input: &str) -> Result<Vec<OutputLine>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|output_line| {
+        .enumerate()
+        .map(|(line_index, output_line)| {
             let mut tokens = output_line.split_ascii_whitespace();
-            match tokens.next().unwrap() {
+            let mut next_token = |expected: &str| {
+                tokens.next().ok_or_else(|| {
+                    ParseError::at_end_of_line(DAY, line_index, output_line, expected)
+                })
+            };
+
+            match next_token("a command or a directory listing entry")? {
                 "$" => {
-                    let command = tokens.next().unwrap();
+                    let command = next_token("a command")?;
 
                     match command {
                         "cd" => {
-                            let argument = tokens.next().unwrap();
+                            let argument = next_token("a directory name")?;
                             match argument {
-                                ".." => ExecutedCommand(ChangeDirectory(Out)),
+                                ".." => Ok(ExecutedCommand(ChangeDirectory(Out))),
                                 dir_name => {
-                                    ExecutedCommand(ChangeDirectory(In(dir_name.to_string())))
+                                    Ok(ExecutedCommand(ChangeDirectory(In(dir_name.to_string()))))
                                 }
                             }
                         }
-                        "ls" => ExecutedCommand(List),
-                        _ => unreachable!(),
+                        "ls" => Ok(ExecutedCommand(List)),
+                        command => Err(ParseError::at_token(
+                            DAY,
+                            line_index,
+                            output_line,
+                            command,
+                            "one of cd, ls",
+                        )),
                     }
                 }
-                "dir" => ResultLine(Dir),
-                size => ResultLine(File {
-                    size: size.parse().unwrap(),
-                }),
+                "dir" => Ok(ResultLine(Dir)),
+                size => Ok(ResultLine(File {
+                    size: size.parse().map_err(|_| {
+                        ParseError::at_token(DAY, line_index, output_line, size, "a file size")
+                    })?,
+                })),
             }
         })
         .collect()
@@ -101,16 +128,18 @@ fn dir_sizes(terminal_output: &[OutputLine]) -> HashMap<Path, u32> {
     dir_sizes
 }
 
+/// Returns the sum of the sizes of all directories of at most 100000 in size.
 #[aoc(day7, part1)]
-fn part1(terminal_output: &[OutputLine]) -> u32 {
+pub fn part1(terminal_output: &[OutputLine]) -> u32 {
     dir_sizes(terminal_output)
         .values()
         .filter(|&size| *size <= SMALL_DIR_SIZE_LIMIT)
         .sum()
 }
 
+/// Returns the size of the smallest directory that frees up enough space for the update.
 #[aoc(day7, part2)]
-fn part2(terminal_output: &[OutputLine]) -> u32 {
+pub fn part2(terminal_output: &[OutputLine]) -> u32 {
     let dir_sizes = dir_sizes(terminal_output);
     let root_size = *dir_sizes.get(&vec![ROOT.to_string()]).unwrap();
     let disk_space_available = TOTAL_DISK_SPACE - root_size;
@@ -122,6 +151,32 @@ fn part2(terminal_output: &[OutputLine]) -> u32 {
         .unwrap()
 }
 
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = Vec<OutputLine>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "No Space Left On Device"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,11 +207,31 @@ $ ls
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 95_437);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 95_437);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            95_437
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 24_933_642);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 24_933_642);
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            parse("$ cd /\n$ rm -rf a").err(),
+            Some(ParseError::new(7, 2, 3, "rm", "one of cd, ls"))
+        );
+        assert_eq!(
+            parse("$ cd /\n$ ls\n12kb a.txt").err(),
+            Some(ParseError::new(7, 3, 1, "12kb", "a file size"))
+        );
     }
 }
@@ -1,63 +1,56 @@
+//! Day 8: Treetop Tree House.
+
 use crate::day08::Axis::*;
+use crate::error::ParseError;
+use crate::geometry::{Grid, Point2};
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution, DEFAULT_VARIANT};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashSet;
 
-type Coordinates = (usize, usize);
-type TreeHeight = u32;
+const DAY: u8 = 8;
 
-struct Grid {
-    tree_heights: Vec<Vec<TreeHeight>>,
-    width: usize,
-    depth: usize,
-}
+pub type TreeHeight = u32;
 
 enum Axis {
     X,
     Y,
 }
 
+/// Parses the map of tree heights, one row of digits per line.
 #[aoc_generator(day8)]
-fn parse_input(input: &str) -> Grid {
-    Grid {
-        tree_heights: input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|tree| tree.to_digit(10).unwrap())
-                    .collect()
-            })
-            .collect(),
-        width: input.lines().next().unwrap().len(),
-        depth: input.lines().count(),
-    }
+pub fn parse(input: &str) -> Result<Grid<TreeHeight>, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    Grid::parse(DAY, input, "a tree height", |_, tree| tree.to_digit(10))
 }
 
-fn trees_visible_along_axis(grid: &Grid, axis: Axis) -> Vec<Coordinates> {
-    let mut visible_trees = Vec::with_capacity(grid.depth * grid.width);
+fn trees_visible_along_axis(grid: &Grid<TreeHeight>, axis: Axis) -> Vec<Point2<usize>> {
+    let mut visible_trees = Vec::with_capacity(grid.height() * grid.width());
 
     let (len_a, len_b) = match axis {
-        X => (grid.depth, grid.width),
-        Y => (grid.width, grid.depth),
+        X => (grid.height(), grid.width()),
+        Y => (grid.width(), grid.height()),
     };
 
     for coordinate_a in 0..len_a {
-        let (x, y) = match axis {
-            X => (0, coordinate_a),
-            Y => (coordinate_a, 0),
+        let tree = match axis {
+            X => Point2::new(0, coordinate_a),
+            Y => Point2::new(coordinate_a, 0),
         };
 
-        visible_trees.push((x, y));
-        let mut top_height = grid.tree_heights[y][x];
+        visible_trees.push(tree);
+        let mut top_height = grid[tree];
         let mut previous_tree_height = top_height;
         let mut maybe_visible_trees = Vec::with_capacity(len_b);
 
         for coordinate_b in 1..len_b {
-            let (x, y) = match axis {
-                X => (coordinate_b, coordinate_a),
-                Y => (coordinate_a, coordinate_b),
+            let tree = match axis {
+                X => Point2::new(coordinate_b, coordinate_a),
+                Y => Point2::new(coordinate_a, coordinate_b),
             };
 
-            let tree_height = grid.tree_heights[y][x];
+            let tree_height = grid[tree];
 
             if tree_height >= top_height {
                 maybe_visible_trees.clear();
@@ -73,54 +66,58 @@ fn trees_visible_along_axis(grid: &Grid, axis: Axis) -> Vec<Coordinates> {
                 }
             }
 
-            maybe_visible_trees.push(((x, y), tree_height));
+            maybe_visible_trees.push((tree, tree_height));
 
             if tree_height > top_height {
-                visible_trees.push((x, y));
+                visible_trees.push(tree);
                 top_height = tree_height;
             }
         }
 
-        visible_trees.extend(
-            maybe_visible_trees
-                .into_iter()
-                .map(|(coordinates, _)| coordinates),
-        );
+        visible_trees.extend(maybe_visible_trees.into_iter().map(|(tree, _)| tree));
     }
 
     visible_trees
 }
 
+/// Returns the number of trees visible from outside the grid.
 #[aoc(day8, part1)]
-fn part1(grid: &Grid) -> usize {
+pub fn part1(grid: &Grid<TreeHeight>) -> usize {
     let trees_visible_along_x = trees_visible_along_axis(grid, X);
     let trees_visible_along_y = trees_visible_along_axis(grid, Y);
 
     trees_visible_along_x
         .into_iter()
-        .chain(trees_visible_along_y.into_iter())
+        .chain(trees_visible_along_y)
         .collect::<HashSet<_>>()
         .len()
 }
 
+/// Returns the highest scenic score of any tree.
+///
+/// Scans every row and column in both directions with a monotonic stack of
+/// blocking trees, so the whole grid is processed in linear time.
 #[aoc(day8, part2, clever)]
-fn part2_clever(grid: &Grid) -> usize {
-    let mut scenic_scores = vec![vec![0; grid.width]; grid.depth];
-    let mut stack = Vec::with_capacity(usize::max(grid.width - 2, grid.depth - 2));
+pub fn part2_clever(grid: &Grid<TreeHeight>) -> usize {
+    let mut scenic_scores = vec![vec![0; grid.width()]; grid.height()];
+    let mut stack = Vec::with_capacity(usize::max(grid.width(), grid.height()).saturating_sub(2));
 
     for (y, x_scores) in scenic_scores
         .iter_mut()
         .enumerate()
-        .take(grid.depth - 1)
+        .take(grid.height() - 1)
         .skip(1)
     {
-        'outer: for (x, scenic_score) in
-            x_scores.iter_mut().enumerate().take(grid.width - 1).skip(1)
+        'outer: for (x, scenic_score) in x_scores
+            .iter_mut()
+            .enumerate()
+            .take(grid.width() - 1)
+            .skip(1)
         {
             while !stack.is_empty() {
                 let last = *stack.last().unwrap();
 
-                if grid.tree_heights[y][last] < grid.tree_heights[y][x] {
+                if grid[Point2::new(last, y)] < grid[Point2::new(x, y)] {
                     stack.pop();
                 } else {
                     *scenic_score = x - last;
@@ -139,14 +136,14 @@ fn part2_clever(grid: &Grid) -> usize {
     for (y, x_scores) in scenic_scores
         .iter_mut()
         .enumerate()
-        .take(grid.depth - 1)
+        .take(grid.height() - 1)
         .skip(1)
     {
-        'outer: for x in (1..grid.width - 1).rev() {
+        'outer: for x in (1..grid.width() - 1).rev() {
             while !stack.is_empty() {
                 let last = *stack.last().unwrap();
 
-                if grid.tree_heights[y][last] < grid.tree_heights[y][x] {
+                if grid[Point2::new(last, y)] < grid[Point2::new(x, y)] {
                     stack.pop();
                 } else {
                     x_scores[x] *= last - x;
@@ -155,24 +152,24 @@ fn part2_clever(grid: &Grid) -> usize {
                 }
             }
 
-            x_scores[x] *= grid.width - 1 - x;
+            x_scores[x] *= grid.width() - 1 - x;
             stack.push(x);
         }
 
         stack.clear();
     }
 
-    for x in 1..grid.width - 1 {
+    for x in 1..grid.width() - 1 {
         'outer: for (y, x_scores) in scenic_scores
             .iter_mut()
             .enumerate()
-            .take(grid.depth - 1)
+            .take(grid.height() - 1)
             .skip(1)
         {
             while !stack.is_empty() {
                 let last: usize = *stack.last().unwrap();
 
-                if grid.tree_heights[last][x] < grid.tree_heights[y][x] {
+                if grid[Point2::new(x, last)] < grid[Point2::new(x, y)] {
                     stack.pop();
                 } else {
                     x_scores[x] *= y - last;
@@ -189,12 +186,13 @@ fn part2_clever(grid: &Grid) -> usize {
         stack.clear();
     }
 
-    for x in 1..grid.width - 1 {
-        'outer: for y in (1..grid.depth - 1).rev() {
+    #[allow(clippy::needless_range_loop)]
+    for x in 1..grid.width() - 1 {
+        'outer: for y in (1..grid.height() - 1).rev() {
             while !stack.is_empty() {
                 let last: usize = *stack.last().unwrap();
 
-                if grid.tree_heights[last][x] < grid.tree_heights[y][x] {
+                if grid[Point2::new(x, last)] < grid[Point2::new(x, y)] {
                     stack.pop();
                 } else {
                     scenic_scores[y][x] *= last - y;
@@ -203,7 +201,7 @@ fn part2_clever(grid: &Grid) -> usize {
                 }
             }
 
-            scenic_scores[y][x] *= grid.depth - 1 - y;
+            scenic_scores[y][x] *= grid.height() - 1 - y;
             stack.push(y);
         }
 
@@ -213,21 +211,24 @@ fn part2_clever(grid: &Grid) -> usize {
     *scenic_scores.iter().flatten().max().unwrap()
 }
 
+/// Returns the highest scenic score of any tree.
+///
+/// Walks from every tree in all four directions until the view is blocked.
 #[aoc(day8, part2, naive)]
-fn part2_naive(grid: &Grid) -> usize {
-    let mut scenic_scores = Vec::with_capacity(grid.width * grid.depth);
+pub fn part2_naive(grid: &Grid<TreeHeight>) -> usize {
+    let mut scenic_scores = Vec::with_capacity(grid.width() * grid.height());
 
-    for tree_x in 0..grid.width {
-        for tree_y in 0..grid.depth {
+    for tree_x in 0..grid.width() {
+        for tree_y in 0..grid.height() {
             let mut scenic_score = 1;
-            let tree_height = grid.tree_heights[tree_y][tree_x];
+            let tree_height = grid[Point2::new(tree_x, tree_y)];
 
             let mut visible_trees_count = 0;
 
             for x in (0..tree_x).rev() {
                 visible_trees_count += 1;
 
-                if grid.tree_heights[tree_y][x] >= tree_height {
+                if grid[Point2::new(x, tree_y)] >= tree_height {
                     break;
                 }
             }
@@ -235,10 +236,10 @@ fn part2_naive(grid: &Grid) -> usize {
             scenic_score *= visible_trees_count;
             visible_trees_count = 0;
 
-            for x in tree_x + 1..grid.width {
+            for x in tree_x + 1..grid.width() {
                 visible_trees_count += 1;
 
-                if grid.tree_heights[tree_y][x] >= tree_height {
+                if grid[Point2::new(x, tree_y)] >= tree_height {
                     break;
                 }
             }
@@ -249,7 +250,7 @@ fn part2_naive(grid: &Grid) -> usize {
             for y in (0..tree_y).rev() {
                 visible_trees_count += 1;
 
-                if grid.tree_heights[y][tree_x] >= tree_height {
+                if grid[Point2::new(tree_x, y)] >= tree_height {
                     break;
                 }
             }
@@ -257,10 +258,10 @@ fn part2_naive(grid: &Grid) -> usize {
             scenic_score *= visible_trees_count;
             visible_trees_count = 0;
 
-            for y in tree_y + 1..grid.depth {
+            for y in tree_y + 1..grid.height() {
                 visible_trees_count += 1;
 
-                if grid.tree_heights[y][tree_x] >= tree_height {
+                if grid[Point2::new(tree_x, y)] >= tree_height {
                     break;
                 }
             }
@@ -273,9 +274,52 @@ fn part2_naive(grid: &Grid) -> usize {
     *scenic_scores.iter().max().unwrap()
 }
 
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Grid<TreeHeight>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Treetop Tree House"
+    }
+
+    fn variants(&self, part: Part) -> &'static [&'static str] {
+        match part {
+            Part::One => &[DEFAULT_VARIANT],
+            Part::Two => &["clever", "naive"],
+        }
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2_clever(input).into()
+    }
+
+    fn solve(&self, input: &Self::Input, part: Part, variant: &str) -> Option<Answer> {
+        match (part, variant) {
+            (Part::One, DEFAULT_VARIANT) => Some(self.part1(input)),
+            (Part::Two, "clever") => Some(self.part2(input)),
+            (Part::Two, "naive") => Some(part2_naive(input).into()),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_INPUT: &str = r"30373
 25512
@@ -285,16 +329,35 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 21);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 21);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 21);
     }
 
     #[test]
     fn part2_clever_example() {
-        assert_eq!(part2_clever(&parse_input(TEST_INPUT)), 8);
+        assert_eq!(part2_clever(&parse(TEST_INPUT).unwrap()), 8);
     }
 
     #[test]
     fn part2_naive_example() {
-        assert_eq!(part2_naive(&parse_input(TEST_INPUT)), 8);
+        assert_eq!(part2_naive(&parse(TEST_INPUT).unwrap()), 8);
+    }
+
+    fn grid() -> impl Strategy<Value = Grid<TreeHeight>> {
+        (1..12usize, 1..12usize).prop_flat_map(|(width, height)| {
+            prop::collection::vec(prop::collection::vec(0..10 as TreeHeight, width), height)
+                .prop_map(|rows| Grid::from_rows(rows).unwrap())
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn part2_clever_matches_naive(grid in grid()) {
+            prop_assert_eq!(part2_clever(&grid), part2_naive(&grid));
+        }
     }
 }
@@ -1,30 +1,32 @@
-use crate::day09::Direction::*;
+//! Day 9: Rope Bridge.
+
+use crate::error::ParseError;
+use crate::geometry::Direction::{self, *};
+use crate::geometry::{BoundingBox, Point2};
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution};
+use crate::visualize::{Cell, Frame, Frames, Visualize, BACKGROUND, GOLD, GREEN, RED, WHITE};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashSet;
+use std::iter;
+
+const DAY: u8 = 9;
 
 const SHORT_ROPE_KNOTS_COUNT: usize = 2;
 const LONG_ROPE_KNOTS_COUNT: usize = 10;
 
-enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
-struct Motion {
-    direction: Direction,
-    steps: usize,
+/// Moves the head of the rope `steps` times in `direction`.
+pub struct Motion {
+    pub direction: Direction,
+    pub steps: usize,
 }
 
-type Coordinates = (i32, i32);
-
 struct Rope {
-    knots: Vec<Coordinates>,
+    knots: Vec<Point2<i32>>,
 }
 
 impl Rope {
-    fn tail(&self) -> Coordinates {
+    fn tail(&self) -> Point2<i32> {
         *self.knots.last().unwrap()
     }
 
@@ -32,63 +34,83 @@ impl Rope {
         let mut previous_knot = *self.knots.first().unwrap();
 
         for knot in self.knots.iter_mut().skip(1) {
-            let (distance_x, distance_y) = (previous_knot.0 - knot.0, previous_knot.1 - knot.1);
+            let distance = previous_knot - *knot;
 
-            if distance_x.abs() > 1 || distance_y.abs() > 1 {
-                knot.0 += distance_x.signum();
-                knot.1 += distance_y.signum();
+            if distance.x.abs() > 1 || distance.y.abs() > 1 {
+                *knot += distance.signum();
             }
 
             previous_knot = *knot;
         }
     }
 
-    fn move_once(&mut self, direction: &Direction) {
-        let mut head = self.knots.first_mut().unwrap();
-
-        match direction {
-            Left => head.0 -= 1,
-            Right => head.0 += 1,
-            Up => head.1 += 1,
-            Down => head.1 -= 1,
-        }
+    fn move_once(&mut self, direction: Direction) {
+        *self.knots.first_mut().unwrap() += direction.offset();
 
         self.pull_tail();
     }
 }
 
+/// Parses the series of motions, one per line.
 #[aoc_generator(day9)]
-fn parse_input(input: &str) -> Vec<Motion> {
+pub fn parse(input: &str) -> Result<Vec<Motion>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|motion| {
+        .enumerate()
+        .map(|(line_index, motion)| {
             let mut tokens = motion.split_ascii_whitespace();
 
-            Motion {
-                direction: match tokens.next().unwrap() {
-                    "L" => Left,
-                    "R" => Right,
-                    "U" => Up,
-                    "D" => Down,
-                    _ => unreachable!(),
-                },
-                steps: tokens.next().unwrap().parse().unwrap(),
-            }
+            let direction = match tokens.next() {
+                Some("L") => Left,
+                Some("R") => Right,
+                Some("U") => Up,
+                Some("D") => Down,
+                Some(token) => {
+                    return Err(ParseError::at_token(
+                        DAY,
+                        line_index,
+                        motion,
+                        token,
+                        "one of L, R, U, D",
+                    ))
+                }
+                None => {
+                    return Err(ParseError::at_end_of_line(
+                        DAY,
+                        line_index,
+                        motion,
+                        "one of L, R, U, D",
+                    ))
+                }
+            };
+
+            let steps = tokens.next().ok_or_else(|| {
+                ParseError::at_end_of_line(DAY, line_index, motion, "a number of steps")
+            })?;
+
+            Ok(Motion {
+                direction,
+                steps: steps.parse().map_err(|_| {
+                    ParseError::at_token(DAY, line_index, motion, steps, "a number of steps")
+                })?,
+            })
         })
         .collect()
 }
 
 fn visited_by_tail(series_of_motions: &[Motion], knots_count: usize) -> usize {
     let mut rope = Rope {
-        knots: vec![(0, 0); knots_count],
+        knots: vec![Point2::default(); knots_count],
     };
 
     let mut tail_visited = HashSet::with_capacity(series_of_motions.len());
-    tail_visited.insert((0, 0));
+    tail_visited.insert(Point2::default());
 
     for motion in series_of_motions {
         for _ in 0..motion.steps {
-            rope.move_once(&motion.direction);
+            rope.move_once(motion.direction);
             tail_visited.insert(rope.tail());
         }
     }
@@ -96,16 +118,103 @@ fn visited_by_tail(series_of_motions: &[Motion], knots_count: usize) -> usize {
     tail_visited.len()
 }
 
+/// Returns the number of positions the tail of a two-knot rope visits.
 #[aoc(day9, part1)]
-fn part1(series_of_motions: &[Motion]) -> usize {
+pub fn part1(series_of_motions: &[Motion]) -> usize {
     visited_by_tail(series_of_motions, SHORT_ROPE_KNOTS_COUNT)
 }
 
+/// Returns the number of positions the tail of a ten-knot rope visits.
 #[aoc(day9, part2)]
-fn part2(series_of_motions: &[Motion]) -> usize {
+pub fn part2(series_of_motions: &[Motion]) -> usize {
     visited_by_tail(series_of_motions, LONG_ROPE_KNOTS_COUNT)
 }
 
+impl Visualize for [Motion] {
+    /// Draws the rope after every step of the head, over the positions the tail visited.
+    fn frames(&self, part: Part) -> Frames<'_> {
+        let knots_count = match part {
+            Part::One => SHORT_ROPE_KNOTS_COUNT,
+            Part::Two => LONG_ROPE_KNOTS_COUNT,
+        };
+
+        // Every knot follows the previous one, so the head's path bounds the whole rope.
+        let head_path = self.iter().scan(Point2::default(), |head, motion| {
+            *head += motion.direction.offset() * motion.steps as i32;
+            Some(*head)
+        });
+        let bounds = BoundingBox::from_points(iter::once(Point2::default()).chain(head_path))
+            .expect("the path includes the start");
+
+        let mut rope = Rope {
+            knots: vec![Point2::default(); knots_count],
+        };
+        let mut tail_visited = HashSet::from([Point2::default()]);
+
+        let steps = self
+            .iter()
+            .flat_map(|motion| iter::repeat_n(motion.direction, motion.steps));
+
+        Box::new(iter::once(None).chain(steps.map(Some)).map(move |step| {
+            if let Some(direction) = step {
+                rope.move_once(direction);
+                tail_visited.insert(rope.tail());
+            }
+
+            let mut frame = Frame::new(bounds, Cell::new('.', BACKGROUND));
+
+            for &position in &tail_visited {
+                frame.draw(position, Cell::new('#', GREEN));
+            }
+
+            frame.draw(Point2::default(), Cell::new('s', WHITE));
+
+            // Knots closer to the head cover the ones behind them.
+            for (index, &knot) in rope.knots.iter().enumerate().rev() {
+                let cell = match index {
+                    0 => Cell::new('H', GOLD),
+                    _ if knots_count == 2 => Cell::new('T', RED),
+                    _ => Cell::new(char::from_digit(index as u32, 36).unwrap(), RED),
+                };
+
+                frame.draw(knot, cell);
+            }
+
+            frame
+        }))
+    }
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<Motion>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Rope Bridge"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn frames<'a>(&self, input: &'a Self::Input, part: Part) -> Option<Frames<'a>> {
+        Some(input.frames(part))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,12 +239,43 @@ U 20";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT_1)), 13);
+        assert_eq!(part1(&parse(TEST_INPUT_1).unwrap()), 13);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT_1)).unwrap()),
+            13
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT_1)), 1);
-        assert_eq!(part2(&parse_input(TEST_INPUT_2)), 36);
+        assert_eq!(part2(&parse(TEST_INPUT_1).unwrap()), 1);
+        assert_eq!(part2(&parse(TEST_INPUT_2).unwrap()), 36);
+    }
+
+    #[test]
+    fn frames_example() {
+        let frames: Vec<_> = parse(TEST_INPUT_1).unwrap().frames(Part::One).collect();
+
+        assert_eq!(frames.len(), 25);
+        assert_eq!(
+            frames[0].to_string(),
+            r"......
+......
+......
+......
+H....."
+        );
+        assert_eq!(
+            frames[24].to_string(),
+            r"..##..
+...##.
+.TH##.
+....#.
+s###.."
+        );
     }
 }
@@ -1,8 +1,15 @@
+//! Day 10: Cathode-Ray Tube.
+
 use crate::day10::Instruction::*;
 use crate::day10::PixelState::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::fmt::{Display, Formatter};
 
+const DAY: u8 = 10;
+
 const X_STARTING_VALUE: i32 = 1;
 const SIGNAL_STRENGTH_MEASURE_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
 const MAX_INSTRUCTION_DURATION: usize = 2;
@@ -10,7 +17,7 @@ const MAX_INSTRUCTION_DURATION: usize = 2;
 const CRT_WIDTH: usize = 40;
 const CRT_HEIGHT: usize = 6;
 
-enum Instruction {
+pub enum Instruction {
     AddX(i32),
     NoOp,
 }
@@ -57,24 +64,49 @@ impl Display for Crt {
     }
 }
 
+/// Parses the program, one instruction per line.
 #[aoc_generator(day10)]
-fn parse_input(input: &str) -> Vec<Instruction> {
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|instruction| {
+        .enumerate()
+        .map(|(line_index, instruction)| {
             let mut tokens = instruction.split_ascii_whitespace();
 
-            match tokens.next().unwrap() {
-                "addx" => AddX(tokens.next().unwrap().parse().unwrap()),
-                "noop" => NoOp,
-                _ => unreachable!(),
+            match tokens.next() {
+                Some("addx") => {
+                    let value = tokens.next().ok_or_else(|| {
+                        ParseError::at_end_of_line(DAY, line_index, instruction, "an addx value")
+                    })?;
+
+                    Ok(AddX(value.parse().map_err(|_| {
+                        ParseError::at_token(DAY, line_index, instruction, value, "an addx value")
+                    })?))
+                }
+                Some("noop") => Ok(NoOp),
+                Some(token) => Err(ParseError::at_token(
+                    DAY,
+                    line_index,
+                    instruction,
+                    token,
+                    "one of addx, noop",
+                )),
+                None => Err(ParseError::at_end_of_line(
+                    DAY,
+                    line_index,
+                    instruction,
+                    "one of addx, noop",
+                )),
             }
         })
         .collect()
 }
 
+/// Returns the sum of the six signal strengths measured during the program.
 #[aoc(day10, part1)]
-fn part1(program: &[Instruction]) -> i32 {
+pub fn part1(program: &[Instruction]) -> i32 {
     let mut cycles_passed = 0;
     let mut x_value = X_STARTING_VALUE;
     let mut signal_strengths_sum = 0;
@@ -100,8 +132,9 @@ fn part1(program: &[Instruction]) -> i32 {
     signal_strengths_sum
 }
 
+/// Returns the image rendered on the CRT, one line per row.
 #[aoc(day10, part2)]
-fn part2(program: &[Instruction]) -> String {
+pub fn part2(program: &[Instruction]) -> String {
     let mut cycles_passed = 0;
     let mut x_value = X_STARTING_VALUE;
     let mut crt = Crt([Dark; CRT_WIDTH * CRT_HEIGHT]);
@@ -130,6 +163,32 @@ fn part2(program: &[Instruction]) -> String {
     crt.to_string()
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Instruction>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Cathode-Ray Tube"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,13 +343,21 @@ noop
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 13_140);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 13_140);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            13_140
+        );
     }
 
     #[test]
     fn part2_example() {
         assert_eq!(
-            part2(&parse_input(TEST_INPUT)),
+            part2(&parse(TEST_INPUT).unwrap()),
             r"##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
 ####....####....####....####....####....
@@ -1,14 +1,28 @@
+//! Day 11: Monkey in the Middle.
+
+use crate::arithmetic::{natural, Arithmetic, Natural, Overflow, Remainder};
+#[cfg(any(feature = "checked", feature = "bigint"))]
+use crate::cycle;
 use crate::day11::Operand::*;
 use crate::day11::Operator::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution, DEFAULT_VARIANT};
 use aoc_runner_derive::{aoc, aoc_generator};
+use num_traits::Zero;
 use std::collections::vec_deque::VecDeque;
 
-type WorryLevel = u64;
+const DAY: u8 = 11;
+
+#[cfg(any(feature = "checked", feature = "bigint"))]
+const TRILLION_ROUNDS: usize = 1_000_000_000_000;
+
+pub type WorryLevel = Natural;
 type MonkeyId = usize;
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 enum Operand {
-    Number(u64),
+    Number(WorryLevel),
     Old,
 }
 
@@ -18,7 +32,7 @@ enum Operator {
     Mul,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 struct Operation {
     lhs: Operand,
     operator: Operator,
@@ -26,121 +40,221 @@ struct Operation {
 }
 
 impl Operation {
-    fn execute(&self, old: u64) -> u64 {
-        let lhs = match self.lhs {
+    fn execute(&self, old: &WorryLevel) -> Result<WorryLevel, Overflow> {
+        let lhs = match &self.lhs {
             Number(operand) => operand,
             Old => old,
         };
 
-        let rhs = match self.rhs {
+        let rhs = match &self.rhs {
             Number(operand) => operand,
             Old => old,
         };
 
         match self.operator {
-            Add => lhs + rhs,
-            Mul => lhs * rhs,
+            Add => lhs.try_add(rhs),
+            Mul => lhs.try_mul(rhs),
         }
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 struct Test {
-    divisible_by: u64,
+    divisible_by: WorryLevel,
     if_true: MonkeyId,
     if_false: MonkeyId,
 }
 
+/// A monkey holding items and deciding whom to throw them to.
 #[derive(Clone)]
-struct Monkey {
+pub struct Monkey {
     starting_items: VecDeque<WorryLevel>,
     operation: Operation,
     test: Test,
 }
 
+fn field<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    previous_line: (usize, &'a str),
+    prefix: &str,
+) -> Result<(usize, &'a str, &'a str), ParseError> {
+    let (line_index, line) = lines.next().ok_or_else(|| {
+        ParseError::at_end_of_line(DAY, previous_line.0, previous_line.1, format!("{prefix:?}"))
+    })?;
+
+    line.strip_prefix(prefix)
+        .map(|value| (line_index, line, value))
+        .ok_or_else(|| ParseError::at_token(DAY, line_index, line, line, format!("{prefix:?}")))
+}
+
+fn parse_operand(
+    token: Option<&str>,
+    line_index: usize,
+    line: &str,
+) -> Result<Operand, ParseError> {
+    match token {
+        Some("old") => Ok(Old),
+        Some(token) => token
+            .parse()
+            .map(Number)
+            .map_err(|_| ParseError::at_token(DAY, line_index, line, token, "old or a number")),
+        None => Err(ParseError::at_end_of_line(
+            DAY,
+            line_index,
+            line,
+            "old or a number",
+        )),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(
+    token: &str,
+    line_index: usize,
+    line: &str,
+    expected: &str,
+) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::at_token(DAY, line_index, line, token, expected))
+}
+
+/// Parses the monkey notes, separated by blank lines.
 #[aoc_generator(day11)]
-fn parse_input(input: &str) -> Vec<Monkey> {
-    input
-        .split("\n\n")
-        .map(|monkey| {
-            let mut arguments = monkey.lines().skip(1);
-
-            Monkey {
-                starting_items: arguments
-                    .next()
-                    .unwrap()
-                    .split_once("  Starting items: ")
-                    .unwrap()
-                    .1
-                    .split(", ")
-                    .map(|token| token.parse().unwrap())
-                    .collect(),
-                operation: {
-                    let mut tokens = arguments
-                        .next()
-                        .unwrap()
-                        .split_once("  Operation: new = ")
-                        .unwrap()
-                        .1
-                        .split_ascii_whitespace();
-
-                    Operation {
-                        lhs: if let Ok(worry_level) = tokens.next().unwrap().parse::<u64>() {
-                            Number(worry_level)
-                        } else {
-                            Old
-                        },
-                        operator: match tokens.next().unwrap() {
-                            "+" => Add,
-                            "*" => Mul,
-                            _ => unreachable!(),
-                        },
-                        rhs: if let Ok(worry_level) = tokens.next().unwrap().parse::<u64>() {
-                            Number(worry_level)
-                        } else {
-                            Old
-                        },
-                    }
-                },
-                test: Test {
-                    divisible_by: arguments
-                        .next()
-                        .unwrap()
-                        .split_once("  Test: divisible by ")
-                        .unwrap()
-                        .1
-                        .parse()
-                        .unwrap(),
-                    if_true: arguments
-                        .next()
-                        .unwrap()
-                        .split_once("    If true: throw to monkey ")
-                        .unwrap()
-                        .1
-                        .parse()
-                        .unwrap(),
-                    if_false: arguments
-                        .next()
-                        .unwrap()
-                        .split_once("    If false: throw to monkey ")
-                        .unwrap()
-                        .1
-                        .parse()
-                        .unwrap(),
-                },
-            }
-        })
-        .collect()
+pub fn parse(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty());
+    let mut monkeys = Vec::new();
+    let mut throw_targets = Vec::new();
+
+    while let Some((line_index, line)) = lines.next() {
+        let monkey_id = line
+            .strip_prefix("Monkey ")
+            .and_then(|monkey_id| monkey_id.strip_suffix(':'))
+            .ok_or_else(|| ParseError::at_token(DAY, line_index, line, line, "\"Monkey N:\""))?;
+
+        if parse_number::<MonkeyId>(monkey_id, line_index, line, "a monkey ID")? != monkeys.len() {
+            return Err(ParseError::at_token(
+                DAY,
+                line_index,
+                line,
+                monkey_id,
+                format!("monkey ID {}", monkeys.len()),
+            ));
+        }
+
+        let (line_index, line, starting_items) =
+            field(&mut lines, (line_index, line), "  Starting items: ")?;
+        let starting_items = starting_items
+            .split(", ")
+            .map(|token| parse_number(token, line_index, line, "a worry level"))
+            .collect::<Result<_, _>>()?;
+
+        let (line_index, line, operation) =
+            field(&mut lines, (line_index, line), "  Operation: new = ")?;
+        let mut tokens = operation.split_ascii_whitespace();
+        let operation = Operation {
+            lhs: parse_operand(tokens.next(), line_index, line)?,
+            operator: match tokens.next() {
+                Some("+") => Add,
+                Some("*") => Mul,
+                Some(token) => {
+                    return Err(ParseError::at_token(
+                        DAY,
+                        line_index,
+                        line,
+                        token,
+                        "one of +, *",
+                    ))
+                }
+                None => {
+                    return Err(ParseError::at_end_of_line(
+                        DAY,
+                        line_index,
+                        line,
+                        "one of +, *",
+                    ))
+                }
+            },
+            rhs: parse_operand(tokens.next(), line_index, line)?,
+        };
+
+        let (line_index, line, divisor) =
+            field(&mut lines, (line_index, line), "  Test: divisible by ")?;
+        let divisible_by: WorryLevel = parse_number(divisor, line_index, line, "a divisor")?;
+
+        if divisible_by.is_zero() {
+            return Err(ParseError::at_token(
+                DAY,
+                line_index,
+                line,
+                divisor,
+                "a positive divisor",
+            ));
+        }
+
+        let (line_index, line, if_true) = field(
+            &mut lines,
+            (line_index, line),
+            "    If true: throw to monkey ",
+        )?;
+        throw_targets.push((line_index, line, if_true));
+        let if_true = parse_number(if_true, line_index, line, "a monkey ID")?;
+
+        let (line_index, line, if_false) = field(
+            &mut lines,
+            (line_index, line),
+            "    If false: throw to monkey ",
+        )?;
+        throw_targets.push((line_index, line, if_false));
+        let if_false = parse_number(if_false, line_index, line, "a monkey ID")?;
+
+        monkeys.push(Monkey {
+            starting_items,
+            operation,
+            test: Test {
+                divisible_by,
+                if_true,
+                if_false,
+            },
+        });
+    }
+
+    for (line_index, line, monkey_id) in throw_targets {
+        if parse_number::<MonkeyId>(monkey_id, line_index, line, "a monkey ID")? >= monkeys.len() {
+            return Err(ParseError::at_token(
+                DAY,
+                line_index,
+                line,
+                monkey_id,
+                format!("a monkey ID below {}", monkeys.len()),
+            ));
+        }
+    }
+
+    Ok(monkeys)
 }
 
-fn monkey_business(monkeys: &[Monkey], worry_relief: bool, number_of_rounds: usize) -> usize {
+fn common_multiple(monkeys: &[Monkey]) -> Result<WorryLevel, Overflow> {
+    monkeys.iter().try_fold(natural(1), |product, monkey| {
+        product.try_mul(&monkey.test.divisible_by)
+    })
+}
+
+/// Returns how many items each monkey inspects in `number_of_rounds` rounds.
+fn inspections(
+    monkeys: &[Monkey],
+    worry_relief: bool,
+    number_of_rounds: usize,
+) -> Result<Vec<usize>, Overflow> {
     let mut inspected_items: Vec<usize> = vec![0; monkeys.len()];
     let mut monkeys: Vec<_> = monkeys.to_vec();
 
-    let common_multiple: u64 = monkeys
-        .iter()
-        .map(|monkey| monkey.test.divisible_by)
-        .product();
+    let common_multiple = common_multiple(&monkeys)?;
+    let relief = natural(3);
 
     for _round in 0..number_of_rounds {
         for id in 0..monkeys.len() {
@@ -155,15 +269,15 @@ fn monkey_business(monkeys: &[Monkey], worry_relief: bool, number_of_rounds: usi
 
             while !monkey.starting_items.is_empty() {
                 let mut worry_level = monkey.starting_items.pop_front().unwrap();
-                worry_level = monkey.operation.execute(worry_level);
+                worry_level = monkey.operation.execute(&worry_level)?;
 
                 if worry_relief {
-                    worry_level /= 3;
+                    worry_level = worry_level.try_div(&relief)?;
                 } else {
-                    worry_level %= common_multiple;
+                    worry_level = worry_level.try_rem(&common_multiple)?;
                 }
 
-                if worry_level % monkey.test.divisible_by == 0 {
+                if worry_level.try_rem(&monkey.test.divisible_by)?.is_zero() {
                     if_true_items.push_back(worry_level);
                 } else {
                     if_false_items.push_back(worry_level);
@@ -179,18 +293,190 @@ fn monkey_business(monkeys: &[Monkey], worry_relief: bool, number_of_rounds: usi
         }
     }
 
+    Ok(inspected_items)
+}
+
+#[cfg(any(feature = "checked", feature = "bigint"))]
+/// Where an item is at the start of a round, which decides all its next rounds
+/// without worry relief.
+#[derive(Clone, Eq, PartialEq)]
+struct Position {
+    monkey: MonkeyId,
+    worry_level: WorryLevel,
+}
+
+#[cfg(any(feature = "checked", feature = "bigint"))]
+/// An item on its own, and how many times each monkey has inspected it.
+#[derive(Clone)]
+struct Item {
+    position: Position,
+    inspections: Vec<usize>,
+}
+
+#[cfg(any(feature = "checked", feature = "bigint"))]
+/// Returns how many items each monkey inspects in `number_of_rounds` rounds
+/// without worry relief, skipping ahead once the rounds of each item repeat.
+/// Items without worry relief move independently of each other.
+fn extrapolated_inspections(
+    monkeys: &[Monkey],
+    number_of_rounds: usize,
+) -> Result<Vec<usize>, Overflow> {
+    let common_multiple = common_multiple(monkeys)?;
+
+    // Worry levels stay below the common multiple, and no operation
+    // overflows on them if none overflows on the largest one.
+    let largest_worry_level = common_multiple.try_sub(&natural(1))?;
+
+    for monkey in monkeys {
+        monkey.operation.execute(&largest_worry_level)?;
+    }
+
+    let round = |item: &Item| {
+        let mut item = item.clone();
+        let position = &mut item.position;
+
+        // Items thrown to a monkey further on are inspected again in the same round.
+        loop {
+            let monkey = &monkeys[position.monkey];
+            item.inspections[position.monkey] += 1;
+            position.worry_level = monkey
+                .operation
+                .execute(&position.worry_level)
+                .and_then(|worry_level| worry_level.try_rem(&common_multiple))
+                .expect("worry levels below the common multiple do not overflow");
+
+            let divisible = position
+                .worry_level
+                .try_rem(&monkey.test.divisible_by)
+                .is_ok_and(|remainder| remainder.is_zero());
+            let target = if divisible {
+                monkey.test.if_true
+            } else {
+                monkey.test.if_false
+            };
+
+            let next_round = target <= position.monkey;
+            position.monkey = target;
+
+            if next_round {
+                return item;
+            }
+        }
+    };
+
+    let mut inspected_items = vec![0; monkeys.len()];
+
+    for (id, monkey) in monkeys.iter().enumerate() {
+        for worry_level in &monkey.starting_items {
+            let item = Item {
+                position: Position {
+                    monkey: id,
+                    worry_level: worry_level.try_rem(&common_multiple)?,
+                },
+                inspections: vec![0; monkeys.len()],
+            };
+
+            let inspections = cycle::brent(
+                item,
+                round,
+                |item| item.position.clone(),
+                |item| item.inspections.clone(),
+                number_of_rounds,
+            );
+
+            for (total, inspections) in inspected_items.iter_mut().zip(inspections) {
+                *total += inspections;
+            }
+        }
+    }
+
+    Ok(inspected_items)
+}
+
+/// Returns the product of the two largest numbers of inspected items.
+fn monkey_business(mut inspected_items: Vec<usize>) -> Result<usize, Overflow> {
     inspected_items.sort_unstable();
-    inspected_items.iter().rev().take(2).product()
+    inspected_items
+        .iter()
+        .rev()
+        .take(2)
+        .try_fold(1, |product, inspected| product.try_mul(inspected))
 }
 
+/// Returns the level of monkey business after 20 rounds with worry relief.
 #[aoc(day11, part1)]
-fn part1(monkeys: &[Monkey]) -> usize {
-    monkey_business(monkeys, true, 20)
+pub fn part1(monkeys: &[Monkey]) -> Result<usize, Overflow> {
+    monkey_business(inspections(monkeys, true, 20)?)
 }
 
+/// Returns the level of monkey business after 10000 rounds without worry relief.
 #[aoc(day11, part2)]
-fn part2(monkeys: &[Monkey]) -> usize {
-    monkey_business(monkeys, false, 10_000)
+pub fn part2(monkeys: &[Monkey]) -> Result<usize, Overflow> {
+    monkey_business(inspections(monkeys, false, 10_000)?)
+}
+
+/// Returns the level of monkey business after 10^12 rounds without worry
+/// relief, which needs the `bigint` feature not to overflow. Without `checked`
+/// or `bigint` the overflow would go unnoticed, so it is left out.
+#[cfg(any(feature = "checked", feature = "bigint"))]
+#[aoc(day11, part2, trillion)]
+pub fn part2_trillion(monkeys: &[Monkey]) -> Result<Natural, Overflow> {
+    let mut inspected_items = extrapolated_inspections(monkeys, TRILLION_ROUNDS)?;
+    inspected_items.sort_unstable();
+
+    inspected_items
+        .iter()
+        .rev()
+        .take(2)
+        .try_fold(natural(1), |product, &inspected| {
+            product.try_mul(&natural(inspected as u64))
+        })
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Monkey in the Middle"
+    }
+
+    fn variants(&self, part: Part) -> &'static [&'static str] {
+        match part {
+            Part::One => &[DEFAULT_VARIANT],
+            #[cfg(any(feature = "checked", feature = "bigint"))]
+            Part::Two => &[DEFAULT_VARIANT, "trillion"],
+            #[cfg(not(any(feature = "checked", feature = "bigint")))]
+            Part::Two => &[DEFAULT_VARIANT],
+        }
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn solve(&self, input: &Self::Input, part: Part, variant: &str) -> Option<Answer> {
+        match (part, variant) {
+            (Part::One, DEFAULT_VARIANT) => Some(self.part1(input)),
+            (Part::Two, DEFAULT_VARIANT) => Some(self.part2(input)),
+            #[cfg(any(feature = "checked", feature = "bigint"))]
+            (Part::Two, "trillion") => Some(part2_trillion(input).into()),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -227,11 +513,74 @@ Monkey 3:
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 10_605);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), Ok(10_605));
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            Ok(10_605)
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 2_713_310_158);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), Ok(2_713_310_158));
+    }
+
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    #[test]
+    fn extrapolated_inspections_example() {
+        let monkeys = parse(TEST_INPUT).unwrap();
+
+        for number_of_rounds in [0, 1, 20, 10_000] {
+            assert_eq!(
+                extrapolated_inspections(&monkeys, number_of_rounds),
+                inspections(&monkeys, false, number_of_rounds)
+            );
+        }
+    }
+
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    #[test]
+    fn part2_trillion_example() {
+        let answer = part2_trillion(&parse(TEST_INPUT).unwrap());
+
+        if cfg!(feature = "bigint") {
+            assert_eq!(
+                answer.map(|answer| answer.to_string()),
+                Ok((5_217_653_508_757_u128 * 5_202_028_508_760).to_string())
+            );
+        } else {
+            assert_eq!(answer, Err(Overflow));
+        }
+    }
+
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    #[test]
+    fn large_divisors() {
+        let input = TEST_INPUT
+            .replace("by 23", "by 4294967311")
+            .replace("by 19", "by 4294967357");
+        let answer = part2(&parse(&input).unwrap());
+
+        if cfg!(feature = "bigint") {
+            assert!(answer.is_ok());
+        } else {
+            assert_eq!(answer, Err(Overflow));
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            parse(&TEST_INPUT.replace("old + 6", "old ^ 6")).err(),
+            Some(ParseError::new(11, 10, 24, "^", "one of +, *"))
+        );
+        assert_eq!(
+            parse(&TEST_INPUT.replace("throw to monkey 0", "throw to monkey 7")).err(),
+            Some(ParseError::new(11, 13, 31, "7", "a monkey ID below 4"))
+        );
     }
 }
@@ -1,120 +1,131 @@
+//! Day 12: Hill Climbing Algorithm.
+
+use crate::error::ParseError;
+use crate::geometry::{Grid, Point2};
+use crate::preprocess;
+use crate::search;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{HashSet, VecDeque};
 
-type Coordinates = (i32, i32);
+const DAY: u8 = 12;
 
 const LOWEST_ELEVATION: u32 = 'a' as u32;
 const HIGHEST_ELEVATION: u32 = 'z' as u32;
 
-struct Map {
-    elevations: Vec<Vec<u32>>,
-    current_position: Coordinates,
-    best_signal_location: Coordinates,
-    width: usize,
-    height: usize,
+/// A heightmap with the current position and the best signal location.
+pub struct Map {
+    elevations: Grid<u32>,
+    current_position: Point2<usize>,
+    best_signal_location: Point2<usize>,
 }
 
 impl Map {
-    fn neighbor_elevations(&self, current_position: Coordinates) -> Vec<(Coordinates, u32)> {
-        [(-1, 0), (1, 0), (0, -1), (0, 1)]
-            .into_iter()
-            .map(|(x, y)| (current_position.0 + x, current_position.1 + y))
-            .filter(|(x, y)| {
-                *x >= 0 && (*x as usize) < self.width && *y >= 0 && (*y as usize) < self.height
-            })
-            .map(|(x, y)| ((x, y), self.elevations[y as usize][x as usize]))
-            .collect()
+    /// The squares you can climb to from `position`: at most one higher.
+    fn climbable_neighbors(
+        &self,
+        position: Point2<usize>,
+    ) -> impl Iterator<Item = Point2<usize>> + '_ {
+        let elevation = self.elevations[position];
+
+        self.elevations
+            .neighbors4(position)
+            .filter(move |&neighbor| self.elevations[neighbor] <= elevation + 1)
+    }
+
+    /// Returns the fewest steps from any of `starts` to the best signal location.
+    fn fewest_steps(&self, starts: impl IntoIterator<Item = Point2<usize>>) -> usize {
+        search::bfs(
+            starts,
+            |&position| self.climbable_neighbors(position),
+            |&position| position == self.best_signal_location,
+        )
+        .goal_cost()
+        .unwrap_or(0)
     }
 }
 
+/// Parses the heightmap, one row of squares per line.
 #[aoc_generator(day12)]
-fn parse_input(input: &str) -> Map {
-    let mut current_position = (0, 0);
-    let mut best_signal_location = (0, 0);
-    let mut elevations = Vec::new();
-
-    for (y, row) in input.lines().enumerate() {
-        let mut row_elevations = Vec::with_capacity(row.len());
-
-        for (x, elevation) in row.chars().enumerate() {
-            match elevation {
-                'S' => {
-                    current_position = (x as i32, y as i32);
-                    row_elevations.push(LOWEST_ELEVATION)
-                }
-                'E' => {
-                    best_signal_location = (x as i32, y as i32);
-                    row_elevations.push(HIGHEST_ELEVATION)
-                }
-                elevation => row_elevations.push(elevation as u32),
-            };
-        }
-
-        elevations.push(row_elevations);
-    }
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let mut current_position = None;
+    let mut best_signal_location = None;
+
+    let elevations = Grid::parse(
+        DAY,
+        input,
+        "an elevation from a to z, S or E",
+        |position, elevation| match elevation {
+            'S' => {
+                current_position = Some(position);
+                Some(LOWEST_ELEVATION)
+            }
+            'E' => {
+                best_signal_location = Some(position);
+                Some(HIGHEST_ELEVATION)
+            }
+            'a'..='z' => Some(elevation as u32),
+            _ => None,
+        },
+    )?;
+
+    let last_line = input.lines().enumerate().last().unwrap_or_default();
 
-    let width = elevations[0].len();
-    let height = elevations.len();
+    let current_position = current_position.ok_or_else(|| {
+        ParseError::at_end_of_line(DAY, last_line.0, last_line.1, "a current position S")
+    })?;
+    let best_signal_location = best_signal_location.ok_or_else(|| {
+        ParseError::at_end_of_line(DAY, last_line.0, last_line.1, "a best signal location E")
+    })?;
 
-    Map {
+    Ok(Map {
         elevations,
         current_position,
         best_signal_location,
-        width,
-        height,
-    }
+    })
 }
 
+/// Returns the fewest steps from the current position to the best signal location.
 #[aoc(day12, part1)]
-fn part1(map: &Map) -> usize {
-    let current_position = map.current_position;
-    let mut queue = VecDeque::from(vec![(current_position, LOWEST_ELEVATION, 0)]);
-    let mut visited: HashSet<Coordinates> = HashSet::from([current_position]);
-
-    while !queue.is_empty() {
-        let (current_position, current_elevation, path_length) = queue.pop_front().unwrap();
-
-        if current_position == map.best_signal_location {
-            return path_length;
-        }
-
-        for (neighbor_position, neighbor_elevation) in map.neighbor_elevations(current_position) {
-            if neighbor_elevation as i32 - current_elevation as i32 <= 1
-                && !visited.contains(&neighbor_position)
-            {
-                queue.push_back((neighbor_position, neighbor_elevation, path_length + 1));
-                visited.insert(neighbor_position);
-            }
-        }
-    }
-
-    0
+pub fn part1(map: &Map) -> usize {
+    map.fewest_steps([map.current_position])
 }
 
+/// Returns the fewest steps from any lowest square to the best signal location.
 #[aoc(day12, part2)]
-fn part2(map: &Map) -> usize {
-    let current_position = map.best_signal_location;
-    let mut queue = VecDeque::from(vec![(current_position, HIGHEST_ELEVATION, 0)]);
-    let mut visited: HashSet<Coordinates> = HashSet::from([current_position]);
-
-    while !queue.is_empty() {
-        let (current_position, current_elevation, path_length) = queue.pop_front().unwrap();
-
-        if current_elevation == LOWEST_ELEVATION {
-            return path_length;
-        }
-
-        for (neighbor_position, neighbor_elevation) in map.neighbor_elevations(current_position) {
-            if current_elevation as i32 - neighbor_elevation as i32 <= 1
-                && !visited.contains(&neighbor_position)
-            {
-                queue.push_back((neighbor_position, neighbor_elevation, path_length + 1));
-                visited.insert(neighbor_position);
-            }
-        }
+pub fn part2(map: &Map) -> usize {
+    map.fewest_steps(
+        map.elevations
+            .points()
+            .filter(|&position| map.elevations[position] == LOWEST_ELEVATION),
+    )
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Map;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Hill Climbing Algorithm"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
 
-    0
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[cfg(test)]
@@ -129,11 +140,16 @@ abdefghi";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 31);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 31);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 31);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 29);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 29);
     }
 }
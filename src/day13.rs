@@ -1,52 +1,112 @@
+//! Day 13: Distress Signal.
+
 use crate::day13::PacketSymbol::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::cmp::Ordering::*;
 use std::collections::VecDeque;
 
+const DAY: u8 = 13;
+
 #[derive(Eq, PartialEq, Clone, Copy)]
-enum PacketSymbol {
+pub enum PacketSymbol {
     LeftBracket,
     RightBracket,
     Integer(u32),
 }
 
-type Packet = VecDeque<PacketSymbol>;
+/// A packet flattened into its brackets and integers.
+pub type Packet = VecDeque<PacketSymbol>;
 
 static DIVIDER_PACKET_1: &str = "[[2]]";
 static DIVIDER_PACKET_2: &str = "[[6]]";
 
-fn parse_packet(packet: &str) -> Packet {
+fn parse_packet(packet: &str) -> Result<Packet, usize> {
     let mut packet_symbols = VecDeque::with_capacity(packet.len());
     let mut integer_string = String::with_capacity(2);
+    let mut depth = 0usize;
+
+    for (index, symbol) in packet.chars().enumerate() {
+        if depth == 0 && index > 0 {
+            return Err(index);
+        }
 
-    for symbol in packet.chars() {
         if !symbol.is_ascii_digit() {
             if !integer_string.is_empty() {
-                packet_symbols.push_back(Integer(integer_string.parse().unwrap()));
+                packet_symbols.push_back(Integer(
+                    integer_string
+                        .parse()
+                        .map_err(|_| index - integer_string.len())?,
+                ));
                 integer_string.clear();
             }
 
             match symbol {
-                '[' => packet_symbols.push_back(LeftBracket),
-                ']' => packet_symbols.push_back(RightBracket),
-                ',' => (),
-                _ => unreachable!(),
+                '[' => {
+                    depth += 1;
+                    packet_symbols.push_back(LeftBracket);
+                }
+                ']' if depth > 0 => {
+                    depth -= 1;
+                    packet_symbols.push_back(RightBracket);
+                }
+                ',' if depth > 0 => (),
+                _ => return Err(index),
             }
+        } else if depth == 0 {
+            return Err(index);
         } else {
             integer_string.push(symbol);
         }
     }
 
-    packet_symbols
+    if depth > 0 || packet_symbols.is_empty() {
+        return Err(packet.chars().count());
+    }
+
+    Ok(packet_symbols)
 }
 
+/// Parses the pairs of packets, separated by blank lines.
 #[aoc_generator(day13)]
-fn parse_input(input: &str) -> Vec<(Packet, Packet)> {
-    input
-        .split("\n\n")
+pub fn parse(input: &str) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let lines: Vec<_> = input.lines().enumerate().collect();
+
+    lines
+        .split(|(_, line)| line.is_empty())
+        .filter(|packet_pair| !packet_pair.is_empty())
         .map(|packet_pair| {
-            let mut packets = packet_pair.lines().map(parse_packet);
-            (packets.next().unwrap(), packets.next().unwrap())
+            let mut packets = packet_pair.iter().map(|&(line_index, packet)| {
+                parse_packet(packet).map_err(|char_index| match packet.chars().nth(char_index) {
+                    Some(symbol) => {
+                        ParseError::at_char(DAY, line_index, char_index, symbol, "a packet symbol")
+                    }
+                    None => {
+                        ParseError::at_end_of_line(DAY, line_index, packet, "a closing bracket")
+                    }
+                })
+            });
+
+            match (packets.next(), packets.next(), packet_pair.get(2)) {
+                (Some(left), Some(right), None) => Ok((left?, right?)),
+                (_, _, Some(&(line_index, packet))) => Err(ParseError::at_token(
+                    DAY,
+                    line_index,
+                    packet,
+                    packet,
+                    "a blank line between packet pairs",
+                )),
+                _ => Err(ParseError::at_end_of_line(
+                    DAY,
+                    packet_pair[0].0 + 1,
+                    "",
+                    "a second packet",
+                )),
+            }
         })
         .collect()
 }
@@ -85,8 +145,9 @@ fn is_right_order(left: &Packet, right: &Packet) -> bool {
     true
 }
 
+/// Returns the sum of the indices of the pairs that are in the right order.
 #[aoc(day13, part1)]
-fn part1(packet_pairs: &[(Packet, Packet)]) -> usize {
+pub fn part1(packet_pairs: &[(Packet, Packet)]) -> usize {
     packet_pairs
         .iter()
         .enumerate()
@@ -95,15 +156,16 @@ fn part1(packet_pairs: &[(Packet, Packet)]) -> usize {
         .sum()
 }
 
+/// Returns the decoder key for the distress signal.
 #[aoc(day13, part2)]
-fn part2(packet_pairs: &[(Packet, Packet)]) -> usize {
+pub fn part2(packet_pairs: &[(Packet, Packet)]) -> usize {
     let packets: Vec<_> = packet_pairs
         .iter()
         .flat_map(|(left, right)| [left, right])
         .collect();
 
-    let divider_packet_1 = parse_packet(DIVIDER_PACKET_1);
-    let divider_packet_2 = parse_packet(DIVIDER_PACKET_2);
+    let divider_packet_1 = parse_packet(DIVIDER_PACKET_1).unwrap();
+    let divider_packet_2 = parse_packet(DIVIDER_PACKET_2).unwrap();
 
     let (num_of_packets_before_divider_1, num_of_packets_before_divider_2) = packets.iter().fold(
         (0, 0),
@@ -126,6 +188,32 @@ fn part2(packet_pairs: &[(Packet, Packet)]) -> usize {
     //the order of [[2]] is less than the order of [[6]]
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<(Packet, Packet)>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Distress Signal"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,11 +244,16 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 13);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 13);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 13);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 140);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 140);
     }
 }
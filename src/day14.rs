@@ -1,47 +1,57 @@
+//! Day 14: Regolith Reservoir.
+
+use crate::error::ParseError;
+use crate::geometry::{BoundingBox, Point2};
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution};
+use crate::visualize::{Cell, Frame, Frames, Visualize, BACKGROUND, GRAY, SAND, WHITE};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashSet;
+use std::iter;
+
+const DAY: u8 = 14;
 
-type Coordinates = (i32, i32);
-type Path = Vec<Coordinates>;
-const SAND_POURING_POINT: Coordinates = (500, 0);
+/// The corner points of a path of solid rock.
+pub type Path = Vec<Point2<i32>>;
+const SAND_POURING_POINT: Point2<i32> = Point2::new(500, 0);
 
 struct Cave {
-    rock_coordinates: HashSet<Coordinates>,
+    rock_coordinates: HashSet<Point2<i32>>,
     depth: i32,
 }
 
 impl From<&[Path]> for Cave {
     fn from(paths: &[Path]) -> Self {
         let mut rock_coordinates = HashSet::new();
-        let mut depth = SAND_POURING_POINT.1;
+        let mut depth = SAND_POURING_POINT.y;
 
         for path in paths {
             for line in path.windows(2) {
-                if line[0].0 == line[1].0 {
-                    let x = line[0].0;
+                if line[0].x == line[1].x {
+                    let x = line[0].x;
 
-                    let y_start = i32::min(line[0].1, line[1].1);
-                    let y_end = i32::max(line[0].1, line[1].1);
+                    let y_start = i32::min(line[0].y, line[1].y);
+                    let y_end = i32::max(line[0].y, line[1].y);
 
                     if y_end > depth {
                         depth = y_end;
                     }
 
                     for y in y_start..=y_end {
-                        rock_coordinates.insert((x, y));
+                        rock_coordinates.insert(Point2::new(x, y));
                     }
                 } else {
-                    let y = line[0].1;
+                    let y = line[0].y;
 
                     if y > depth {
                         depth = y;
                     }
 
-                    let x_start = i32::min(line[0].0, line[1].0);
-                    let x_end = i32::max(line[0].0, line[1].0);
+                    let x_start = i32::min(line[0].x, line[1].x);
+                    let x_end = i32::max(line[0].x, line[1].x);
 
                     for x in x_start..=x_end {
-                        rock_coordinates.insert((x, y));
+                        rock_coordinates.insert(Point2::new(x, y));
                     }
                 }
             }
@@ -54,39 +64,65 @@ impl From<&[Path]> for Cave {
     }
 }
 
+/// Parses the scan of rock paths, one path per line.
 #[aoc_generator(day14)]
-fn parse_input(input: &str) -> Vec<Path> {
+pub fn parse(input: &str) -> Result<Vec<Path>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|path| {
-            path.split(" -> ")
-                .map(|coordinates| {
-                    let mut coordinates = coordinates.split(',');
-                    (
-                        coordinates.next().unwrap().parse().unwrap(),
-                        coordinates.next().unwrap().parse().unwrap(),
-                    )
-                })
-                .collect()
+        .enumerate()
+        .map(|(line_index, line)| {
+            let mut path: Path = Vec::new();
+
+            for point in line.split(" -> ") {
+                let (x, y) = point.split_once(',').ok_or_else(|| {
+                    ParseError::at_token(DAY, line_index, line, point, "a point x,y")
+                })?;
+
+                let parse_coordinate = |coordinate: &str| {
+                    coordinate.parse().map_err(|_| {
+                        ParseError::at_token(DAY, line_index, line, coordinate, "a coordinate")
+                    })
+                };
+
+                let coordinates = Point2::new(parse_coordinate(x)?, parse_coordinate(y)?);
+
+                if let Some(previous) = path.last() {
+                    if previous.x != coordinates.x && previous.y != coordinates.y {
+                        return Err(ParseError::at_token(
+                            DAY,
+                            line_index,
+                            line,
+                            point,
+                            "a point in line with the previous one",
+                        ));
+                    }
+                }
+
+                path.push(coordinates);
+            }
+
+            Ok(path)
         })
         .collect()
 }
 
+/// Returns the number of units of sand that come to rest before sand flows into the abyss.
 #[aoc(day14, part1)]
-fn part1(paths: &[Path]) -> usize {
+pub fn part1(paths: &[Path]) -> usize {
     let mut cave: Cave = paths.into();
     let mut sand_units_count = 0;
     let mut visited = vec![(SAND_POURING_POINT, false, false, false)];
 
-    while !visited.is_empty() {
-        let (sand_unit_coordinates, been_down, been_down_left, been_down_right) =
-            visited.pop().unwrap();
-
-        if sand_unit_coordinates.1 > cave.depth {
+    while let Some((sand_unit_coordinates, been_down, been_down_left, been_down_right)) =
+        visited.pop()
+    {
+        if sand_unit_coordinates.y > cave.depth {
             return sand_units_count;
         }
 
-        let down = (sand_unit_coordinates.0, sand_unit_coordinates.1 + 1);
+        let down = sand_unit_coordinates + Point2::new(0, 1);
 
         if !been_down && !cave.rock_coordinates.contains(&down) {
             visited.push((sand_unit_coordinates, true, been_down_left, been_down_right));
@@ -94,7 +130,7 @@ fn part1(paths: &[Path]) -> usize {
             continue;
         }
 
-        let down_left = (sand_unit_coordinates.0 - 1, sand_unit_coordinates.1 + 1);
+        let down_left = sand_unit_coordinates + Point2::new(-1, 1);
 
         if !been_down_left && !cave.rock_coordinates.contains(&down_left) {
             visited.push((sand_unit_coordinates, been_down, true, been_down_right));
@@ -102,7 +138,7 @@ fn part1(paths: &[Path]) -> usize {
             continue;
         }
 
-        let down_right = (sand_unit_coordinates.0 + 1, sand_unit_coordinates.1 + 1);
+        let down_right = sand_unit_coordinates + Point2::new(1, 1);
 
         if !been_down_right && !cave.rock_coordinates.contains(&down_right) {
             visited.push((
@@ -122,57 +158,262 @@ fn part1(paths: &[Path]) -> usize {
     sand_units_count
 }
 
+/// Returns the number of units of sand that come to rest once the floor is in place.
 #[aoc(day14, part2)]
-fn part2(paths: &[Path]) -> usize {
+pub fn part2(paths: &[Path]) -> usize {
     let mut cave: Cave = paths.into();
     let mut sand_units_count = 0;
     let floor_depth = cave.depth + 2;
 
-    let mut x_min = SAND_POURING_POINT.0;
-    let mut x_max = SAND_POURING_POINT.0;
-
-    for y in SAND_POURING_POINT.1..floor_depth {
+    for y in SAND_POURING_POINT.y..floor_depth {
+        let x_min = SAND_POURING_POINT.x - (y - SAND_POURING_POINT.y);
+        let x_max = SAND_POURING_POINT.x + (y - SAND_POURING_POINT.y);
         let mut rock_count = 2;
 
         for x in x_min..=x_max {
-            if cave.rock_coordinates.contains(&(x, y)) {
+            if cave.rock_coordinates.contains(&Point2::new(x, y)) {
                 rock_count += 1;
 
                 if rock_count >= 3 {
-                    cave.rock_coordinates.insert((x - 1, y + 1));
+                    cave.rock_coordinates.insert(Point2::new(x - 1, y + 1));
                 }
 
                 if x == x_max {
-                    cave.rock_coordinates.insert((x, y + 1));
-                    cave.rock_coordinates.insert((x + 1, y + 1));
+                    if rock_count >= 2 {
+                        cave.rock_coordinates.insert(Point2::new(x, y + 1));
+                    }
+
+                    cave.rock_coordinates.insert(Point2::new(x + 1, y + 1));
                 }
             } else {
                 sand_units_count += 1;
                 rock_count = 0;
             }
         }
-
-        x_min -= 1;
-        x_max += 1;
     }
 
     sand_units_count
 }
 
+/// Returns where the next unit of sand comes to rest, or `None` once sand
+/// flows into the abyss or the source is blocked.
+fn drop_sand(
+    cave: &Cave,
+    sand: &HashSet<Point2<i32>>,
+    floor_depth: Option<i32>,
+) -> Option<Point2<i32>> {
+    let is_free = |position: &Point2<i32>| {
+        !cave.rock_coordinates.contains(position)
+            && !sand.contains(position)
+            && floor_depth != Some(position.y)
+    };
+
+    let mut sand_unit = SAND_POURING_POINT;
+
+    if !is_free(&sand_unit) {
+        return None;
+    }
+
+    while let Some(next_position) = [Point2::new(0, 1), Point2::new(-1, 1), Point2::new(1, 1)]
+        .into_iter()
+        .map(|step| sand_unit + step)
+        .find(is_free)
+    {
+        if floor_depth.is_none() && next_position.y > cave.depth {
+            return None;
+        }
+
+        sand_unit = next_position;
+    }
+
+    Some(sand_unit)
+}
+
+impl Visualize for [Path] {
+    /// Draws the cave every time a unit of sand comes to rest.
+    fn frames(&self, part: Part) -> Frames<'_> {
+        let cave = Cave::from(self);
+
+        let (floor_depth, bounds) = match part {
+            Part::One => (
+                None,
+                BoundingBox::from_points(
+                    cave.rock_coordinates
+                        .iter()
+                        .copied()
+                        .chain(iter::once(SAND_POURING_POINT)),
+                )
+                .expect("the bounds include the source"),
+            ),
+            // The sand piles up in a triangle below the source.
+            Part::Two => {
+                let floor_depth = cave.depth + 2;
+
+                (
+                    Some(floor_depth),
+                    BoundingBox {
+                        min: Point2::new(SAND_POURING_POINT.x - floor_depth, SAND_POURING_POINT.y),
+                        max: Point2::new(SAND_POURING_POINT.x + floor_depth, floor_depth),
+                    },
+                )
+            }
+        };
+
+        let mut frame = Frame::new(bounds, Cell::new('.', BACKGROUND));
+
+        for &rock in &cave.rock_coordinates {
+            frame.draw(rock, Cell::new('#', GRAY));
+        }
+
+        if let Some(floor_depth) = floor_depth {
+            for x in bounds.min.x..=bounds.max.x {
+                frame.draw(Point2::new(x, floor_depth), Cell::new('#', GRAY));
+            }
+        }
+
+        frame.draw(SAND_POURING_POINT, Cell::new('+', WHITE));
+
+        let mut sand = HashSet::new();
+
+        Box::new(iter::once(frame.clone()).chain(iter::from_fn(move || {
+            let sand_unit = drop_sand(&cave, &sand, floor_depth)?;
+
+            sand.insert(sand_unit);
+            frame.draw(sand_unit, Cell::new('o', SAND));
+
+            Some(frame.clone())
+        })))
+    }
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Vec<Path>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Regolith Reservoir"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn frames<'a>(&self, input: &'a Self::Input, part: Part) -> Option<Frames<'a>> {
+        Some(input.frames(part))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_INPUT: &str = r"498,4 -> 498,6 -> 496,6
 503,4 -> 502,4 -> 502,9 -> 494,9";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 24);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 24);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 24);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 93);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 93);
+    }
+
+    #[test]
+    fn frames_example() {
+        let paths = parse(TEST_INPUT).unwrap();
+        let frames: Vec<_> = paths.frames(Part::One).collect();
+
+        assert_eq!(frames.len(), 25);
+        assert_eq!(
+            frames[24].to_string(),
+            r"......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########."
+        );
+        assert_eq!(paths.frames(Part::Two).count(), 94);
+    }
+
+    /// Drops units of sand one at a time until one comes to rest at the source.
+    fn part2_brute_force(paths: &[Path]) -> usize {
+        let mut cave: Cave = paths.into();
+        let floor_depth = cave.depth + 2;
+        let mut sand_units_count = 0;
+
+        while !cave.rock_coordinates.contains(&SAND_POURING_POINT) {
+            let mut sand_unit = SAND_POURING_POINT;
+
+            while let Some(next_position) =
+                [Point2::new(0, 1), Point2::new(-1, 1), Point2::new(1, 1)]
+                    .into_iter()
+                    .map(|step| sand_unit + step)
+                    .find(|next_position| !cave.rock_coordinates.contains(next_position))
+                    .filter(|_| sand_unit.y + 1 < floor_depth)
+            {
+                sand_unit = next_position;
+            }
+
+            cave.rock_coordinates.insert(sand_unit);
+            sand_units_count += 1;
+        }
+
+        sand_units_count
+    }
+
+    fn path() -> impl Strategy<Value = Path> {
+        (
+            (490..510, 1..12),
+            prop::collection::vec(-4..=4, 1..5),
+            any::<bool>(),
+        )
+            .prop_map(|((x, y), steps, starts_horizontally)| {
+                let mut path = vec![Point2::new(x, y)];
+
+                for (index, step) in steps.into_iter().enumerate() {
+                    let Point2 { x, y } = *path.last().unwrap();
+
+                    path.push(if (index % 2 == 0) == starts_horizontally {
+                        Point2::new(x + step, y)
+                    } else {
+                        Point2::new(x, i32::max(0, y + step))
+                    });
+                }
+
+                path
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn part2_matches_brute_force(paths in prop::collection::vec(path(), 1..6)) {
+            prop_assert_eq!(part2(&paths), part2_brute_force(&paths));
+        }
     }
 }
@@ -1,56 +1,65 @@
+//! Day 15: Beacon Exclusion Zone.
+
+use crate::arithmetic::{integer, Arithmetic, Integer, Overflow};
+use crate::error::ParseError;
+use crate::geometry::Point2;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
-type Coordinates = (i64, i64);
+const DAY: u8 = 15;
 
-fn manhattan_distance(start: &Coordinates, end: &Coordinates) -> i64 {
-    (start.0 - end.0).abs() + (start.1 - end.1).abs()
-}
+/// The position of a sensor and of the beacon closest to it.
+pub type Report = (Point2<i64>, Point2<i64>);
 
+/// Parses sensor and closest beacon positions, one sensor per line.
 #[aoc_generator(day15)]
-fn parse_input(input: &str) -> Vec<(Coordinates, Coordinates)> {
+pub fn parse(input: &str) -> Result<Vec<Report>, ParseError> {
     use aoc_parse::{parser, prelude::*};
 
+    let input = &preprocess::normalize(input);
+
     let parser = parser!(lines(
         "Sensor at x=" sensor_x:i64
         ", y=" sensor_y:i64
         ": closest beacon is at x=" beacon_x:i64
         ", y=" beacon_y:i64 =>
-            ((sensor_x, sensor_y), (beacon_x, beacon_y))
+            (Point2::new(sensor_x, sensor_y), Point2::new(beacon_x, beacon_y))
     ));
-    parser.parse(input).unwrap()
+    parser
+        .parse(input)
+        .map_err(|error| ParseError::from_aoc_parse(DAY, error))
 }
 
-fn non_beacon_positions(coordinates: &[(Coordinates, Coordinates)], row: i64) -> usize {
+/// Returns the number of positions in `row` where a beacon cannot be present.
+pub fn non_beacon_positions(coordinates: &[Report], row: i64) -> usize {
     let mut segments: Vec<_> = coordinates
         .iter()
         .map(|(sensor_coordinates, beacon_coordinates)| {
             (
                 *sensor_coordinates,
-                manhattan_distance(sensor_coordinates, beacon_coordinates),
+                sensor_coordinates.manhattan_distance(*beacon_coordinates),
             )
         })
-        .filter(|(sensor_coordinates, radius)| *radius >= (sensor_coordinates.1 - row).abs())
+        .filter(|(sensor_coordinates, radius)| *radius >= (sensor_coordinates.y - row).abs())
         .map(|(sensor_coordinates, radius)| {
-            let distance = radius - (sensor_coordinates.1 - row).abs();
+            let distance = radius - (sensor_coordinates.y - row).abs();
 
             (
-                sensor_coordinates.0 - distance,
-                sensor_coordinates.0 + distance,
+                sensor_coordinates.x - distance,
+                sensor_coordinates.x + distance,
             )
         })
         .collect();
 
-    let mut non_overlapping_segments = Vec::with_capacity(segments.len());
+    let mut non_overlapping_segments: Vec<(i64, i64)> = Vec::with_capacity(segments.len());
 
-    while !segments.is_empty() {
-        let mut segment = segments.pop().unwrap();
+    while let Some(mut segment) = segments.pop() {
         let mut still_non_overlapping_segments =
             Vec::with_capacity(non_overlapping_segments.len() + 1);
 
-        while !non_overlapping_segments.is_empty() {
-            let non_overlapping_segment: (i64, i64) = non_overlapping_segments.pop().unwrap();
-
+        while let Some(non_overlapping_segment) = non_overlapping_segments.pop() {
             if non_overlapping_segment.1 < segment.0 || non_overlapping_segment.0 > segment.1 {
                 still_non_overlapping_segments.push(non_overlapping_segment);
             } else {
@@ -72,7 +81,7 @@ fn non_beacon_positions(coordinates: &[(Coordinates, Coordinates)], row: i64) ->
 
     let beacons_count = coordinates
         .iter()
-        .filter(|(_, beacon_coordinates)| beacon_coordinates.1 == row)
+        .filter(|(_, beacon_coordinates)| beacon_coordinates.y == row)
         .map(|(_, beacon_coordinates)| beacon_coordinates)
         .collect::<HashSet<_>>()
         .len();
@@ -80,21 +89,31 @@ fn non_beacon_positions(coordinates: &[(Coordinates, Coordinates)], row: i64) ->
     scanned_positions_count - beacons_count
 }
 
+/// Returns the number of positions in row 2000000 where a beacon cannot be present.
 #[aoc(day15, part1)]
-fn part1(coordinates: &[(Coordinates, Coordinates)]) -> usize {
+pub fn part1(coordinates: &[Report]) -> usize {
     non_beacon_positions(coordinates, 2_000_000)
 }
 
-fn tuning_frequency(
-    coordinates: &[(Coordinates, Coordinates)],
+fn frequency(position: Point2<i64>) -> Result<Integer, Overflow> {
+    integer(position.x)
+        .try_mul(&integer(4_000_000))?
+        .try_add(&integer(position.y))
+}
+
+/// Returns the tuning frequency of the only position with both coordinates
+/// between 0 and `max_search_space_coordinate` that no sensor covers, or of the
+/// first one row by row if there are several.
+pub fn tuning_frequency(
+    coordinates: &[Report],
     max_search_space_coordinate: i64,
-) -> Option<i64> {
-    let sensor_radiuses: HashMap<_, _> = coordinates
+) -> Result<Option<Integer>, Overflow> {
+    let sensor_radiuses: Vec<_> = coordinates
         .iter()
         .map(|(sensor_coordinates, beacon_coordinates)| {
             (
                 *sensor_coordinates,
-                manhattan_distance(sensor_coordinates, beacon_coordinates),
+                sensor_coordinates.manhattan_distance(*beacon_coordinates),
             )
         })
         .collect();
@@ -102,21 +121,20 @@ fn tuning_frequency(
     for y in 0..=max_search_space_coordinate {
         let mut segments: Vec<_> = sensor_radiuses
             .iter()
-            .filter(|(&sensor_coordinates, &radius)| radius >= (sensor_coordinates.1 - y).abs())
-            .map(|(&sensor_coordinates, &radius)| {
-                let distance = radius - (sensor_coordinates.1 - y).abs();
+            .filter(|&&(sensor_coordinates, radius)| radius >= (sensor_coordinates.y - y).abs())
+            .map(|&(sensor_coordinates, radius)| {
+                let distance = radius - (sensor_coordinates.y - y).abs();
 
                 (
-                    sensor_coordinates.0 - distance,
-                    sensor_coordinates.0 + distance,
+                    sensor_coordinates.x - distance,
+                    sensor_coordinates.x + distance,
                 )
             })
             .collect();
 
-        let mut non_overlapping_segments = Vec::with_capacity(segments.len());
+        let mut non_overlapping_segments: Vec<(i64, i64)> = Vec::with_capacity(segments.len());
 
-        while !segments.is_empty() {
-            let mut segment = segments.pop().unwrap();
+        while let Some(mut segment) = segments.pop() {
             let mut processed_segments = Vec::with_capacity(non_overlapping_segments.len() + 1);
 
             if segment.1 < 0 || segment.0 > max_search_space_coordinate {
@@ -128,9 +146,7 @@ fn tuning_frequency(
                 );
             }
 
-            while !non_overlapping_segments.is_empty() {
-                let previous_segment: (i64, i64) = non_overlapping_segments.pop().unwrap();
-
+            while let Some(previous_segment) = non_overlapping_segments.pop() {
                 if previous_segment.1 < segment.0 || previous_segment.0 > segment.1 {
                     processed_segments.push(previous_segment);
                 } else {
@@ -145,23 +161,61 @@ fn tuning_frequency(
             non_overlapping_segments = processed_segments;
         }
 
-        if non_overlapping_segments.len() == 2 {
-            let x = i64::min(non_overlapping_segments[0].1, non_overlapping_segments[1].1) + 1;
-            return Some(x * 4_000_000 + y);
+        non_overlapping_segments.sort_unstable();
+        let mut x = 0;
+
+        for (x_start, x_end) in non_overlapping_segments {
+            if x_start > x {
+                break;
+            }
+
+            x = i64::max(x, x_end + 1);
+        }
+
+        if x <= max_search_space_coordinate {
+            return frequency(Point2::new(x, y)).map(Some);
         }
     }
 
-    None
+    Ok(None)
 }
 
+/// Returns the tuning frequency of the distress beacon in the 4000000 by 4000000 search space.
 #[aoc(day15, part2)]
-fn part2(coordinates: &[(Coordinates, Coordinates)]) -> Option<i64> {
-    tuning_frequency(coordinates, 4_000_000)
+pub fn part2(coordinates: &[Report]) -> Option<Integer> {
+    tuning_frequency(coordinates, 4_000_000).expect("frequencies in this search space fit in i64")
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<Report>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Beacon Exclusion Zone"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     static TEST_INPUT: &str = r"Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
@@ -180,14 +234,79 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn part1_example() {
-        assert_eq!(non_beacon_positions(&parse_input(TEST_INPUT), 10), 26);
+        assert_eq!(non_beacon_positions(&parse(TEST_INPUT).unwrap(), 10), 26);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            non_beacon_positions(&parse(&preprocess::windows(TEST_INPUT)).unwrap(), 10),
+            26
+        );
     }
 
     #[test]
     fn part2_example() {
         assert_eq!(
-            tuning_frequency(&parse_input(TEST_INPUT), 20),
-            Some(56_000_011)
+            tuning_frequency(&parse(TEST_INPUT).unwrap(), 20),
+            Ok(Some(integer(56_000_011)))
         );
     }
+
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    #[test]
+    fn large_search_space() {
+        let radius = 3_000_000_000_000;
+        let coordinates = [(Point2::new(0, 0), Point2::new(radius, 0))];
+        let answer = tuning_frequency(&coordinates, 4_000_000_000_000);
+
+        if cfg!(feature = "bigint") {
+            assert_eq!(
+                answer.map(|answer| answer.map(|answer| answer.to_string())),
+                Ok(Some("12000000000004000000".to_string()))
+            );
+        } else {
+            assert_eq!(answer, Err(Overflow));
+        }
+    }
+
+    /// Checks every position of the search space, row by row.
+    fn tuning_frequency_brute_force(
+        coordinates: &[Report],
+        max_search_space_coordinate: i64,
+    ) -> Result<Option<Integer>, Overflow> {
+        (0..=max_search_space_coordinate)
+            .flat_map(|y| (0..=max_search_space_coordinate).map(move |x| Point2::new(x, y)))
+            .find(|position| {
+                coordinates.iter().all(|(sensor, beacon)| {
+                    sensor.manhattan_distance(*position) > sensor.manhattan_distance(*beacon)
+                })
+            })
+            .map(frequency)
+            .transpose()
+    }
+
+    fn sensor() -> impl Strategy<Value = Report> {
+        ((-5..25i64, -5..25i64), (-12..=12i64, -12..=12i64)).prop_map(
+            |((sensor_x, sensor_y), (beacon_dx, beacon_dy))| {
+                (
+                    Point2::new(sensor_x, sensor_y),
+                    Point2::new(sensor_x + beacon_dx, sensor_y + beacon_dy),
+                )
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn tuning_frequency_matches_brute_force(
+            coordinates in prop::collection::vec(sensor(), 1..15),
+            max_search_space_coordinate in 1..20i64,
+        ) {
+            prop_assert_eq!(
+                tuning_frequency(&coordinates, max_search_space_coordinate),
+                tuning_frequency_brute_force(&coordinates, max_search_space_coordinate)
+            );
+        }
+    }
 }
@@ -1,17 +1,29 @@
+//! Day 16: Proboscidea Volcanium.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::search::{self, AllPairs};
+use crate::solution::{Answer, Solution};
+use crate::trace;
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const DAY: u8 = 16;
 
 #[derive(Debug)]
-struct Valve {
-    label: String,
-    flow_rate: u32,
-    connected_valves: Vec<String>,
+pub struct Valve {
+    pub label: String,
+    pub flow_rate: u32,
+    pub connected_valves: Vec<String>,
 }
 
+/// Parses the valve scan, one valve per line.
 #[aoc_generator(day16)]
-fn parse_input(input: &str) -> Vec<Valve> {
+pub fn parse(input: &str) -> Result<Vec<Valve>, ParseError> {
     use aoc_parse::{parser, prelude::*};
 
+    let input = &preprocess::normalize(input);
+
     let valves_parser = parser!(repeat_sep(string(upper+), ", "));
 
     let parser = parser!(lines(
@@ -24,29 +36,31 @@ fn parse_input(input: &str) -> Vec<Valve> {
                 connected_valves
             }
     ));
-    parser.parse(input).unwrap()
-}
+    let valves = parser
+        .parse(input)
+        .map_err(|error| ParseError::from_aoc_parse(DAY, error))?;
 
-fn floyd_warshall(distances: &mut Vec<Vec<Option<u32>>>) {
-    for k in 0..distances.len() {
-        for i in 0..distances.len() {
-            for j in 0..distances.len() {
-                if distances[i][k].is_some() && distances[k][j].is_some() {
-                    let new_distance = distances[i][k].unwrap() + distances[k][j].unwrap();
-
-                    match distances[i][j] {
-                        None => {
-                            distances[i][j] = Some(new_distance);
-                        }
-                        Some(distance) if distance > new_distance => {
-                            distances[i][j] = Some(new_distance);
-                        }
-                        _ => {}
-                    }
-                }
-            }
+    let labels: HashSet<_> = valves.iter().map(|valve| valve.label.as_str()).collect();
+
+    for (line_index, (line, valve)) in input.lines().zip(valves.iter()).enumerate() {
+        if let Some(label) = valve
+            .connected_valves
+            .iter()
+            .find(|label| !labels.contains(label.as_str()))
+        {
+            let token = &line[line.rfind(label.as_str()).unwrap()..][..label.len()];
+
+            return Err(ParseError::at_token(
+                DAY,
+                line_index,
+                line,
+                token,
+                "a label of a listed valve",
+            ));
         }
     }
+
+    Ok(valves)
 }
 
 #[derive(Debug)]
@@ -59,7 +73,7 @@ struct Node {
 
 struct Setup {
     functioning_valves: BTreeSet<usize>,
-    distances: Vec<Vec<Option<u32>>>,
+    distances: AllPairs<u32>,
     flow_rates: Vec<u32>,
     time_to_eruption: u32,
     starting_valve_index: usize,
@@ -67,7 +81,6 @@ struct Setup {
 
 impl Setup {
     fn new(valves: &[Valve], starting_valve_label: String, time_to_eruption: u32) -> Self {
-        let mut distances: Vec<Vec<Option<u32>>> = vec![vec![None; valves.len()]; valves.len()];
         let mut functioning_valves: BTreeSet<usize> = BTreeSet::new();
 
         let dictionary: HashMap<_, _> = valves
@@ -76,6 +89,7 @@ impl Setup {
             .map(|(index, valve)| (valve.label.to_owned(), index))
             .collect();
         let mut flow_rates = Vec::with_capacity(valves.len());
+        let mut tunnels = Vec::new();
         let mut starting_valve_index = 0;
 
         for (index, valve) in valves.iter().enumerate() {
@@ -89,16 +103,16 @@ impl Setup {
 
             flow_rates.push(valve.flow_rate);
 
-            distances[index][index] = Some(0);
-
             for connected_valve_label in valve.connected_valves.iter() {
                 let other_index = *dictionary.get(connected_valve_label).unwrap();
 
-                distances[index][other_index] = Some(1);
+                tunnels.push((index, other_index, 1));
             }
         }
 
-        floyd_warshall(&mut distances);
+        let distances = trace::phase("distances", || {
+            search::floyd_warshall(valves.len(), tunnels)
+        });
 
         Self {
             functioning_valves,
@@ -110,8 +124,31 @@ impl Setup {
     }
 }
 
+/// What the depth-first search of the valves did, for the `trace` feature.
+#[derive(Default)]
+struct SearchCounters {
+    explored: trace::Counter,
+    out_of_reach: trace::Counter,
+    max_stack_size: trace::Counter,
+}
+
+impl SearchCounters {
+    /// Counts a node taken off the stack, with `nodes` still on it.
+    fn explore(&mut self, nodes: &[Node]) {
+        self.explored.add(1);
+        self.max_stack_size.raise(nodes.len() as u64 + 1);
+    }
+
+    fn record(&self) {
+        self.explored.count("nodes explored");
+        self.out_of_reach.count("valves out of reach");
+        self.max_stack_size.maximum("max stack size");
+    }
+}
+
+/// Returns the most pressure you can release alone in 30 minutes.
 #[aoc(day16, part1)]
-fn part1(valves: &[Valve]) -> u32 {
+pub fn part1(valves: &[Valve]) -> u32 {
     const STARTING_VALVE_LABEL: &str = "AA";
     const TIME_TO_ERUPTION: u32 = 30;
 
@@ -120,8 +157,10 @@ fn part1(valves: &[Valve]) -> u32 {
     let mut nodes = Vec::new();
 
     for functioning_valve_index in setup.functioning_valves.iter() {
-        let distance =
-            setup.distances[setup.starting_valve_index][*functioning_valve_index].unwrap();
+        let distance = setup
+            .distances
+            .distance(setup.starting_valve_index, *functioning_valve_index)
+            .unwrap();
 
         if distance < setup.time_to_eruption {
             let time_left = setup.time_to_eruption - distance - 1;
@@ -138,43 +177,54 @@ fn part1(valves: &[Valve]) -> u32 {
     }
 
     let mut max_total_released_pressure = 0;
+    let mut counters = SearchCounters::default();
+
+    trace::phase("search", || {
+        while let Some(node) = nodes.pop() {
+            counters.explore(&nodes);
+
+            let mut more_closed_valves_in_reach = false;
+
+            for closed_valve_index in node.closed_functioning_valves.iter() {
+                let distance = setup
+                    .distances
+                    .distance(node.current_index, *closed_valve_index)
+                    .unwrap();
+
+                if distance < node.time_left {
+                    more_closed_valves_in_reach = true;
+
+                    let time_left = node.time_left - distance - 1;
+                    let mut closed_functioning_valves = node.closed_functioning_valves.clone();
+                    closed_functioning_valves.remove(closed_valve_index);
+
+                    nodes.push(Node {
+                        current_index: *closed_valve_index,
+                        total_released_pressure: node.total_released_pressure
+                            + setup.flow_rates[*closed_valve_index] * time_left,
+                        closed_functioning_valves,
+                        time_left,
+                    });
+                } else {
+                    counters.out_of_reach.add(1);
+                }
+            }
 
-    while !nodes.is_empty() {
-        let node = nodes.pop().unwrap();
-
-        let mut more_closed_valves_in_reach = false;
-
-        for closed_valve_index in node.closed_functioning_valves.iter() {
-            let distance = setup.distances[node.current_index][*closed_valve_index].unwrap();
-
-            if distance < node.time_left {
-                more_closed_valves_in_reach = true;
-
-                let time_left = node.time_left - distance - 1;
-                let mut closed_functioning_valves = node.closed_functioning_valves.clone();
-                closed_functioning_valves.remove(closed_valve_index);
-
-                nodes.push(Node {
-                    current_index: *closed_valve_index,
-                    total_released_pressure: node.total_released_pressure
-                        + setup.flow_rates[*closed_valve_index] * time_left,
-                    closed_functioning_valves,
-                    time_left,
-                });
+            if !more_closed_valves_in_reach {
+                max_total_released_pressure =
+                    u32::max(max_total_released_pressure, node.total_released_pressure);
             }
         }
+    });
 
-        if !more_closed_valves_in_reach {
-            max_total_released_pressure =
-                u32::max(max_total_released_pressure, node.total_released_pressure);
-        }
-    }
+    counters.record();
 
     max_total_released_pressure
 }
 
+/// Returns the most pressure you and an elephant can release together in 26 minutes.
 #[aoc(day16, part2)]
-fn part2(valves: &[Valve]) -> u32 {
+pub fn part2(valves: &[Valve]) -> u32 {
     const STARTING_VALVE_LABEL: &str = "AA";
     const TIME_TO_ERUPTION: u32 = 26;
 
@@ -183,8 +233,10 @@ fn part2(valves: &[Valve]) -> u32 {
     let mut nodes = Vec::new();
 
     for functioning_valve_index in setup.functioning_valves.iter() {
-        let distance =
-            setup.distances[setup.starting_valve_index][*functioning_valve_index].unwrap();
+        let distance = setup
+            .distances
+            .distance(setup.starting_valve_index, *functioning_valve_index)
+            .unwrap();
 
         if distance < setup.time_to_eruption {
             let time_left = setup.time_to_eruption - distance - 1;
@@ -201,56 +253,95 @@ fn part2(valves: &[Valve]) -> u32 {
     }
 
     let mut max_total_pressures: HashMap<BTreeSet<usize>, u32> = HashMap::new();
+    let mut counters = SearchCounters::default();
+
+    trace::phase("search", || {
+        while let Some(node) = nodes.pop() {
+            counters.explore(&nodes);
+
+            let max_total_pressure = max_total_pressures
+                .entry(
+                    setup
+                        .functioning_valves
+                        .difference(&node.closed_functioning_valves.clone())
+                        .copied()
+                        .collect::<BTreeSet<_>>(),
+                )
+                .or_insert(0);
+            if *max_total_pressure < node.total_released_pressure {
+                *max_total_pressure = node.total_released_pressure;
+            }
 
-    while !nodes.is_empty() {
-        let node = nodes.pop().unwrap();
-
-        let max_total_pressure = max_total_pressures
-            .entry(
-                setup
-                    .functioning_valves
-                    .difference(&node.closed_functioning_valves.clone())
-                    .copied()
-                    .collect::<BTreeSet<_>>(),
-            )
-            .or_insert(0);
-        if *max_total_pressure < node.total_released_pressure {
-            *max_total_pressure = node.total_released_pressure;
+            for closed_valve_index in node.closed_functioning_valves.iter() {
+                let distance = setup
+                    .distances
+                    .distance(node.current_index, *closed_valve_index)
+                    .unwrap();
+
+                if distance < node.time_left {
+                    let time_left = node.time_left - distance - 1;
+                    let mut closed_functioning_valves = node.closed_functioning_valves.clone();
+                    closed_functioning_valves.remove(closed_valve_index);
+
+                    nodes.push(Node {
+                        current_index: *closed_valve_index,
+                        total_released_pressure: node.total_released_pressure
+                            + setup.flow_rates[*closed_valve_index] * time_left,
+                        closed_functioning_valves,
+                        time_left,
+                    });
+                } else {
+                    counters.out_of_reach.add(1);
+                }
+            }
         }
+    });
+
+    counters.record();
+    trace::count("valve sets", max_total_pressures.len() as u64);
 
-        for closed_valve_index in node.closed_functioning_valves.iter() {
-            let distance = setup.distances[node.current_index][*closed_valve_index].unwrap();
-
-            if distance < node.time_left {
-                let time_left = node.time_left - distance - 1;
-                let mut closed_functioning_valves = node.closed_functioning_valves.clone();
-                closed_functioning_valves.remove(closed_valve_index);
-
-                nodes.push(Node {
-                    current_index: *closed_valve_index,
-                    total_released_pressure: node.total_released_pressure
-                        + setup.flow_rates[*closed_valve_index] * time_left,
-                    closed_functioning_valves,
-                    time_left,
-                });
+    trace::phase("pairing", || {
+        let mut max_total_released_pressure = 0;
+
+        for (human_valves, human_total_pressure) in max_total_pressures.iter() {
+            for (elephant_valves, elephant_total_pressure) in max_total_pressures.iter() {
+                if human_valves.intersection(elephant_valves).count() == 0 {
+                    max_total_released_pressure = u32::max(
+                        max_total_released_pressure,
+                        *human_total_pressure + *elephant_total_pressure,
+                    );
+                }
             }
         }
+
+        max_total_released_pressure
+    })
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Vec<Valve>;
+
+    fn day(&self) -> u8 {
+        DAY
     }
 
-    let mut max_total_released_pressure = 0;
+    fn name(&self) -> &'static str {
+        "Proboscidea Volcanium"
+    }
 
-    for (human_valves, human_total_pressure) in max_total_pressures.iter() {
-        for (elephant_valves, elephant_total_pressure) in max_total_pressures.iter() {
-            if human_valves.intersection(elephant_valves).count() == 0 {
-                max_total_released_pressure = u32::max(
-                    max_total_released_pressure,
-                    *human_total_pressure + *elephant_total_pressure,
-                );
-            }
-        }
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
 
-    max_total_released_pressure
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[cfg(test)]
@@ -270,11 +361,33 @@ Valve JJ has flow rate=21; tunnel leads to valve II";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 1_651);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 1_651);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            1_651
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 1_707);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 1_707);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn part2_trace() {
+        trace::take();
+        part2(&parse(TEST_INPUT).unwrap());
+        let trace = trace::take();
+
+        assert!(trace.counters["nodes explored"] >= trace.counters["valve sets"]);
+        assert!(trace.counters["max stack size"] > 0);
+        assert!(["distances", "search", "pairing"]
+            .iter()
+            .all(|phase| trace.phases.contains_key(phase)));
     }
 }
@@ -1,16 +1,25 @@
+//! Day 17: Pyroclastic Flow.
+
+use crate::cycle;
 use crate::day17::Direction::*;
 use crate::day17::TileType::*;
+use crate::error::ParseError;
+use crate::geometry::{BoundingBox, Point2};
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution};
+use crate::visualize::{Cell, Frame, Frames, Visualize, BACKGROUND, GOLD, GRAY, WHITE};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 
+const DAY: u8 = 17;
+
 const SMALLER_NUMBER_OF_ROCKS: usize = 2_022;
 const LARGER_NUMBER_OF_ROCKS: usize = 1_000_000_000_000;
 
 const CHAMBER_WIDTH: usize = 7;
 
-#[derive(Debug)]
-enum Direction {
+#[derive(Clone, Copy, Debug)]
+pub enum Direction {
     Left,
     Right,
 }
@@ -28,6 +37,7 @@ enum TileType {
     Empty,
 }
 
+#[derive(Clone)]
 struct Chamber {
     tiles: Vec<[TileType; CHAMBER_WIDTH]>,
     height: usize,
@@ -104,14 +114,49 @@ fn get_shapes() -> Vec<Shape> {
         .collect()
 }
 
+/// Parses the jet pattern.
 #[aoc_generator(day17)]
-fn parse_input(input: &str) -> Vec<Direction> {
-    input
+pub fn parse(input: &str) -> Result<Vec<Direction>, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let mut lines = input.lines().enumerate();
+
+    let (line_index, jet_pattern) = lines
+        .next()
+        .ok_or_else(|| ParseError::at_end_of_line(DAY, 0, "", "a jet pattern"))?;
+
+    if let Some((line_index, line)) = lines.find(|(_, line)| !line.is_empty()) {
+        return Err(ParseError::at_token(
+            DAY,
+            line_index,
+            line,
+            line,
+            "end of input",
+        ));
+    }
+
+    if jet_pattern.is_empty() {
+        return Err(ParseError::at_end_of_line(
+            DAY,
+            line_index,
+            jet_pattern,
+            "a jet pattern",
+        ));
+    }
+
+    jet_pattern
         .chars()
-        .map(|direction| match direction {
-            '<' => Left,
-            '>' => Right,
-            _ => unreachable!(),
+        .enumerate()
+        .map(|(char_index, direction)| match direction {
+            '<' => Ok(Left),
+            '>' => Ok(Right),
+            _ => Err(ParseError::at_char(
+                DAY,
+                line_index,
+                char_index,
+                direction,
+                "one of '<', '>'",
+            )),
         })
         .collect()
 }
@@ -243,8 +288,9 @@ fn truncate_chamber(chamber: &mut Chamber) {
     }
 }
 
+/// Returns the height of the tower after 2022 rocks have stopped falling.
 #[aoc(day17, part1)]
-fn part1(directions: &[Direction]) -> usize {
+pub fn part1(directions: &[Direction]) -> usize {
     let mut chamber = Chamber {
         tiles: Vec::new(),
         height: 0,
@@ -286,87 +332,354 @@ struct State {
     shape_index: usize,
 }
 
-#[aoc(day17, part2)]
-fn part2(directions: &[Direction]) -> usize {
-    let mut chamber = Chamber {
-        tiles: Vec::new(),
-        height: 0,
-    };
+/// The chamber after some rocks have stopped falling.
+#[derive(Clone)]
+struct Tower {
+    chamber: Chamber,
+    rock_number: usize,
+    direction_index: usize,
+}
 
+/// Returns the height of the tower after `number_of_rocks` rocks have stopped
+/// falling, skipping ahead once the top of the tower starts repeating.
+fn tower_height(directions: &[Direction], number_of_rocks: usize) -> usize {
     let shapes = get_shapes();
-    let mut direction_index = 0;
-
-    let mut states: HashMap<State, (usize, usize)> = HashMap::new();
-    let mut rock_number = 0;
 
-    let mut cycle_found = false;
+    let tower = Tower {
+        chamber: Chamber {
+            tiles: Vec::new(),
+            height: 0,
+        },
+        rock_number: 0,
+        direction_index: 0,
+    };
 
-    while rock_number < LARGER_NUMBER_OF_ROCKS {
-        let shape_index = rock_number % shapes.len();
-        let shape = shapes.get(shape_index).unwrap();
-        let mut position = starting_position(&chamber);
+    let drop_rock = |tower: &Tower| {
+        let mut tower = tower.clone();
+        let shape = shapes.get(tower.rock_number % shapes.len()).unwrap();
+        let mut position = starting_position(&tower.chamber);
 
         loop {
-            let direction = directions.get(direction_index).unwrap();
-            direction_index = (direction_index + 1) % directions.len();
+            let direction = directions.get(tower.direction_index).unwrap();
+            tower.direction_index = (tower.direction_index + 1) % directions.len();
 
-            if let Ok(new_position) = push_rock(shape, &position, direction, &chamber) {
+            if let Ok(new_position) = push_rock(shape, &position, direction, &tower.chamber) {
                 position = new_position;
             }
 
-            if let Ok(new_position) = fall_down(shape, &position, &chamber) {
+            if let Ok(new_position) = fall_down(shape, &position, &tower.chamber) {
                 position = new_position;
             } else {
-                update_chamber(shape, &position, &mut chamber);
+                update_chamber(shape, &position, &mut tower.chamber);
+                truncate_chamber(&mut tower.chamber);
 
-                if !cycle_found {
-                    truncate_chamber(&mut chamber);
+                break;
+            }
+        }
 
-                    let state = State {
-                        tiles: chamber.tiles.clone(),
-                        direction_index,
-                        shape_index,
-                    };
+        tower.rock_number += 1;
+        tower
+    };
 
-                    if let Some((prev_rock_number, prev_height)) = states.get(&state) {
-                        let cycle_len = rock_number - prev_rock_number;
-                        let height_delta = chamber.height - prev_height;
+    cycle::hashing(
+        tower,
+        drop_rock,
+        |tower| State {
+            tiles: tower.chamber.tiles.clone(),
+            direction_index: tower.direction_index,
+            shape_index: tower.rock_number % shapes.len(),
+        },
+        |tower| tower.chamber.height,
+        number_of_rocks,
+    )
+}
 
-                        let remaining_rocks = LARGER_NUMBER_OF_ROCKS - 1 - rock_number;
-                        let remaining_cycles = remaining_rocks / cycle_len;
+/// Returns the height of the tower after 1000000000000 rocks have stopped falling.
+#[aoc(day17, part2)]
+pub fn part2(directions: &[Direction]) -> usize {
+    tower_height(directions, LARGER_NUMBER_OF_ROCKS)
+}
+
+/// The number of chamber rows shown above the floor or the top of the tower.
+const VIEW_HEIGHT: usize = 24;
+
+struct FallingRocks<'a> {
+    directions: &'a [Direction],
+    shapes: Vec<Shape>,
+    chamber: Chamber,
+    direction_index: usize,
+    rock_number: usize,
+    falling_rock: Option<(usize, (usize, usize))>,
+}
+
+impl FallingRocks<'_> {
+    fn frame(&self) -> Frame {
+        let bounds = BoundingBox {
+            min: Point2::new(0, 0),
+            max: Point2::new(CHAMBER_WIDTH as i32 + 1, VIEW_HEIGHT as i32),
+        };
+        let mut frame = Frame::new(bounds, Cell::new('.', BACKGROUND));
+
+        // A falling rock reaches at most seven rows above the tower.
+        let top = usize::max(VIEW_HEIGHT, self.chamber.tiles.len() + 7);
+
+        for row in 0..=VIEW_HEIGHT {
+            let frame_y = row as i32;
+
+            let Some(y) = (top - 1).checked_sub(row) else {
+                frame.draw(Point2::new(0, frame_y), Cell::new('+', GRAY));
+                frame.draw(
+                    Point2::new(CHAMBER_WIDTH as i32 + 1, frame_y),
+                    Cell::new('+', GRAY),
+                );
+
+                for x in 1..=CHAMBER_WIDTH as i32 {
+                    frame.draw(Point2::new(x, frame_y), Cell::new('-', GRAY));
+                }
 
-                        chamber.height += remaining_cycles * height_delta;
-                        rock_number += remaining_cycles * cycle_len;
+                continue;
+            };
 
-                        cycle_found = true;
-                    } else {
-                        states.insert(state, (rock_number, chamber.height));
+            frame.draw(Point2::new(0, frame_y), Cell::new('|', GRAY));
+            frame.draw(
+                Point2::new(CHAMBER_WIDTH as i32 + 1, frame_y),
+                Cell::new('|', GRAY),
+            );
+
+            if let Some(tiles) = self.chamber.tiles.get(y) {
+                for (x, tile) in tiles.iter().enumerate() {
+                    if *tile == Rock {
+                        frame.draw(Point2::new(x as i32 + 1, frame_y), Cell::new('#', WHITE));
                     }
                 }
+            }
 
-                break;
+            if let Some((shape_index, position)) = self.falling_rock {
+                let shape = &self.shapes[shape_index];
+
+                if let Some(shape_row) =
+                    y.checked_sub(position.1).and_then(|dy| shape.tiles.get(dy))
+                {
+                    for (dx, tile) in shape_row.iter().enumerate() {
+                        if *tile == Rock {
+                            let x = (position.0 + dx) as i32 + 1;
+                            frame.draw(Point2::new(x, frame_y), Cell::new('@', GOLD));
+                        }
+                    }
+                }
             }
         }
 
-        rock_number += 1;
+        frame
     }
+}
 
-    chamber.height
+impl Iterator for FallingRocks<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.falling_rock {
+            None if self.rock_number == SMALLER_NUMBER_OF_ROCKS || self.directions.is_empty() => {
+                return None
+            }
+            None => {
+                let shape_index = self.rock_number % self.shapes.len();
+                self.falling_rock = Some((shape_index, starting_position(&self.chamber)));
+            }
+            Some((shape_index, mut position)) => {
+                let shape = &self.shapes[shape_index];
+                let direction = &self.directions[self.direction_index];
+                self.direction_index = (self.direction_index + 1) % self.directions.len();
+
+                if let Ok(new_position) = push_rock(shape, &position, direction, &self.chamber) {
+                    position = new_position;
+                }
+
+                if let Ok(new_position) = fall_down(shape, &position, &self.chamber) {
+                    self.falling_rock = Some((shape_index, new_position));
+                } else {
+                    update_chamber(shape, &position, &mut self.chamber);
+                    self.falling_rock = None;
+                    self.rock_number += 1;
+                }
+            }
+        }
+
+        Some(self.frame())
+    }
+}
+
+impl Visualize for [Direction] {
+    /// Draws the top of the chamber after every jet push and fall of the first
+    /// 2022 rocks, for either part.
+    fn frames(&self, _part: Part) -> Frames<'_> {
+        Box::new(FallingRocks {
+            directions: self,
+            shapes: get_shapes(),
+            chamber: Chamber {
+                tiles: Vec::new(),
+                height: 0,
+            },
+            direction_index: 0,
+            rock_number: 0,
+            falling_rock: None,
+        })
+    }
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Vec<Direction>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Pyroclastic Flow"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn frames<'a>(&self, input: &'a Self::Input, part: Part) -> Option<Frames<'a>> {
+        Some(input.frames(part))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
 
     const TEST_INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 3_068);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 3_068);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            3_068
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 1_514_285_714_288);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 1_514_285_714_288);
+    }
+
+    /// Drops every rock without looking for a cycle.
+    #[test]
+    fn frames_example() {
+        let directions = parse(TEST_INPUT).unwrap();
+        let frames: Vec<_> = directions.frames(Part::One).collect();
+        let bottom = |frame: &Frame, rows: usize| {
+            let picture = frame.to_string();
+            let lines: Vec<_> = picture.lines().collect();
+            lines[lines.len() - rows..].join("\n")
+        };
+
+        assert_eq!(
+            bottom(&frames[0], 5),
+            "|..@@@@.|\n|.......|\n|.......|\n|.......|\n+-------+"
+        );
+        assert_eq!(bottom(&frames[4], 2), "|..####.|\n+-------+");
+        assert_eq!(
+            bottom(&frames[5], 9),
+            r"|.......|
+|...@...|
+|..@@@..|
+|...@...|
+|.......|
+|.......|
+|.......|
+|..####.|
++-------+"
+        );
+    }
+
+    fn tower_height_brute_force(directions: &[Direction], number_of_rocks: usize) -> usize {
+        let shapes: [&[(i64, i64)]; 5] = [
+            &[(0, 0), (1, 0), (2, 0), (3, 0)],
+            &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)],
+            &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+            &[(0, 0), (0, 1), (0, 2), (0, 3)],
+            &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        ];
+        let mut rocks = HashSet::new();
+        let mut height = 0;
+        let mut jets = directions.iter().cycle();
+
+        let fits = |rocks: &HashSet<(i64, i64)>, shape: &[(i64, i64)], (x, y): (i64, i64)| {
+            shape.iter().all(|&(dx, dy)| {
+                (0..CHAMBER_WIDTH as i64).contains(&(x + dx))
+                    && y + dy >= 0
+                    && !rocks.contains(&(x + dx, y + dy))
+            })
+        };
+
+        for shape in shapes.into_iter().cycle().take(number_of_rocks) {
+            let mut position = (2, height + 3);
+
+            loop {
+                let pushed = match jets.next().unwrap() {
+                    Left => (position.0 - 1, position.1),
+                    Right => (position.0 + 1, position.1),
+                };
+
+                if fits(&rocks, shape, pushed) {
+                    position = pushed;
+                }
+
+                if !fits(&rocks, shape, (position.0, position.1 - 1)) {
+                    break;
+                }
+
+                position.1 -= 1;
+            }
+
+            for &(dx, dy) in shape {
+                rocks.insert((position.0 + dx, position.1 + dy));
+                height = i64::max(height, position.1 + dy + 1);
+            }
+        }
+
+        height as usize
+    }
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop_oneof![Just(Left), Just(Right)]
+    }
+
+    proptest! {
+        // Short jet patterns can keep the tower from ever closing off, and then
+        // every rock clones the whole chamber, so the cases are few and small.
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn tower_height_matches_brute_force(
+            directions in prop::collection::vec(direction(), 1..50),
+            number_of_rocks in 0..1_000usize,
+        ) {
+            prop_assert_eq!(
+                tower_height(&directions, number_of_rocks),
+                tower_height_brute_force(&directions, number_of_rocks)
+            );
+        }
     }
 }
@@ -1,98 +1,100 @@
+//! Day 18: Boiling Boulders.
+
+use crate::error::ParseError;
+use crate::geometry::{BoundingBox, Point3};
+use crate::preprocess;
+use crate::search;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
 
-type Coordinates = (i32, i32, i32);
+const DAY: u8 = 18;
 
+/// Parses the positions of the lava droplet cubes, one per line.
 #[aoc_generator(day18)]
-fn parse_input(input: &str) -> Vec<Coordinates> {
+pub fn parse(input: &str) -> Result<Vec<Point3<i32>>, ParseError> {
     use aoc_parse::{parser, prelude::*};
 
-    parser!(lines((i32 "," i32 "," i32))).parse(input).unwrap()
+    let input = &preprocess::normalize(input);
+
+    parser!(lines(x:i32 "," y:i32 "," z:i32 => Point3::new(x, y, z)))
+        .parse(input)
+        .map_err(|error| ParseError::from_aoc_parse(DAY, error))
 }
 
+/// Returns the surface area of the lava droplet, including air pockets.
 #[aoc(day18, part1)]
-fn part1(cubes: &[Coordinates]) -> usize {
-    let cubes: HashSet<Coordinates> = cubes.iter().copied().collect();
+pub fn part1(cubes: &[Point3<i32>]) -> usize {
+    let cubes: HashSet<Point3<i32>> = cubes.iter().copied().collect();
 
     cubes
         .iter()
         .map(|cube| {
-            6 - [
-                (-1, 0, 0),
-                (1, 0, 0),
-                (0, -1, 0),
-                (0, 1, 0),
-                (0, 0, -1),
-                (0, 0, 1),
-            ]
-            .into_iter()
-            .map(|(x, y, z)| (x + cube.0, y + cube.1, z + cube.2))
-            .filter(|(x, y, z)| cubes.contains(&(*x, *y, *z)))
-            .count()
+            6 - cube
+                .neighbors6()
+                .filter(|neighbor| cubes.contains(neighbor))
+                .count()
         })
         .sum()
 }
 
+/// Returns the exterior surface area of the lava droplet.
 #[aoc(day18, part2)]
-fn part2(cubes: &[Coordinates]) -> usize {
-    let (min_x, max_x, min_y, max_y, min_z, max_z) = cubes.iter().fold(
-        (
-            cubes[0].0 - 1,
-            cubes[0].0 + 1,
-            cubes[0].1 - 1,
-            cubes[0].1 + 1,
-            cubes[0].2 - 1,
-            cubes[0].2 + 1,
-        ),
-        |(min_x, max_x, min_y, max_y, min_z, max_z), cube| {
-            (
-                i32::min(min_x, cube.0 - 1),
-                i32::max(max_x, cube.0 + 1),
-                i32::min(min_y, cube.1 - 1),
-                i32::max(max_y, cube.1 + 1),
-                i32::min(min_z, cube.2 - 1),
-                i32::max(max_z, cube.2 + 1),
-            )
+pub fn part2(cubes: &[Point3<i32>]) -> usize {
+    let Some(bounding_box) = BoundingBox::from_points(cubes.iter().copied()) else {
+        return 0;
+    };
+    let bounding_box = bounding_box.expand(1);
+    let cubes: HashSet<Point3<i32>> = cubes.iter().copied().collect();
+
+    let is_air = |coordinates: Point3<i32>| {
+        bounding_box.contains(coordinates) && !cubes.contains(&coordinates)
+    };
+    let outside = search::bfs(
+        [bounding_box.min],
+        |&coordinates| {
+            coordinates
+                .neighbors6()
+                .filter(move |&neighbor| is_air(neighbor))
         },
+        |_| false,
     );
 
-    let mut queue = VecDeque::from(vec![(min_x, min_y, min_z)]);
-    let mut visited: HashSet<Coordinates> = HashSet::from([(min_x, min_y, min_z)]);
+    outside
+        .nodes()
+        .map(|coordinates| {
+            coordinates
+                .neighbors6()
+                .filter(|neighbor| cubes.contains(neighbor))
+                .count()
+        })
+        .sum()
+}
 
-    let mut external_sides_count = 0;
+pub struct Day18;
 
-    while !queue.is_empty() {
-        let current_coordinates = queue.pop_front().unwrap();
+impl Solution for Day18 {
+    type Input = Vec<Point3<i32>>;
 
-        for (x, y, z) in [
-            (-1, 0, 0),
-            (1, 0, 0),
-            (0, -1, 0),
-            (0, 1, 0),
-            (0, 0, -1),
-            (0, 0, 1),
-        ]
-        .iter()
-        .map(|(x, y, z)| {
-            (
-                x + current_coordinates.0,
-                y + current_coordinates.1,
-                z + current_coordinates.2,
-            )
-        })
-        .filter(|(x, y, z)| {
-            *x >= min_x && *x <= max_x && *y >= min_y && *y <= max_y && *z >= min_z && *z <= max_z
-        }) {
-            if cubes.contains(&(x, y, z)) {
-                external_sides_count += 1;
-            } else if !visited.contains(&(x, y, z)) {
-                queue.push_back((x, y, z));
-                visited.insert((x, y, z));
-            }
-        }
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Boiling Boulders"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
     }
 
-    external_sides_count
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[cfg(test)]
@@ -115,11 +117,16 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 64);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 64);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 64);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 58);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 58);
     }
 }
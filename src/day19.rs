@@ -1,9 +1,17 @@
+//! Day 19: Not Enough Minerals.
+
 use crate::day19::ResourceType::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
+use crate::trace;
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::cmp::Ordering;
 use std::cmp::Ordering::*;
 use std::ops::SubAssign;
 
+const DAY: u8 = 19;
+
 #[derive(Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd, Debug)]
 enum ResourceType {
     Ore = 0,
@@ -75,7 +83,9 @@ struct State {
     resources: ResourcesCombination,
 }
 
-struct Blueprint {
+/// The costs of the four kinds of robots.
+#[derive(Eq, PartialEq, Debug)]
+pub struct Blueprint {
     id: usize,
     robot_costs: [ResourcesCombination; 4],
 }
@@ -97,7 +107,8 @@ impl Blueprint {
         self.max_geodes(24) * self.id
     }
 
-    fn max_geodes(&self, time_left: usize) -> usize {
+    /// Returns the largest number of geodes that can be opened in `time_left` minutes.
+    pub fn max_geodes(&self, time_left: usize) -> usize {
         let mut states = Vec::new();
 
         states.push(State {
@@ -107,76 +118,93 @@ impl Blueprint {
         });
 
         let mut max_geodes_quantity = 0;
-
-        while !states.is_empty() {
-            let state = states.pop().unwrap();
-
-            'outer: for next_robot in 0usize..4 {
-                let next_robot: ResourceType = next_robot.try_into().unwrap();
-                let cost = self.cost(&next_robot);
-
-                if next_robot != Geode
-                    && state.robots[next_robot as usize] * (time_left - state.time)
-                        + state.resources.resource_quantity(&next_robot)
-                        >= self.highest_cost(&next_robot) * (time_left - state.time)
-                {
-                    continue;
-                }
-
-                let mut time = state.time;
-                let mut resources = state.resources;
-
-                while resources.partial_cmp(cost) != Some(Greater)
-                    && resources.partial_cmp(cost) != Some(Equal)
-                {
-                    time += 1;
-                    for resource_type in 0..3 {
-                        resources.add_resource(
-                            resource_type.try_into().unwrap(),
-                            state.robots[resource_type],
-                        );
+        let mut explored = trace::Counter::new();
+        let mut enough_robots = trace::Counter::new();
+        let mut out_of_time = trace::Counter::new();
+        let mut max_stack_size = trace::Counter::new();
+
+        trace::phase("search", || {
+            while let Some(state) = states.pop() {
+                explored.add(1);
+                max_stack_size.raise(states.len() as u64 + 1);
+
+                'outer: for next_robot in 0usize..4 {
+                    let next_robot: ResourceType = next_robot.try_into().unwrap();
+                    let cost = self.cost(&next_robot);
+
+                    if next_robot != Geode
+                        && state.robots[next_robot as usize] * (time_left - state.time)
+                            + state.resources.resource_quantity(&next_robot)
+                            >= self.highest_cost(&next_robot) * (time_left - state.time)
+                    {
+                        enough_robots.add(1);
+                        continue;
                     }
 
-                    if time >= time_left - 1 {
-                        continue 'outer;
+                    let mut time = state.time;
+                    let mut resources = state.resources;
+
+                    while resources.partial_cmp(cost) != Some(Greater)
+                        && resources.partial_cmp(cost) != Some(Equal)
+                    {
+                        time += 1;
+                        for resource_type in 0..3 {
+                            resources.add_resource(
+                                resource_type.try_into().unwrap(),
+                                state.robots[resource_type],
+                            );
+                        }
+
+                        if time >= time_left - 1 {
+                            out_of_time.add(1);
+                            continue 'outer;
+                        }
                     }
-                }
 
-                resources -= cost;
-                let mut robots = state.robots;
+                    resources -= cost;
+                    let mut robots = state.robots;
 
-                for (resource_type, quantity) in robots.iter().enumerate().take(3) {
-                    resources.add_resource(resource_type.try_into().unwrap(), *quantity);
-                }
+                    for (resource_type, quantity) in robots.iter().enumerate().take(3) {
+                        resources.add_resource(resource_type.try_into().unwrap(), *quantity);
+                    }
 
-                time += 1;
+                    time += 1;
 
-                if next_robot == Geode {
-                    resources.add_resource(Geode, time_left - time);
-                    max_geodes_quantity =
-                        usize::max(max_geodes_quantity, resources.resource_quantity(&Geode));
-                }
+                    if next_robot == Geode {
+                        resources.add_resource(Geode, time_left - time);
+                        max_geodes_quantity =
+                            usize::max(max_geodes_quantity, resources.resource_quantity(&Geode));
+                    }
 
-                robots[next_robot as usize] += 1;
+                    robots[next_robot as usize] += 1;
 
-                if time < time_left {
-                    states.push(State {
-                        time,
-                        robots,
-                        resources,
-                    });
+                    if time < time_left {
+                        states.push(State {
+                            time,
+                            robots,
+                            resources,
+                        });
+                    }
                 }
             }
-        }
+        });
+
+        explored.count("states explored");
+        enough_robots.count("robots pruned, enough of them");
+        out_of_time.count("robots pruned, out of time");
+        max_stack_size.maximum("max stack size");
 
         max_geodes_quantity
     }
 }
 
+/// Parses the blueprints, one per line.
 #[aoc_generator(day19)]
-fn parse_input(input: &str) -> Vec<Blueprint> {
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
     use aoc_parse::{parser, prelude::*};
 
+    let input = &preprocess::normalize(input);
+
     parser!(lines(
         "Blueprint " id:usize ": "
         "Each ore robot costs " ore_robot_ore_cost:usize " ore. "
@@ -198,19 +226,21 @@ fn parse_input(input: &str) -> Vec<Blueprint> {
             }
     ))
     .parse(input)
-    .unwrap()
+    .map_err(|error| ParseError::from_aoc_parse(DAY, error))
 }
 
+/// Returns the sum of the quality levels of all blueprints.
 #[aoc(day19, part1)]
-fn part1(blueprints: &[Blueprint]) -> usize {
+pub fn part1(blueprints: &[Blueprint]) -> usize {
     blueprints
         .iter()
         .map(|blueprint| blueprint.quality_level())
         .sum()
 }
 
+/// Returns the product of the geodes opened in 32 minutes by the first three blueprints.
 #[aoc(day19, part2)]
-fn part2(blueprints: &[Blueprint]) -> usize {
+pub fn part2(blueprints: &[Blueprint]) -> usize {
     blueprints
         .iter()
         .take(3)
@@ -218,6 +248,32 @@ fn part2(blueprints: &[Blueprint]) -> usize {
         .product()
 }
 
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Vec<Blueprint>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Not Enough Minerals"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,16 +283,48 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 33);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 33);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            parse(&preprocess::windows(TEST_INPUT)).unwrap(),
+            parse(TEST_INPUT).unwrap()
+        );
     }
 
     #[test]
     fn part2_example1() {
-        assert_eq!(parse_input(TEST_INPUT)[0].max_geodes(32), 56);
+        assert_eq!(parse(TEST_INPUT).unwrap()[0].max_geodes(32), 56);
     }
 
     #[test]
     fn part2_example2() {
-        assert_eq!(parse_input(TEST_INPUT)[1].max_geodes(32), 62);
+        assert_eq!(parse(TEST_INPUT).unwrap()[1].max_geodes(32), 62);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn max_geodes_trace() {
+        let blueprints = parse(TEST_INPUT).unwrap();
+
+        trace::take();
+        blueprints[0].max_geodes(24);
+        let first = trace::take();
+        blueprints[0].max_geodes(24);
+        blueprints[0].max_geodes(24);
+        let twice = trace::take();
+
+        assert!(first.counters["states explored"] > 0);
+        assert!(first.counters["robots pruned, out of time"] > 0);
+        assert_eq!(
+            twice.counters["states explored"],
+            2 * first.counters["states explored"]
+        );
+        assert_eq!(
+            twice.counters["max stack size"],
+            first.counters["max stack size"]
+        );
     }
 }
@@ -1,19 +1,50 @@
+//! Day 20: Grove Positioning System.
+
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
 
+const DAY: u8 = 20;
+
 const FIRST_INDEX: usize = 1_000;
 const SECOND_INDEX: usize = 2_000;
 const THIRD_INDEX: usize = 3_000;
 const DECRYPTION_KEY: i64 = 811_589_153;
 
+/// Parses the encrypted file, one number per line.
 #[aoc_generator(day20)]
-fn parse_input(input: &str) -> Vec<i64> {
-    input
+pub fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let numbers = input
         .lines()
-        .map(|number| number.parse().unwrap())
-        .collect()
+        .enumerate()
+        .map(|(line_index, number)| {
+            number
+                .parse()
+                .map_err(|_| ParseError::at_token(DAY, line_index, number, number, "a number"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !numbers.contains(&0) {
+        return Err(ParseError::at_end_of_line(
+            DAY,
+            numbers.len(),
+            "",
+            "a number 0 in the file",
+        ));
+    }
+
+    Ok(numbers)
 }
 
 fn wrap_to_next(index: i64, vec_len: usize) -> usize {
+    // A file of a single number leaves nothing to move it around.
+    if vec_len == 0 {
+        return 0;
+    }
+
     let vec_len = vec_len as i64;
     let index = index % vec_len;
 
@@ -25,8 +56,9 @@ fn wrap(index: i64, vec_len: usize) -> usize {
     ((index % vec_len + vec_len) % vec_len) as usize
 }
 
+/// Returns the sum of the grove coordinates after mixing the file once.
 #[aoc(day20, part1)]
-fn part1(original_order: &[i64]) -> i64 {
+pub fn part1(original_order: &[i64]) -> i64 {
     let mut order = original_order.to_vec();
     let mut indexes: Vec<_> = original_order
         .iter()
@@ -54,8 +86,9 @@ fn part1(original_order: &[i64]) -> i64 {
         + order[wrap((index_0 + THIRD_INDEX) as i64, order.len())]
 }
 
+/// Returns the sum of the grove coordinates after applying the decryption key and mixing ten times.
 #[aoc(day20, part2)]
-fn part2(original_order: &[i64]) -> i64 {
+pub fn part2(original_order: &[i64]) -> i64 {
     let mut order: Vec<_> = original_order
         .iter()
         .map(|element| *element * DECRYPTION_KEY)
@@ -89,6 +122,32 @@ fn part2(original_order: &[i64]) -> i64 {
         + order[wrap((index_0 + THIRD_INDEX) as i64, order.len())]
 }
 
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<i64>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Grove Positioning System"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,11 +162,22 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 3);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 3);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()), 3);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 1_623_178_306);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 1_623_178_306);
+    }
+
+    #[test]
+    fn single_number() {
+        assert_eq!(part1(&[0]), 0);
+        assert_eq!(part2(&[0]), 0);
     }
 }
@@ -1,35 +1,49 @@
+//! Day 21: Monkey Math.
+
+use crate::arithmetic::{integer, Arithmetic, Integer, Overflow};
 use crate::day21::Operator::*;
 use crate::day21::Yell::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Solution};
 use aoc_runner_derive::{aoc, aoc_generator};
-use num_rational::Rational64;
+use num_rational::Ratio;
 use num_traits::identities::*;
 use std::collections::HashMap;
 
+const DAY: u8 = 21;
+
 const ROOT_MONKEY_NAME: &str = "root";
 const MY_NAME: &str = "humn";
 
 #[derive(Clone, Hash, Eq, PartialEq)]
-enum Operator {
+pub enum Operator {
     Add,
     Sub,
     Mul,
     Div,
 }
 
+/// What a monkey yells: a number or the result of an operation on two other monkeys.
 #[derive(Clone, Hash, Eq, PartialEq)]
-enum Yell {
+pub enum Yell {
     Number(i64),
     Operation(String, Operator, String),
 }
 
+type Rational = Ratio<Integer>;
+
 #[derive(Clone, Eq, PartialEq)]
-struct LinearPolynomial(Rational64, Rational64);
+struct LinearPolynomial(Rational, Rational);
 
+/// Parses the monkey jobs, one monkey per line, keyed by monkey name.
 #[aoc_generator(day21)]
-fn parse_input(input: &str) -> HashMap<String, Yell> {
+pub fn parse(input: &str) -> Result<HashMap<String, Yell>, ParseError> {
     use aoc_parse::{parser, prelude::*};
 
-    parser!(lines(
+    let input = &preprocess::normalize(input);
+
+    let monkey_jobs = parser!(lines(
         name:string(lower+) ": "
         yell:{
             lhs:string(lower+) " "
@@ -46,41 +60,74 @@ fn parse_input(input: &str) -> HashMap<String, Yell> {
             (name, yell)
     ))
     .parse(input)
-    .unwrap()
+    .map_err(|error| ParseError::from_aoc_parse(DAY, error))?
     .into_iter()
-    .collect()
+    .collect::<HashMap<_, _>>();
+
+    for (line_index, line) in input.lines().enumerate() {
+        for name in line
+            .split(|char: char| !char.is_ascii_lowercase())
+            .skip(1)
+            .filter(|name| !name.is_empty())
+        {
+            if !monkey_jobs.contains_key(name) {
+                return Err(ParseError::at_token(
+                    DAY,
+                    line_index,
+                    line,
+                    name,
+                    "a name of a listed monkey",
+                ));
+            }
+        }
+    }
+
+    if !monkey_jobs.contains_key(ROOT_MONKEY_NAME) {
+        return Err(ParseError::at_end_of_line(
+            DAY,
+            monkey_jobs.len(),
+            "",
+            format!("a {ROOT_MONKEY_NAME:?} monkey"),
+        ));
+    }
+
+    Ok(monkey_jobs)
 }
 
-fn solve_first_riddle(monkey_name: &String, monkey_jobs: &HashMap<String, Yell>) -> i64 {
+fn solve_first_riddle(
+    monkey_name: &String,
+    monkey_jobs: &HashMap<String, Yell>,
+) -> Result<Integer, Overflow> {
     let yell = monkey_jobs.get(monkey_name).unwrap();
 
     match yell {
-        Number(number) => *number,
+        Number(number) => Ok(integer(*number)),
         Operation(lhs, operator, rhs) => {
-            let lhs = solve_first_riddle(lhs, monkey_jobs);
-            let rhs = solve_first_riddle(rhs, monkey_jobs);
+            let lhs = solve_first_riddle(lhs, monkey_jobs)?;
+            let rhs = solve_first_riddle(rhs, monkey_jobs)?;
             match operator {
-                Add => lhs + rhs,
-                Sub => lhs - rhs,
-                Mul => lhs * rhs,
-                Div => lhs / rhs,
+                Add => lhs.try_add(&rhs),
+                Sub => lhs.try_sub(&rhs),
+                Mul => lhs.try_mul(&rhs),
+                Div => lhs.try_div(&rhs),
             }
         }
     }
 }
 
+/// Returns the number the root monkey yells.
 #[aoc(day21, part1)]
-fn part1(monkey_jobs: &HashMap<String, Yell>) -> i64 {
+pub fn part1(monkey_jobs: &HashMap<String, Yell>) -> Result<Integer, Overflow> {
     solve_first_riddle(&ROOT_MONKEY_NAME.to_string(), monkey_jobs)
 }
 
-fn solve_second_riddle(monkey_jobs: &HashMap<String, Yell>) -> i64 {
+fn solve_second_riddle(monkey_jobs: &HashMap<String, Yell>) -> Result<Integer, Overflow> {
     let yell = monkey_jobs.get(ROOT_MONKEY_NAME).unwrap();
 
     let (lhs, rhs) = match yell {
         Operation(lhs, _, rhs) => (
-            solve_riddle_with_polynomials(lhs, monkey_jobs),
-            solve_riddle_with_polynomials(rhs, monkey_jobs),
+            solve_riddle_with_polynomials(lhs, monkey_jobs)?,
+            solve_riddle_with_polynomials(rhs, monkey_jobs)?,
         ),
         Number(_) => unreachable!(),
     };
@@ -89,55 +136,89 @@ fn solve_second_riddle(monkey_jobs: &HashMap<String, Yell>) -> i64 {
         unreachable!()
     }
 
-    ((rhs.1 - lhs.1) / (lhs.0 - rhs.0)).to_integer()
+    Ok(rhs
+        .1
+        .try_sub(&lhs.1)?
+        .try_div(&lhs.0.try_sub(&rhs.0)?)?
+        .to_integer())
 }
 
 fn solve_riddle_with_polynomials(
     monkey_name: &String,
     monkey_jobs: &HashMap<String, Yell>,
-) -> LinearPolynomial {
+) -> Result<LinearPolynomial, Overflow> {
     let yell = monkey_jobs.get(monkey_name).unwrap();
-    let zero = Rational64::zero();
-    let one = Rational64::one();
+    let zero = Rational::zero();
+    let one = Rational::one();
 
-    match yell {
+    Ok(match yell {
         Number(number) => {
             if monkey_name == MY_NAME {
                 LinearPolynomial(one, zero)
             } else {
-                LinearPolynomial(zero, Rational64::from_integer(*number))
+                LinearPolynomial(zero, Rational::from_integer(integer(*number)))
             }
         }
         Operation(lhs, operator, rhs) => {
-            let lhs = solve_riddle_with_polynomials(lhs, monkey_jobs);
-            let rhs = solve_riddle_with_polynomials(rhs, monkey_jobs);
+            let lhs = solve_riddle_with_polynomials(lhs, monkey_jobs)?;
+            let rhs = solve_riddle_with_polynomials(rhs, monkey_jobs)?;
             match operator {
-                Add => LinearPolynomial(lhs.0 + rhs.0, lhs.1 + rhs.1),
-                Sub => LinearPolynomial(lhs.0 - rhs.0, lhs.1 - rhs.1),
+                Add => LinearPolynomial(lhs.0.try_add(&rhs.0)?, lhs.1.try_add(&rhs.1)?),
+                Sub => LinearPolynomial(lhs.0.try_sub(&rhs.0)?, lhs.1.try_sub(&rhs.1)?),
                 Mul => {
-                    if rhs.0 * lhs.0 != zero {
+                    if rhs.0.try_mul(&lhs.0)? != zero {
                         unreachable!()
                     }
 
-                    LinearPolynomial(rhs.0 * lhs.1 + lhs.0 * rhs.1, rhs.1 * lhs.1)
+                    LinearPolynomial(
+                        rhs.0.try_mul(&lhs.1)?.try_add(&lhs.0.try_mul(&rhs.1)?)?,
+                        rhs.1.try_mul(&lhs.1)?,
+                    )
                 }
                 Div => {
                     if rhs.0 != zero || rhs.1 == zero {
                         unreachable!()
                     }
 
-                    LinearPolynomial(lhs.0 / rhs.1, lhs.1 / rhs.1)
+                    LinearPolynomial(lhs.0.try_div(&rhs.1)?, lhs.1.try_div(&rhs.1)?)
                 }
             }
         }
-    }
+    })
 }
 
+/// Returns the number you need to yell for the root monkey to see two equal numbers.
 #[aoc(day21, part2)]
-fn part2(monkey_jobs: &HashMap<String, Yell>) -> i64 {
+pub fn part2(monkey_jobs: &HashMap<String, Yell>) -> Result<Integer, Overflow> {
     solve_second_riddle(monkey_jobs)
 }
 
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = HashMap<String, Yell>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Monkey Math"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,11 +241,35 @@ hmdt: 32";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 152);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), Ok(integer(152)));
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            Ok(integer(152))
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 301);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), Ok(integer(301)));
+    }
+
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    #[test]
+    fn large_numbers() {
+        let input = TEST_INPUT.replace("hmdt: 32", "hmdt: 9000000000000000000");
+        let answer = part1(&parse(&input).unwrap());
+
+        if cfg!(feature = "bigint") {
+            assert_eq!(
+                answer.map(|answer| answer.to_string()),
+                Ok("44999999999999999992".to_string())
+            );
+        } else {
+            assert_eq!(answer, Err(Overflow));
+        }
     }
 }
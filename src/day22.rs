@@ -1,85 +1,59 @@
-use crate::day22::Direction::*;
+//! Day 22: Monkey Map.
+
 use crate::day22::PathSegment::{MoveSteps, Turn};
 use crate::day22::Tile::*;
-use crate::day22::TurnDirection::*;
+use crate::error::ParseError;
+use crate::geometry::Direction::{self, *};
+use crate::geometry::Rotation::{self, *};
+use crate::geometry::{BoundingBox, Point2};
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution};
+use crate::visualize::{Cell, Frame, Frames, Visualize, EMPTY, GOLD, GRAY, GREEN, WHITE};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashMap;
+use std::iter;
 
-type Coordinates = (i32, i32);
-type Path = Vec<PathSegment>;
-
-const SIDE_SIZE: i32 = 50;
+const DAY: u8 = 22;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
-enum Direction {
-    Right = 0,
-    Down = 1,
-    Left = 2,
-    Up = 3,
-}
+pub type Path = Vec<PathSegment>;
 
-impl From<Direction> for Coordinates {
-    fn from(direction: Direction) -> Self {
-        match direction {
-            Right => (1, 0),
-            Down => (0, 1),
-            Left => (-1, 0),
-            Up => (0, -1),
-        }
-    }
-}
-
-impl Direction {
-    fn turn(self, turn_direction: &TurnDirection) -> Self {
-        match (self, turn_direction) {
-            (Right, Clockwise) => Down,
-            (Down, Clockwise) => Left,
-            (Left, Clockwise) => Up,
-            (Up, Clockwise) => Right,
-            (Right, Counterclockwise) => Up,
-            (Down, Counterclockwise) => Right,
-            (Left, Counterclockwise) => Down,
-            (Up, Counterclockwise) => Left,
-        }
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum TurnDirection {
-    Clockwise,
-    Counterclockwise,
-}
+const SIDE_SIZE: i32 = 50;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
-enum PathSegment {
+pub enum PathSegment {
     MoveSteps(i32),
-    Turn(TurnDirection),
+    Turn(Rotation),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
-enum Tile {
+pub enum Tile {
     OpenTile,
     SolidWall,
 }
 
+/// Parses the board, keyed by one-based column and row, and the path to follow.
+///
+/// Tiles outside the board are absent from the map.
 #[aoc_generator(day22)]
-fn parse_input(input: &str) -> (HashMap<Coordinates, Tile>, Path) {
+pub fn parse(input: &str) -> Result<(Board, Path), ParseError> {
     use aoc_parse::{parser, prelude::*};
 
+    let input = &preprocess::normalize(input);
+
     let (map, path) = parser!(
         map:section(lines(string(tile:any_char+)))
         path:section(line(path_segment:{
             move_steps:i32 => MoveSteps(move_steps),
-            turn_direction:{
+            rotation:{
                 "R" => Clockwise,
                 "L" => Counterclockwise
-            } => Turn(turn_direction)
+            } => Turn(rotation)
         }+)
     ))
     .parse(input)
-    .unwrap();
+    .map_err(|error| ParseError::from_aoc_parse(DAY, error))?;
 
-    let map: HashMap<Coordinates, Tile> = map
+    let map: Board = map
         .iter()
         .enumerate()
         .flat_map(|(row, line)| {
@@ -87,215 +61,278 @@ fn parse_input(input: &str) -> (HashMap<Coordinates, Tile>, Path) {
                 .enumerate()
                 .filter(|(_, tile)| *tile != ' ')
                 .map(move |(column, tile)| match tile {
-                    '.' => ((column as i32 + 1, row as i32 + 1), OpenTile),
-                    '#' => ((column as i32 + 1, row as i32 + 1), SolidWall),
-                    _ => unreachable!(),
+                    '.' => Ok((Point2::new(column as i32 + 1, row as i32 + 1), OpenTile)),
+                    '#' => Ok((Point2::new(column as i32 + 1, row as i32 + 1), SolidWall)),
+                    _ => Err(ParseError::at_char(
+                        DAY,
+                        row,
+                        column,
+                        tile,
+                        "one of '.', '#', ' '",
+                    )),
                 })
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    (map, path)
+    if !map.keys().any(|position| position.y == 1) {
+        return Err(ParseError::at_end_of_line(DAY, 0, "", "an open tile"));
+    }
+
+    Ok((map, path))
 }
 
-fn password(position: &Coordinates, direction: &Direction) -> usize {
-    1_000 * position.1 as usize + 4 * position.0 as usize + *direction as usize
+/// Facing is 0 for right and counts clockwise from there.
+fn facing(direction: Direction) -> usize {
+    match direction {
+        Right => 0,
+        Down => 1,
+        Left => 2,
+        Up => 3,
+    }
 }
 
-#[aoc(day22, part1)]
-fn part1((map, path): &(HashMap<Coordinates, Tile>, Path)) -> usize {
-    let mut current_position = map
-        .keys()
-        .filter(|(_, row)| *row == 1)
-        .min_by(|coordinates1, coordinates2| coordinates1.0.cmp(&coordinates2.0))
+fn password(position: Point2<i32>, direction: Direction) -> usize {
+    1_000 * position.y as usize + 4 * position.x as usize + facing(direction)
+}
+
+type Board = HashMap<Point2<i32>, Tile>;
+
+/// A position on the board and the direction faced there.
+type State = (Point2<i32>, Direction);
+
+fn starting_position(map: &Board) -> Point2<i32> {
+    map.keys()
+        .filter(|position| position.y == 1)
+        .min_by_key(|position| position.x)
         .unwrap()
-        .to_owned();
-    let mut direction = Right;
-
-    for path_segment in path.iter() {
-        match path_segment {
-            MoveSteps(steps) => {
-                let (x_step, y_step): Coordinates = direction.into();
-
-                for _ in 0..*steps {
-                    let mut new_position =
-                        (current_position.0 + x_step, current_position.1 + y_step);
-
-                    if map.get(&new_position).is_none() {
-                        match direction {
-                            Right => {
-                                new_position = map
-                                    .keys()
-                                    .filter(|(_, row)| *row == new_position.1)
-                                    .min_by(|coordinates1, coordinates2| {
-                                        coordinates1.0.cmp(&coordinates2.0)
-                                    })
-                                    .unwrap()
-                                    .to_owned()
-                            }
-                            Down => {
-                                new_position = map
-                                    .keys()
-                                    .filter(|(column, _)| *column == new_position.0)
-                                    .min_by(|coordinates1, coordinates2| {
-                                        coordinates1.1.cmp(&coordinates2.1)
-                                    })
-                                    .unwrap()
-                                    .to_owned()
-                            }
-                            Left => {
-                                new_position = map
-                                    .keys()
-                                    .filter(|(_, row)| *row == new_position.1)
-                                    .max_by(|coordinates1, coordinates2| {
-                                        coordinates1.0.cmp(&coordinates2.0)
-                                    })
-                                    .unwrap()
-                                    .to_owned()
-                            }
-                            Up => {
-                                new_position = map
-                                    .keys()
-                                    .filter(|(column, _)| *column == new_position.0)
-                                    .max_by(|coordinates1, coordinates2| {
-                                        coordinates1.1.cmp(&coordinates2.1)
-                                    })
-                                    .unwrap()
-                                    .to_owned()
-                            }
-                        };
-                    }
+        .to_owned()
+}
 
-                    if let Some(tile) = map.get(&new_position) {
-                        match tile {
-                            OpenTile => {
-                                current_position = new_position;
-                                continue;
-                            }
-                            SolidWall => {
-                                break;
-                            }
-                        }
+/// Follows one segment of the path and returns the new position and direction.
+///
+/// `wrap` tells where stepping off the board leads, and `visit` is called for
+/// every tile entered and every turn.
+fn follow(
+    map: &Board,
+    (mut position, mut direction): State,
+    path_segment: &PathSegment,
+    wrap: &impl Fn(State) -> State,
+    mut visit: impl FnMut(Point2<i32>, Direction),
+) -> State {
+    match path_segment {
+        MoveSteps(steps) => {
+            for _ in 0..*steps {
+                let mut new_position = position + direction.offset();
+                let mut new_direction = direction;
+
+                if !map.contains_key(&new_position) {
+                    (new_position, new_direction) = wrap((position, direction));
+                }
+
+                match map.get(&new_position) {
+                    Some(OpenTile) => {
+                        position = new_position;
+                        direction = new_direction;
+                        visit(position, direction);
                     }
+                    Some(SolidWall) => break,
+                    None => (),
                 }
             }
-            Turn(turn_direction) => {
-                direction = direction.turn(turn_direction);
-            }
+        }
+        Turn(rotation) => {
+            direction = direction.rotate(*rotation);
+            visit(position, direction);
         }
     }
 
-    password(&current_position, &direction)
+    (position, direction)
 }
 
-#[aoc(day22, part2)]
-fn part2_special_case((map, path): &(HashMap<Coordinates, Tile>, Path)) -> usize {
-    let mut connections: HashMap<(Coordinates, Direction), (Coordinates, Direction)> =
-        HashMap::with_capacity(14 * SIDE_SIZE as usize);
+fn final_password(map: &Board, path: &Path, wrap: impl Fn(State) -> State) -> usize {
+    let (position, direction) = path
+        .iter()
+        .fold((starting_position(map), Right), |state, path_segment| {
+            follow(map, state, path_segment, &wrap, |_, _| ())
+        });
 
-    for (tile1, tile2) in (1..=SIDE_SIZE)
-        .map(|y| (SIDE_SIZE + 1, y))
-        .zip((2 * SIDE_SIZE + 1..=3 * SIDE_SIZE).rev().map(|y| (1, y)))
-    {
+    password(position, direction)
+}
+
+/// Wraps around to the other end of the row or column.
+fn flat_wrap(map: &Board) -> impl Fn(State) -> State + '_ {
+    move |(position, direction)| {
+        let row = || map.keys().filter(move |tile| tile.y == position.y);
+        let column = || map.keys().filter(move |tile| tile.x == position.x);
+
+        let wrapped_position = match direction {
+            Right => row().min_by_key(|tile| tile.x),
+            Down => column().min_by_key(|tile| tile.y),
+            Left => row().max_by_key(|tile| tile.x),
+            Up => column().max_by_key(|tile| tile.y),
+        };
+
+        (*wrapped_position.unwrap(), direction)
+    }
+}
+
+/// Wraps around the edges of the cube folded from the net of the real inputs.
+fn cube_wrap() -> impl Fn(State) -> State {
+    let mut connections: HashMap<State, State> = HashMap::with_capacity(14 * SIDE_SIZE as usize);
+
+    for (tile1, tile2) in (1..=SIDE_SIZE).map(|y| Point2::new(SIDE_SIZE + 1, y)).zip(
+        (2 * SIDE_SIZE + 1..=3 * SIDE_SIZE)
+            .rev()
+            .map(|y| Point2::new(1, y)),
+    ) {
         connections.insert((tile1, Left), (tile2, Right));
         connections.insert((tile2, Left), (tile1, Right));
     }
 
     for (tile1, tile2) in (SIDE_SIZE + 1..=2 * SIDE_SIZE)
-        .map(|x| (x, 1))
-        .zip((3 * SIDE_SIZE + 1..=4 * SIDE_SIZE).map(|y| (1, y)))
+        .map(|x| Point2::new(x, 1))
+        .zip((3 * SIDE_SIZE + 1..=4 * SIDE_SIZE).map(|y| Point2::new(1, y)))
     {
         connections.insert((tile1, Up), (tile2, Right));
         connections.insert((tile2, Left), (tile1, Down));
     }
 
     for (tile1, tile2) in (2 * SIDE_SIZE + 1..=3 * SIDE_SIZE)
-        .map(|x| (x, 1))
-        .zip((1..=SIDE_SIZE).map(|x| (x, 4 * SIDE_SIZE)))
+        .map(|x| Point2::new(x, 1))
+        .zip((1..=SIDE_SIZE).map(|x| Point2::new(x, 4 * SIDE_SIZE)))
     {
         connections.insert((tile1, Up), (tile2, Up));
         connections.insert((tile2, Down), (tile1, Down));
     }
 
-    for (tile1, tile2) in (1..=SIDE_SIZE).map(|y| (3 * SIDE_SIZE, y)).zip(
+    for (tile1, tile2) in (1..=SIDE_SIZE).map(|y| Point2::new(3 * SIDE_SIZE, y)).zip(
         (2 * SIDE_SIZE + 1..=3 * SIDE_SIZE)
             .rev()
-            .map(|y| (2 * SIDE_SIZE, y)),
+            .map(|y| Point2::new(2 * SIDE_SIZE, y)),
     ) {
         connections.insert((tile1, Right), (tile2, Left));
         connections.insert((tile2, Right), (tile1, Left));
     }
 
     for (tile1, tile2) in (2 * SIDE_SIZE + 1..=3 * SIDE_SIZE)
-        .map(|x| (x, SIDE_SIZE))
-        .zip((SIDE_SIZE + 1..2 * SIDE_SIZE).map(|y| (2 * SIDE_SIZE, y)))
+        .map(|x| Point2::new(x, SIDE_SIZE))
+        .zip((SIDE_SIZE + 1..=2 * SIDE_SIZE).map(|y| Point2::new(2 * SIDE_SIZE, y)))
     {
         connections.insert((tile1, Down), (tile2, Left));
         connections.insert((tile2, Right), (tile1, Up));
     }
 
     for (tile1, tile2) in (SIDE_SIZE + 1..=2 * SIDE_SIZE)
-        .map(|x| (x, 3 * SIDE_SIZE))
-        .zip((3 * SIDE_SIZE + 1..=4 * SIDE_SIZE).map(|y| (SIDE_SIZE, y)))
+        .map(|x| Point2::new(x, 3 * SIDE_SIZE))
+        .zip((3 * SIDE_SIZE + 1..=4 * SIDE_SIZE).map(|y| Point2::new(SIDE_SIZE, y)))
     {
         connections.insert((tile1, Down), (tile2, Left));
         connections.insert((tile2, Right), (tile1, Up));
     }
 
     for (tile1, tile2) in (1..=SIDE_SIZE)
-        .map(|x| (x, 2 * SIDE_SIZE + 1))
-        .zip((SIDE_SIZE + 1..=2 * SIDE_SIZE).map(|y| (SIDE_SIZE + 1, y)))
+        .map(|x| Point2::new(x, 2 * SIDE_SIZE + 1))
+        .zip((SIDE_SIZE + 1..=2 * SIDE_SIZE).map(|y| Point2::new(SIDE_SIZE + 1, y)))
     {
         connections.insert((tile1, Up), (tile2, Right));
         connections.insert((tile2, Left), (tile1, Down));
     }
 
-    let mut current_position = map
-        .keys()
-        .filter(|(_, row)| *row == 1)
-        .min_by(|coordinates1, coordinates2| coordinates1.0.cmp(&coordinates2.0))
-        .unwrap()
-        .to_owned();
-    let mut direction = Right;
-
-    for path_segment in path.iter() {
-        match path_segment {
-            MoveSteps(steps) => {
-                for _ in 0..*steps {
-                    let (x_step, y_step): Coordinates = direction.into();
+    move |state| connections[&state]
+}
 
-                    let mut new_position =
-                        (current_position.0 + x_step, current_position.1 + y_step);
-                    let mut new_direction = direction;
+/// Returns the final password when the board wraps around flat.
+#[aoc(day22, part1)]
+pub fn part1((map, path): &(Board, Path)) -> usize {
+    final_password(map, path, flat_wrap(map))
+}
 
-                    if map.get(&new_position).is_none() {
-                        let (connected_position, connected_direction) =
-                            connections.get(&(current_position, direction)).unwrap();
+/// Returns the final password when the board folds into a cube.
+///
+/// Only the cube net of the real puzzle inputs, with sides of 50 tiles, is supported.
+#[aoc(day22, part2)]
+pub fn part2((map, path): &(Board, Path)) -> usize {
+    final_password(map, path, cube_wrap())
+}
 
-                        new_position = *connected_position;
-                        new_direction = *connected_direction;
-                    }
+fn arrow(direction: Direction) -> char {
+    match direction {
+        Right => '>',
+        Down => 'v',
+        Left => '<',
+        Up => '^',
+    }
+}
 
-                    if let Some(tile) = map.get(&new_position) {
-                        match tile {
-                            OpenTile => {
-                                current_position = new_position;
-                                direction = new_direction;
-                                continue;
-                            }
-                            SolidWall => {
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-            Turn(turn_direction) => {
-                direction = direction.turn(turn_direction);
-            }
+impl Visualize for (Board, Path) {
+    /// Draws the board with the trail walked so far after every segment of the path.
+    fn frames(&self, part: Part) -> Frames<'_> {
+        let (map, path) = self;
+
+        let wrap: Box<dyn Fn(State) -> State> = match part {
+            Part::One => Box::new(flat_wrap(map)),
+            Part::Two => Box::new(cube_wrap()),
+        };
+
+        let bounds = BoundingBox::from_points(map.keys().copied()).expect("the board is not empty");
+        let mut frame = Frame::new(bounds, EMPTY);
+
+        for (&position, tile) in map {
+            frame.draw(
+                position,
+                match tile {
+                    OpenTile => Cell::new('.', GRAY),
+                    SolidWall => Cell::new('#', WHITE),
+                },
+            );
         }
+
+        let mut state = (starting_position(map), Right);
+        frame.draw(state.0, Cell::new(arrow(state.1), GOLD));
+
+        Box::new(
+            iter::once(frame.clone()).chain(path.iter().map(move |path_segment| {
+                frame.draw(state.0, Cell::new(arrow(state.1), GREEN));
+
+                state = follow(map, state, path_segment, &wrap, |position, direction| {
+                    frame.draw(position, Cell::new(arrow(direction), GREEN))
+                });
+
+                frame.draw(state.0, Cell::new(arrow(state.1), GOLD));
+                frame.clone()
+            })),
+        )
+    }
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = (Board, Path);
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Monkey Map"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
     }
 
-    password(&current_position, &direction)
+    fn frames<'a>(&self, input: &'a Self::Input, part: Part) -> Option<Frames<'a>> {
+        Some(input.frames(part))
+    }
 }
 
 #[cfg(test)]
@@ -319,6 +356,39 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 6_032);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 6_032);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            6_032
+        );
+    }
+
+    #[test]
+    fn frames_example() {
+        let frames: Vec<_> = parse(TEST_INPUT).unwrap().frames(Part::One).collect();
+
+        assert_eq!(frames.len(), 14);
+        assert_eq!(
+            frames[13].to_string(),
+            [
+                "        >>v#    ",
+                "        .#v.    ",
+                "        #.v.    ",
+                "        ..v.    ",
+                "...#...v..v#    ",
+                ">>>v...>#.>>    ",
+                "..#v...#....    ",
+                "...>>>>v..#.    ",
+                "        ...#....",
+                "        .....#..",
+                "        .#......",
+                "        ......#.",
+            ]
+            .join("\n")
+        );
     }
 }
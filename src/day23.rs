@@ -1,113 +1,78 @@
-use crate::day23::CardinalDirection::*;
+//! Day 23: Unstable Diffusion.
+
+use crate::error::ParseError;
+use crate::geometry::Direction::{self, *};
+use crate::geometry::Rotation::Clockwise;
+use crate::geometry::{BoundingBox, Point2};
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution};
+use crate::visualize::{Cell, Frame, Frames, Visualize, BACKGROUND, GREEN};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::hash_map::Entry::Vacant;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::iter;
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
-struct Coordinates(i32, i32);
-
-impl Coordinates {
-    fn all_adjacent_positions(&self) -> [Coordinates; 8] {
-        let mut positions = [Coordinates(0, 0); 8];
-
-        for (index, (x, y)) in [
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-            (0, 1),
-            (-1, 1),
-            (-1, 0),
-        ]
-        .into_iter()
-        .enumerate()
-        {
-            positions[index] = Coordinates(self.0 + x, self.1 + y);
-        }
+const DAY: u8 = 23;
 
-        positions
-    }
-
-    fn adjacent_positions_in_direction(&self, direction: &CardinalDirection) -> [Coordinates; 3] {
-        match direction {
-            N => [
-                Coordinates(self.0 - 1, self.1 - 1),
-                Coordinates(self.0, self.1 - 1),
-                Coordinates(self.0 + 1, self.1 - 1),
-            ],
-            S => [
-                Coordinates(self.0 - 1, self.1 + 1),
-                Coordinates(self.0, self.1 + 1),
-                Coordinates(self.0 + 1, self.1 + 1),
-            ],
-            W => [
-                Coordinates(self.0 - 1, self.1 - 1),
-                Coordinates(self.0 - 1, self.1),
-                Coordinates(self.0 - 1, self.1 + 1),
-            ],
-            E => [
-                Coordinates(self.0 + 1, self.1 - 1),
-                Coordinates(self.0 + 1, self.1),
-                Coordinates(self.0 + 1, self.1 + 1),
-            ],
-        }
-    }
+/// The three positions an elf looks at before moving one step in `direction`.
+fn adjacent_positions_in_direction(
+    position: Point2<i32>,
+    direction: Direction,
+) -> [Point2<i32>; 3] {
+    let step = position + direction.offset();
+    let side = direction.rotate(Clockwise).offset();
 
-    fn move_one_step(&self, direction: &CardinalDirection) -> Coordinates {
-        match direction {
-            N => Coordinates(self.0, self.1 - 1),
-            S => Coordinates(self.0, self.1 + 1),
-            W => Coordinates(self.0 - 1, self.1),
-            E => Coordinates(self.0 + 1, self.1),
-        }
-    }
+    [step - side, step, step + side]
 }
 
-type Map = HashSet<Coordinates>;
+/// The positions of all elves.
+pub type Map = HashSet<Point2<i32>>;
 
+/// Parses the scan of the grove.
 #[aoc_generator(day23)]
-fn parse_input(input: &str) -> Map {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, row)| {
-            row.chars()
-                .enumerate()
-                .filter(|(_, tile)| *tile == '#')
-                .map(move |(x, _)| Coordinates(x as i32, y as i32))
-        })
-        .collect()
-}
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let mut map = Map::new();
+
+    for (y, row) in input.lines().enumerate() {
+        for (x, tile) in row.chars().enumerate() {
+            match tile {
+                '#' => {
+                    map.insert(Point2::new(x as i32, y as i32));
+                }
+                '.' => (),
+                _ => return Err(ParseError::at_char(DAY, y, x, tile, "one of '.', '#'")),
+            }
+        }
+    }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy)]
-enum CardinalDirection {
-    N,
-    S,
-    W,
-    E,
+    if map.is_empty() {
+        return Err(ParseError::at_end_of_line(DAY, 0, "", "an elf '#'"));
+    }
+
+    Ok(map)
 }
 
-fn simulate_one_round(map: &mut Map, directions: &mut VecDeque<CardinalDirection>) -> usize {
-    let mut proposed_moves: HashMap<Coordinates, Coordinates> = HashMap::new();
+fn simulate_one_round(map: &mut Map, directions: &mut VecDeque<Direction>) -> usize {
+    let mut proposed_moves: HashMap<Point2<i32>, Point2<i32>> = HashMap::new();
 
     'next_elf: for elf_position in map.iter() {
         if !elf_position
-            .all_adjacent_positions()
-            .iter()
-            .any(|adjacent_position| map.contains(adjacent_position))
+            .neighbors8()
+            .any(|adjacent_position| map.contains(&adjacent_position))
         {
             continue 'next_elf;
         }
 
         'next_direction: for direction in directions.iter() {
-            for adjacent_position in elf_position.adjacent_positions_in_direction(direction) {
+            for adjacent_position in adjacent_positions_in_direction(*elf_position, *direction) {
                 if map.contains(&adjacent_position) {
                     continue 'next_direction;
                 }
             }
 
-            let new_position = elf_position.move_one_step(direction);
+            let new_position = *elf_position + direction.offset();
 
             if let Vacant(entry) = proposed_moves.entry(new_position) {
                 entry.insert(*elf_position);
@@ -131,7 +96,7 @@ fn simulate_one_round(map: &mut Map, directions: &mut VecDeque<CardinalDirection
 }
 
 fn simulate_rounds(map: &mut Map, rounds: usize) {
-    let mut directions: VecDeque<CardinalDirection> = VecDeque::from([N, S, W, E]);
+    let mut directions: VecDeque<Direction> = VecDeque::from([Up, Down, Left, Right]);
 
     for _round in 1..=rounds {
         if simulate_one_round(map, &mut directions) == 0 {
@@ -141,7 +106,7 @@ fn simulate_rounds(map: &mut Map, rounds: usize) {
 }
 
 fn simulate(map: &mut Map) -> usize {
-    let mut directions: VecDeque<CardinalDirection> = VecDeque::from([N, S, W, E]);
+    let mut directions: VecDeque<Direction> = VecDeque::from([Up, Down, Left, Right]);
 
     let mut rounds = 1;
 
@@ -154,25 +119,105 @@ fn simulate(map: &mut Map) -> usize {
     }
 }
 
+/// Returns the number of empty ground tiles in the smallest rectangle containing every elf after 10 rounds.
 #[aoc(day23, part1)]
-fn part1(map: &Map) -> usize {
+pub fn part1(map: &Map) -> usize {
     let mut map = map.clone();
     simulate_rounds(&mut map, 10);
 
-    let min_x = map.iter().map(|coordinates| coordinates.0).min().unwrap();
-    let max_x = map.iter().map(|coordinates| coordinates.0).max().unwrap();
-    let min_y = map.iter().map(|coordinates| coordinates.1).min().unwrap();
-    let max_y = map.iter().map(|coordinates| coordinates.1).max().unwrap();
+    let bounding_box = BoundingBox::from_points(map.iter().copied()).unwrap();
 
-    ((max_x - min_x + 1) * (max_y - min_y + 1)) as usize - map.len()
+    bounding_box.area() as usize - map.len()
 }
 
+/// Returns the number of the first round where no elf moves.
 #[aoc(day23, part2)]
-fn part2(map: &Map) -> usize {
+pub fn part2(map: &Map) -> usize {
     let mut map = map.clone();
     simulate(&mut map)
 }
 
+/// The positions of the elves after every round, up to `round_limit` rounds
+/// or until no elf moves.
+fn spread(map: &Map, round_limit: Option<usize>) -> impl Iterator<Item = Map> {
+    let mut map = map.clone();
+    let mut directions: VecDeque<Direction> = VecDeque::from([Up, Down, Left, Right]);
+    let mut round = 0;
+    let mut settled = false;
+
+    iter::from_fn(move || {
+        if settled || round_limit.is_some_and(|round_limit| round == round_limit) {
+            return None;
+        }
+
+        round += 1;
+        settled = simulate_one_round(&mut map, &mut directions) == 0;
+
+        Some(map.clone())
+    })
+}
+
+impl Visualize for Map {
+    /// Draws the elves before the first round and after every round: ten
+    /// rounds for part 1, and until no elf moves for part 2.
+    fn frames(&self, part: Part) -> Frames<'_> {
+        let round_limit = match part {
+            Part::One => Some(10),
+            Part::Two => None,
+        };
+
+        // The elves spread out, so the simulation runs once more to find the bounds.
+        let bounds = BoundingBox::from_points(
+            spread(self, round_limit)
+                .flat_map(|map| map.into_iter())
+                .chain(self.iter().copied()),
+        )
+        .expect("there is at least one elf");
+
+        let draw = move |map: &Map| {
+            let mut frame = Frame::new(bounds, Cell::new('.', BACKGROUND));
+
+            for &elf in map {
+                frame.draw(elf, Cell::new('#', GREEN));
+            }
+
+            frame
+        };
+
+        Box::new(iter::once(draw(self)).chain(spread(self, round_limit).map(move |map| draw(&map))))
+    }
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Map;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
+
+    fn name(&self) -> &'static str {
+        "Unstable Diffusion"
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn frames<'a>(&self, input: &'a Self::Input, part: Part) -> Option<Frames<'a>> {
+        Some(input.frames(part))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,11 +232,44 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 110);
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), 110);
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            110
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 20);
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), 20);
+    }
+
+    #[test]
+    fn frames_example() {
+        let map = parse(TEST_INPUT).unwrap();
+        let frames: Vec<_> = map.frames(Part::One).collect();
+        let elves = |frame: &Frame| frame.to_string().matches('#').count();
+
+        assert_eq!(frames.len(), 11);
+        assert!(frames.iter().all(|frame| elves(frame) == 22));
+        assert_eq!(
+            frames[0].to_string(),
+            r"............
+............
+......#.....
+....###.#...
+..#...#.#...
+...#...##...
+..#.###.....
+..##.#.##...
+...#..#.....
+............
+............"
+        );
+        assert_eq!(map.frames(Part::Two).count(), 21);
     }
 }
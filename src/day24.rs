@@ -1,72 +1,175 @@
-use crate::day24::Direction::*;
+//! Day 24: Blizzard Basin.
+
+use crate::error::ParseError;
+use crate::geometry::Direction::{self, *};
+use crate::geometry::{BoundingBox, Point2};
+use crate::preprocess;
+use crate::search;
+use crate::solution::{Answer, Part, Solution};
+use crate::visualize::{Cell, Frame, Frames, Visualize, BACKGROUND, BLUE, GOLD, GRAY, WHITE};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
+use std::iter;
 
-type Coordinates = (i32, i32);
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum Direction {
-    Up = 0,
-    Down = 1,
-    Left = 2,
-    Right = 3,
-}
+const DAY: u8 = 24;
 
+/// The valley with the starting positions of the blizzards, indexed by the
+/// direction they blow in.
 #[derive(Clone, Eq, PartialEq, Hash)]
-struct Map {
+pub struct Map {
     blizzards: [Vec<Vec<bool>>; 4],
     rows: usize,
     columns: usize,
 }
 
 impl Map {
-    fn is_blizzard(&self, (x, y): Coordinates, time_passed: usize) -> bool {
-        let rows = self.rows as i32;
-        let columns = self.columns as i32;
+    /// The directions of the blizzards at `position` after `time_passed` minutes.
+    fn blizzards_at(
+        &self,
+        position: Point2<i32>,
+        time_passed: usize,
+    ) -> impl Iterator<Item = Direction> + '_ {
         let minutes_passed = time_passed as i32;
 
-        for direction in 0..4 {
-            let (xt, yt) = match direction {
-                direction if direction == Up as usize => (x, (y + minutes_passed) % rows),
-                direction if direction == Down as usize => {
-                    (x, ((y - minutes_passed) % rows + rows) % rows)
-                }
-                direction if direction == Left as usize => ((x + minutes_passed) % columns, y),
-                direction if direction == Right as usize => {
-                    (((x - minutes_passed) % columns + columns) % columns, y)
-                }
-                _ => unreachable!(),
-            };
+        Direction::ALL.into_iter().filter(move |&direction| {
+            let origin = position - direction.offset() * minutes_passed;
+            let x = origin.x.rem_euclid(self.columns as i32) as usize;
+            let y = origin.y.rem_euclid(self.rows as i32) as usize;
+
+            self.blizzards[direction as usize][y][x]
+        })
+    }
+
+    fn is_blizzard(&self, position: Point2<i32>, time_passed: usize) -> bool {
+        self.blizzards_at(position, time_passed).next().is_some()
+    }
+
+    fn contains(&self, position: Point2<i32>) -> bool {
+        (0..self.columns as i32).contains(&position.x)
+            && (0..self.rows as i32).contains(&position.y)
+    }
 
-            if self.blizzards[direction][yt as usize][xt as usize] {
-                return true;
+    fn frame(&self, expedition: &HashSet<Point2<i32>>, time_passed: usize) -> Frame {
+        let bounds = BoundingBox {
+            min: Point2::new(-1, -1),
+            max: Point2::new(self.columns as i32, self.rows as i32),
+        };
+        let mut frame = Frame::new(bounds, Cell::new('#', GRAY));
+
+        for y in 0..self.rows as i32 {
+            for x in 0..self.columns as i32 {
+                let position = Point2::new(x, y);
+                let blizzards: Vec<_> = self.blizzards_at(position, time_passed).collect();
+
+                let cell = match blizzards[..] {
+                    [] => Cell::new('.', BACKGROUND),
+                    [Up] => Cell::new('^', BLUE),
+                    [Right] => Cell::new('>', BLUE),
+                    [Down] => Cell::new('v', BLUE),
+                    [Left] => Cell::new('<', BLUE),
+                    _ => Cell::new(char::from_digit(blizzards.len() as u32, 10).unwrap(), WHITE),
+                };
+
+                frame.draw(position, cell);
             }
         }
 
-        false
+        frame.draw(Point2::new(0, -1), Cell::new('.', BACKGROUND));
+        frame.draw(
+            Point2::new(self.columns as i32 - 1, self.rows as i32),
+            Cell::new('.', BACKGROUND),
+        );
+
+        for &position in expedition {
+            frame.draw(position, Cell::new('E', GOLD));
+        }
+
+        frame
     }
 }
 
+/// Parses the map of the valley, including its walls.
 #[aoc_generator(day24)]
-fn parse_input(input: &str) -> Map {
-    let rows = input.lines().count() - 2;
-    let columns = input.lines().next().unwrap().len() - 2;
+pub fn parse(input: &str) -> Result<Map, ParseError> {
+    let input = &preprocess::normalize(input);
+
+    let lines: Vec<_> = input.lines().collect();
+
+    if lines.len() < 3 {
+        return Err(ParseError::at_end_of_line(
+            DAY,
+            lines.len().saturating_sub(1),
+            lines.last().unwrap_or(&""),
+            "a valley of at least three rows",
+        ));
+    }
+
+    let rows = lines.len() - 2;
+    let columns = lines[0].chars().count().saturating_sub(2);
 
     let mut blizzards: [Vec<Vec<bool>>; 4] = Default::default();
 
-    input.lines().skip(1).take(rows).for_each(|tiles| {
+    for (line_index, tiles) in lines.iter().enumerate() {
+        if tiles.chars().count() != columns + 2 {
+            return Err(ParseError::at_end_of_line(
+                DAY,
+                line_index,
+                tiles,
+                format!("a row of {} tiles", columns + 2),
+            ));
+        }
+
+        if line_index == 0 || line_index == rows + 1 {
+            for (char_index, tile) in tiles.chars().enumerate() {
+                let expected_tile = if line_index == 0 && char_index == 1
+                    || line_index == rows + 1 && char_index == columns
+                {
+                    '.'
+                } else {
+                    '#'
+                };
+
+                if tile != expected_tile {
+                    return Err(ParseError::at_char(
+                        DAY,
+                        line_index,
+                        char_index,
+                        tile,
+                        format!("{expected_tile:?}"),
+                    ));
+                }
+            }
+
+            continue;
+        }
+
         let mut row_blizzards: [Vec<bool>; 4] = Default::default();
 
-        tiles.chars().skip(1).take(columns).for_each(|tile| {
+        for (char_index, tile) in tiles.chars().enumerate() {
             let blizzard = match tile {
+                '#' if char_index == 0 || char_index == columns + 1 => continue,
                 '.' => None,
                 '^' => Some(Up),
                 'v' => Some(Down),
                 '<' => Some(Left),
                 '>' => Some(Right),
-                _ => unreachable!(),
+                _ => {
+                    return Err(ParseError::at_char(
+                        DAY,
+                        line_index,
+                        char_index,
+                        tile,
+                        "one of '.', '^', 'v', '<', '>'",
+                    ))
+                }
             };
 
+            if char_index == 0 || char_index == columns + 1 {
+                return Err(ParseError::at_char(
+                    DAY, line_index, char_index, tile, "'#'",
+                ));
+            }
+
             for (index, direction_blizzards) in row_blizzards.iter_mut().enumerate() {
                 match blizzard {
                     Some(direction) if direction as usize == index => {
@@ -75,142 +178,170 @@ fn parse_input(input: &str) -> Map {
                     _ => direction_blizzards.push(false),
                 };
             }
-        });
+        }
 
         for index in 0..4 {
             blizzards[index].push(row_blizzards[index].to_owned());
         }
-    });
+    }
 
-    Map {
+    Ok(Map {
         blizzards,
         rows,
         columns,
-    }
-}
-
-#[derive(Clone, Eq, PartialEq, Hash)]
-struct State {
-    current_position: Coordinates,
-    iteration_x: usize,
-    iteration_y: usize,
-}
-
-#[derive(Clone, Eq, PartialEq)]
-struct Node {
-    current_position: Coordinates,
-    minutes_passed: usize,
+    })
 }
 
 fn time_to_reach_goal(map: &Map, forgot_snacks: bool) -> Option<usize> {
-    let map = map.to_owned();
-    let starting_position = (0, -1);
-    let final_position = (map.columns as i32 - 1, map.rows as i32);
+    let starting_position = Point2::new(0, -1);
+    let final_position = Point2::new(map.columns as i32 - 1, map.rows as i32);
 
-    let mut goals = if forgot_snacks {
+    let goals = if forgot_snacks {
         vec![final_position, starting_position, final_position]
     } else {
         vec![final_position]
     };
 
-    let initial_state = State {
-        current_position: (0, 0),
-        iteration_x: 0,
-        iteration_y: 0,
-    };
+    // The blizzards repeat after this many minutes, so a node of the search is
+    // a position and the minutes passed since the pattern last started over.
+    let period = map.rows * map.columns / gcd(map.rows, map.columns).max(1);
+
+    let mut current_position = Point2::new(0, 0);
+    let mut minutes_passed = 0;
+
+    for goal in goals {
+        let trip = search::bfs(
+            [(current_position, minutes_passed % period)],
+            |&(position, phase): &(Point2<i32>, usize)| {
+                let phase = (phase + 1) % period;
+
+                iter::once(position)
+                    .chain(position.neighbors4())
+                    .filter(move |&new_position| {
+                        new_position == starting_position
+                            || new_position == final_position
+                            || map.contains(new_position) && !map.is_blizzard(new_position, phase)
+                    })
+                    .map(move |new_position| (new_position, phase))
+            },
+            |&(position, _)| position == goal,
+        );
+
+        minutes_passed += trip.goal_cost()?;
+        current_position = goal;
+    }
 
-    let mut queue = VecDeque::from([Node {
-        current_position: (0, 0),
-        minutes_passed: 0,
-    }]);
+    Some(minutes_passed)
+}
 
-    let mut visited: HashSet<State> = HashSet::from([initial_state]);
+/// Returns the fewest minutes needed to reach the goal, or `None` if it is unreachable.
+#[aoc(day24, part1)]
+pub fn part1(map: &Map) -> Option<usize> {
+    time_to_reach_goal(map, false)
+}
 
-    'next_goal: while !goals.is_empty() {
-        let goal = goals.pop().unwrap();
+/// Returns the fewest minutes needed to reach the goal, go back for the snacks and reach the goal again.
+#[aoc(day24, part2)]
+pub fn part2(map: &Map) -> Option<usize> {
+    time_to_reach_goal(map, true)
+}
 
-        while !queue.is_empty() {
-            let node = queue.pop_front().unwrap();
-            let minutes_passed = node.minutes_passed + 1;
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
 
-            for step in [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)].into_iter() {
-                let new_position = (
-                    node.current_position.0 + step.0,
-                    node.current_position.1 + step.1,
-                );
+impl Visualize for Map {
+    /// Draws the blizzards and every position the expedition could be in after
+    /// each minute, until it reaches the exit, after going back for the snacks
+    /// in part 2.
+    fn frames(&self, part: Part) -> Frames<'_> {
+        let entrance = Point2::new(0, -1);
+        let exit = Point2::new(self.columns as i32 - 1, self.rows as i32);
+
+        let mut goals = match part {
+            Part::One => vec![exit],
+            Part::Two => vec![exit, entrance, exit],
+        };
+
+        // The blizzards repeat after this many minutes. The expedition can wait
+        // where it started, so every period it can be in at least the same
+        // positions as the period before. If it cannot be in more, the goal is
+        // out of reach.
+        let period = self.rows * self.columns / gcd(self.rows, self.columns).max(1);
+
+        let mut expedition = HashSet::from([entrance]);
+        let mut minutes_passed = 0;
+        let mut trip_start = (0, expedition.clone());
+        let mut finished = false;
+
+        Box::new(
+            iter::once(self.frame(&expedition, 0)).chain(iter::from_fn(move || {
+                let goal = *goals.last()?;
+
+                if finished {
+                    return None;
+                }
 
-                if new_position == goal {
-                    if goals.is_empty() {
-                        return Some(minutes_passed);
-                    }
+                minutes_passed += 1;
+
+                expedition = expedition
+                    .iter()
+                    .flat_map(|&position| iter::once(position).chain(position.neighbors4()))
+                    .filter(|&position| {
+                        position == entrance
+                            || position == exit
+                            || self.contains(position)
+                                && !self.is_blizzard(position, minutes_passed)
+                    })
+                    .collect();
+
+                if expedition.contains(&goal) {
+                    goals.pop();
+                    expedition = HashSet::from([goal]);
+                    trip_start = (minutes_passed, expedition.clone());
+                } else if (minutes_passed - trip_start.0) % period == 0 {
+                    finished = expedition == trip_start.1;
+                    trip_start.1 = expedition.clone();
+                }
 
-                    let initial_state = State {
-                        current_position: new_position,
-                        iteration_x: minutes_passed % map.columns,
-                        iteration_y: minutes_passed % map.rows,
-                    };
+                Some(self.frame(&expedition, minutes_passed))
+            })),
+        )
+    }
+}
 
-                    queue = VecDeque::from([Node {
-                        current_position: new_position,
-                        minutes_passed,
-                    }]);
+pub struct Day24;
 
-                    visited = HashSet::from([initial_state]);
+impl Solution for Day24 {
+    type Input = Map;
 
-                    continue 'next_goal;
-                }
+    fn day(&self) -> u8 {
+        DAY
+    }
 
-                if new_position == starting_position || new_position == final_position {
-                    let state = State {
-                        current_position: new_position,
-                        iteration_x: minutes_passed % map.columns,
-                        iteration_y: minutes_passed % map.rows,
-                    };
-
-                    if !visited.contains(&state) {
-                        queue.push_back(Node {
-                            current_position: new_position,
-                            minutes_passed,
-                        });
-                    }
-                }
+    fn name(&self) -> &'static str {
+        "Blizzard Basin"
+    }
 
-                if new_position.0 >= 0
-                    && new_position.0 < map.columns as i32
-                    && new_position.1 >= 0
-                    && new_position.1 < map.rows as i32
-                    && !map.is_blizzard(new_position, minutes_passed)
-                {
-                    let state = State {
-                        current_position: new_position,
-                        iteration_x: minutes_passed % map.columns,
-                        iteration_y: minutes_passed % map.rows,
-                    };
-
-                    if !visited.contains(&state) {
-                        visited.insert(state);
-
-                        queue.push_back(Node {
-                            current_position: new_position,
-                            minutes_passed,
-                        });
-                    }
-                }
-            }
-        }
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
 
-    None
-}
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
 
-#[aoc(day24, part1)]
-fn part1(map: &Map) -> Option<usize> {
-    time_to_reach_goal(map, false)
-}
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 
-#[aoc(day24, part2)]
-fn part2(map: &Map) -> Option<usize> {
-    time_to_reach_goal(map, true)
+    fn frames<'a>(&self, input: &'a Self::Input, part: Part) -> Option<Frames<'a>> {
+        Some(input.frames(part))
+    }
 }
 
 #[cfg(test)]
@@ -226,11 +357,64 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), Some(18));
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), Some(18));
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            Some(18)
+        );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), Some(54));
+        assert_eq!(part2(&parse(TEST_INPUT).unwrap()), Some(54));
+    }
+
+    #[test]
+    fn frames_example() {
+        let map = parse(TEST_INPUT).unwrap();
+        let frames: Vec<_> = map.frames(Part::One).collect();
+
+        assert_eq!(frames.len(), 19);
+        assert_eq!(
+            frames[0].to_string(),
+            r"#E######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#"
+        );
+        assert_eq!(
+            frames[18].to_string(),
+            r"#.######
+#>2.<.<#
+#.2v^2<#
+#>..>2>#
+#<....>#
+######E#"
+        );
+        assert_eq!(map.frames(Part::Two).count(), 55);
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            parse(&TEST_INPUT.replace("#>v.><>#", "#>v.x<>#")).err(),
+            Some(ParseError::new(
+                24,
+                4,
+                5,
+                "x",
+                "one of '.', '^', 'v', '<', '>'"
+            ))
+        );
+        assert_eq!(
+            parse(&TEST_INPUT.replace("#<^v^^>#", "#<^v^^>")).err(),
+            Some(ParseError::new(24, 5, 8, "", "a row of 8 tiles"))
+        );
     }
 }
@@ -1,7 +1,16 @@
+//! Day 25: Full of Hot Air.
+
+use crate::arithmetic::{integer, Arithmetic, Integer, Overflow, Remainder};
 use crate::day25::SnafuDigit::*;
+use crate::error::ParseError;
+use crate::preprocess;
+use crate::solution::{Answer, Part, Solution, DEFAULT_VARIANT};
 use aoc_runner_derive::{aoc, aoc_generator};
+use num_traits::ToPrimitive;
 use std::fmt::{Display, Formatter};
 
+const DAY: u8 = 25;
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 enum SnafuDigit {
     Two,
@@ -66,7 +75,7 @@ impl TryFrom<i64> for SnafuDigit {
 }
 
 #[derive(Clone, Eq, PartialEq)]
-struct SnafuNumber(Vec<SnafuDigit>);
+pub struct SnafuNumber(Vec<SnafuDigit>);
 
 impl Display for SnafuNumber {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -105,54 +114,117 @@ impl TryFrom<&str> for SnafuNumber {
     }
 }
 
-impl From<&SnafuNumber> for i64 {
-    fn from(snafu_number: &SnafuNumber) -> Self {
+impl TryFrom<&SnafuNumber> for Integer {
+    type Error = Overflow;
+
+    fn try_from(snafu_number: &SnafuNumber) -> Result<Self, Self::Error> {
+        let five = integer(5);
+
         snafu_number
             .0
             .iter()
-            .enumerate()
-            .map(|(power, digit)| {
-                let digit: i64 = digit.into();
-                digit * 5i64.pow(power as u32)
+            .rev()
+            .try_fold(integer(0), |value, digit| {
+                value.try_mul(&five)?.try_add(&integer(digit.into()))
             })
-            .sum()
     }
 }
 
-impl From<i64> for SnafuNumber {
-    fn from(value: i64) -> Self {
+impl TryFrom<Integer> for SnafuNumber {
+    type Error = Overflow;
+
+    fn try_from(value: Integer) -> Result<Self, Self::Error> {
+        let (zero, two, five) = (integer(0), integer(2), integer(5));
         let mut dividend = value;
         let mut snafu_digits = Vec::new();
 
-        while dividend > 0 {
-            snafu_digits.push(((dividend + 2) % 5 - 2).try_into().unwrap());
-            dividend = (dividend + 2) / 5;
+        while dividend > zero {
+            let shifted = dividend.try_add(&two)?;
+            let digit = shifted.try_rem(&five)?.try_sub(&two)?;
+            snafu_digits.push(digit.to_i64().unwrap().try_into().unwrap());
+            dividend = shifted.try_div(&five)?;
         }
 
-        SnafuNumber(snafu_digits)
+        Ok(SnafuNumber(snafu_digits))
     }
 }
 
+/// Parses the fuel requirements, one SNAFU number per line.
 #[aoc_generator(day25)]
-fn parse_input(input: &str) -> Vec<SnafuNumber> {
+pub fn parse(input: &str) -> Result<Vec<SnafuNumber>, ParseError> {
+    let input = &preprocess::normalize(input);
+
     input
         .lines()
-        .map(|snafu_number| snafu_number.try_into().unwrap())
+        .enumerate()
+        .map(|(line_index, snafu_number)| {
+            if snafu_number.is_empty() {
+                return Err(ParseError::at_end_of_line(
+                    DAY,
+                    line_index,
+                    snafu_number,
+                    "a SNAFU number",
+                ));
+            }
+
+            snafu_number.try_into().map_err(|_| {
+                let (char_index, digit) = snafu_number
+                    .chars()
+                    .enumerate()
+                    .find(|(_, digit)| SnafuDigit::try_from(*digit).is_err())
+                    .unwrap();
+
+                ParseError::at_char(DAY, line_index, char_index, digit, "one of 2, 1, 0, -, =")
+            })
+        })
         .collect()
 }
 
+/// Returns the sum of the fuel requirements as a SNAFU number.
 #[aoc(day25, part1)]
-fn part1(snafu_numbers: &[SnafuNumber]) -> String {
+pub fn part1(snafu_numbers: &[SnafuNumber]) -> Result<String, Overflow> {
     let sum = snafu_numbers
         .iter()
-        .map(|snafu_number| {
-            let value: i64 = snafu_number.into();
-            value
-        })
-        .sum::<i64>();
+        .try_fold(integer(0), |sum, snafu_number| {
+            sum.try_add(&Integer::try_from(snafu_number)?)
+        })?;
+
+    let snafu_number = SnafuNumber::try_from(sum)?;
+    Ok(snafu_number.to_string())
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<SnafuNumber>;
+
+    fn day(&self) -> u8 {
+        DAY
+    }
 
-    let snafu_number: SnafuNumber = sum.try_into().unwrap();
-    snafu_number.to_string()
+    fn name(&self) -> &'static str {
+        "Full of Hot Air"
+    }
+
+    fn variants(&self, part: Part) -> &'static [&'static str] {
+        match part {
+            Part::One => &[DEFAULT_VARIANT],
+            Part::Two => &[],
+        }
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, _input: &Self::Input) -> Answer {
+        // Day 25 has no second puzzle.
+        Answer::NotFound
+    }
 }
 
 #[cfg(test)]
@@ -175,6 +247,27 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), "2=-1=0".to_string());
+        assert_eq!(part1(&parse(TEST_INPUT).unwrap()), Ok("2=-1=0".to_string()));
+    }
+
+    #[test]
+    fn windows_example() {
+        assert_eq!(
+            part1(&parse(&preprocess::windows(TEST_INPUT)).unwrap()),
+            Ok("2=-1=0".to_string())
+        );
+    }
+
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    #[test]
+    fn large_numbers() {
+        let input = "2".repeat(30) + "\n1";
+        let answer = part1(&parse(&input).unwrap());
+
+        if cfg!(feature = "bigint") {
+            assert_eq!(answer, Ok(format!("1{}", "=".repeat(30))));
+        } else {
+            assert_eq!(answer, Err(Overflow));
+        }
     }
 }
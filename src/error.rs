@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(
+        day: u8,
+        line: usize,
+        column: usize,
+        token: impl Into<String>,
+        expected: impl Into<String>,
+    ) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            token: token.into(),
+            expected: expected.into(),
+        }
+    }
+
+    pub fn at_token(
+        day: u8,
+        line_index: usize,
+        line: &str,
+        token: &str,
+        expected: impl Into<String>,
+    ) -> Self {
+        Self::new(day, line_index + 1, column(line, token), token, expected)
+    }
+
+    pub fn at_end_of_line(
+        day: u8,
+        line_index: usize,
+        line: &str,
+        expected: impl Into<String>,
+    ) -> Self {
+        Self::new(day, line_index + 1, line.chars().count() + 1, "", expected)
+    }
+
+    pub fn at_char(
+        day: u8,
+        line_index: usize,
+        char_index: usize,
+        char: char,
+        expected: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            day,
+            line_index + 1,
+            char_index + 1,
+            char.to_string(),
+            expected,
+        )
+    }
+
+    pub fn from_aoc_parse(day: u8, error: aoc_parse::ParseError) -> Self {
+        let source = &error.source;
+        let location = usize::min(error.location, source.len());
+
+        let line_start = source[..location].rfind('\n').map_or(0, |index| index + 1);
+        let line = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..location].chars().count() + 1;
+
+        let token = source[location..]
+            .split(|char: char| char.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default();
+
+        let message = error.to_string();
+        let expected = message
+            .rsplit_once(" at ")
+            .map_or(message.as_str(), |(reason, _)| reason);
+
+        Self::new(day, line, column, token, expected)
+    }
+}
+
+fn column(line: &str, token: &str) -> usize {
+    let offset = (token.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+
+    if offset <= line.len() && line.is_char_boundary(offset) {
+        line[..offset].chars().count() + 1
+    } else {
+        1
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: expected {}, ",
+            self.day, self.line, self.column, self.expected
+        )?;
+
+        if self.token.is_empty() {
+            write!(f, "found end of line")
+        } else {
+            write!(f, "found {:?}", self.token)
+        }
+    }
+}
+
+// aoc-runner prints errors returned by generators with their Debug form,
+// so the human-readable diagnostic is what we show there too.
+impl Debug for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_of_token() {
+        let line = "move 1 from x to 3";
+        let token = line.split_ascii_whitespace().nth(3).unwrap();
+
+        assert_eq!(
+            ParseError::at_token(5, 6, line, token, "a stack number"),
+            ParseError::new(5, 7, 13, "x", "a stack number")
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            ParseError::new(2, 3, 1, "Q", "one of A, B, C").to_string(),
+            r#"day 2, line 3, column 1: expected one of A, B, C, found "Q""#
+        );
+        assert_eq!(
+            ParseError::at_end_of_line(9, 0, "R", "a step count").to_string(),
+            "day 9, line 1, column 2: expected a step count, found end of line"
+        );
+    }
+
+    #[test]
+    fn from_aoc_parse() {
+        use aoc_parse::{parser, prelude::*};
+
+        let error = parser!(lines(i32 "," i32)).parse("1,2\n3;4\n").unwrap_err();
+        let error = ParseError::from_aoc_parse(18, error);
+
+        assert_eq!((error.day, error.line, error.column), (18, 2, 2));
+        assert_eq!(error.token, ";4");
+    }
+}
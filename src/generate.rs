@@ -0,0 +1,1091 @@
+//! Seeded random puzzle inputs, for stress-testing and benchmarking the solvers
+//! on inputs far larger than the puzzle examples.
+//!
+//! Every generator takes a `size` whose meaning depends on the day and returns
+//! an input that the day's parser accepts and its solvers can answer.
+
+use crate::arithmetic::integer;
+use crate::day25::SnafuNumber;
+use crate::geometry::Point2;
+use std::collections::HashSet;
+
+/// A SplitMix64 generator: small, fast and the same on every platform.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Returns a number in `low..=high`.
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next_u64() % (high - low + 1) as u64) as i64
+    }
+
+    /// Returns `true` with a probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+}
+
+pub type Generator = fn(usize, &mut Rng) -> String;
+
+/// The generators of every day, in order.
+pub const GENERATORS: [Generator; 25] = [
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+];
+
+/// Generates an input for `day`, or returns `None` if there is no such day.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let generator = GENERATORS.get(usize::from(day).checked_sub(1)?)?;
+
+    Some(generator(size, &mut Rng::new(seed)))
+}
+
+fn join_lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+fn random_word(rng: &mut Rng, length: usize) -> String {
+    (0..length)
+        .map(|_| (b'a' + rng.below(26) as u8) as char)
+        .collect()
+}
+
+/// Calorie lists of `size` elves.
+pub fn day01(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let food_count = rng.between(1, 15);
+
+            join_lines((0..food_count).map(|_| rng.between(1_000, 60_000).to_string()))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A strategy guide of `size` rounds.
+pub fn day02(size: usize, rng: &mut Rng) -> String {
+    join_lines((0..size.max(1)).map(|_| {
+        let opponent = *rng.choose(&['A', 'B', 'C']);
+        let response = *rng.choose(&['X', 'Y', 'Z']);
+
+        format!("{opponent} {response}")
+    }))
+}
+
+/// The rucksacks of `size` groups of three elves.
+pub fn day03(size: usize, rng: &mut Rng) -> String {
+    let all_item_types: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+    let mut rucksacks = Vec::with_capacity(3 * size.max(1));
+
+    for _ in 0..size.max(1) {
+        let mut item_types = all_item_types.clone();
+        rng.shuffle(&mut item_types);
+
+        // The other 51 item types are split between the three rucksacks, so
+        // that the badge is the only item type the whole group shares.
+        let badge = item_types[0];
+
+        for own_item_types in item_types[1..].chunks(17) {
+            rucksacks.push(rucksack(badge, own_item_types, rng));
+        }
+    }
+
+    join_lines(rucksacks)
+}
+
+fn rucksack(badge: char, own_item_types: &[char], rng: &mut Rng) -> String {
+    let shared = if rng.chance(1, 4) {
+        badge
+    } else {
+        own_item_types[0]
+    };
+    let (first_only, second_only) = own_item_types[1..].split_at(8);
+    let compartment_size = rng.between(2, 16) as usize;
+
+    let mut first_compartment = vec![shared];
+    let mut second_compartment = vec![shared];
+
+    if shared != badge {
+        first_compartment.push(badge);
+    }
+
+    while first_compartment.len() < compartment_size {
+        first_compartment.push(*rng.choose(first_only));
+    }
+
+    while second_compartment.len() < compartment_size {
+        second_compartment.push(*rng.choose(second_only));
+    }
+
+    rng.shuffle(&mut first_compartment);
+    rng.shuffle(&mut second_compartment);
+
+    first_compartment
+        .into_iter()
+        .chain(second_compartment)
+        .collect()
+}
+
+/// `size` pairs of section assignments.
+pub fn day04(size: usize, rng: &mut Rng) -> String {
+    let assignment = |rng: &mut Rng| {
+        let start = rng.between(1, 99);
+
+        format!("{start}-{}", rng.between(start, 99))
+    };
+
+    join_lines((0..size.max(1)).map(|_| format!("{},{}", assignment(rng), assignment(rng))))
+}
+
+/// Nine stacks of crates and `size` rearrangement steps.
+pub fn day05(size: usize, rng: &mut Rng) -> String {
+    const STACK_COUNT: usize = 9;
+
+    let crate_ids: Vec<char> = ('A'..='Z').collect();
+    let mut stacks: Vec<Vec<char>> = (0..STACK_COUNT)
+        .map(|_| {
+            let height = rng.between(1, 8);
+
+            (0..height).map(|_| *rng.choose(&crate_ids)).collect()
+        })
+        .collect();
+
+    let drawing_height = stacks.iter().map(Vec::len).max().unwrap_or_default();
+    let mut lines: Vec<String> = (0..drawing_height)
+        .rev()
+        .map(|level| {
+            stacks
+                .iter()
+                .map(|stack| {
+                    stack
+                        .get(level)
+                        .map_or("   ".to_string(), |crate_id| format!("[{crate_id}]"))
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+
+    lines.push(
+        (1..=STACK_COUNT)
+            .map(|number| format!(" {number} "))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    lines.push(String::new());
+
+    for _ in 0..size {
+        let from = loop {
+            let from = rng.below(STACK_COUNT);
+
+            if !stacks[from].is_empty() {
+                break from;
+            }
+        };
+        let to = (from + 1 + rng.below(STACK_COUNT - 1)) % STACK_COUNT;
+        let count = rng.between(1, stacks[from].len() as i64) as usize;
+
+        let height = stacks[from].len();
+        let moved_crates = stacks[from].split_off(height - count);
+        stacks[to].extend(moved_crates);
+
+        lines.push(format!("move {count} from {} to {}", from + 1, to + 1));
+    }
+
+    join_lines(lines)
+}
+
+/// A datastream of `size` characters whose start-of-message marker comes last.
+pub fn day06(size: usize, rng: &mut Rng) -> String {
+    const MARKER: &str = "nopqrstuvwxyza";
+
+    // Thirteen letters cannot form a start-of-message marker, so part 2 has to
+    // scan the whole datastream.
+    let mut datastream: String = (0..size.saturating_sub(MARKER.len()))
+        .map(|_| (b'a' + rng.below(13) as u8) as char)
+        .collect();
+    datastream.push_str(MARKER);
+    datastream
+}
+
+/// The terminal output of browsing a file system of `size` directories.
+pub fn day07(size: usize, rng: &mut Rng) -> String {
+    let directory_count = size.max(1);
+    let mut children = vec![Vec::new(); directory_count];
+
+    for directory in 1..directory_count {
+        children[rng.below(directory)].push(directory);
+    }
+
+    let mut names = vec!["/".to_string(); directory_count];
+
+    for siblings in &children {
+        let mut sibling_names = HashSet::new();
+
+        for &directory in siblings {
+            names[directory] = loop {
+                let length = rng.between(1, 8) as usize;
+                let name = random_word(rng, length);
+
+                if sibling_names.insert(name.clone()) {
+                    break name;
+                }
+            };
+        }
+    }
+
+    // The root gets at least one file, so that every directory size is known.
+    let mut file_sizes: Vec<Vec<u64>> = (0..directory_count)
+        .map(|directory| {
+            let file_count = rng.between(i64::from(directory == 0), 4);
+
+            (0..file_count)
+                .map(|_| rng.between(1, 1_000) as u64)
+                .collect()
+        })
+        .collect();
+
+    // Scales the files so that the update needs a directory to be deleted.
+    let total_size: u64 = file_sizes.iter().flatten().sum();
+    let used_space = rng.between(45_000_000, 65_000_000) as u64;
+
+    for file_size in file_sizes.iter_mut().flatten() {
+        *file_size = u64::max(1, *file_size * used_space / total_size);
+    }
+
+    let mut lines = vec!["$ cd /".to_string()];
+    let mut stack = vec![Some(0)];
+
+    // Walks the tree depth first, with `None` standing for leaving a directory.
+    while let Some(entry) = stack.pop() {
+        let Some(directory) = entry else {
+            lines.push("$ cd ..".to_string());
+            continue;
+        };
+
+        if directory != 0 {
+            lines.push(format!("$ cd {}", names[directory]));
+        }
+
+        lines.push("$ ls".to_string());
+
+        let mut listing: Vec<String> = children[directory]
+            .iter()
+            .map(|&child| format!("dir {}", names[child]))
+            .chain(file_sizes[directory].iter().map(|file_size| {
+                let length = rng.between(1, 8) as usize;
+
+                format!(
+                    "{file_size} {}.{}",
+                    random_word(rng, length),
+                    random_word(rng, 3)
+                )
+            }))
+            .collect();
+        rng.shuffle(&mut listing);
+        lines.extend(listing);
+
+        for &child in children[directory].iter().rev() {
+            stack.push(None);
+            stack.push(Some(child));
+        }
+    }
+
+    join_lines(lines)
+}
+
+/// A square grid of trees `size` trees wide.
+pub fn day08(size: usize, rng: &mut Rng) -> String {
+    let size = size.max(3);
+
+    join_lines((0..size).map(|_| {
+        (0..size)
+            .map(|_| (b'0' + rng.below(10) as u8) as char)
+            .collect()
+    }))
+}
+
+/// A series of `size` motions.
+pub fn day09(size: usize, rng: &mut Rng) -> String {
+    join_lines((0..size.max(1)).map(|_| {
+        let direction = *rng.choose(&['U', 'D', 'L', 'R']);
+
+        format!("{direction} {}", rng.between(1, 20))
+    }))
+}
+
+/// A program that runs for exactly the 240 cycles the screen takes to draw.
+///
+/// `size` is ignored: the CRT only has room for one frame.
+pub fn day10(_size: usize, rng: &mut Rng) -> String {
+    let mut cycles_left = 240;
+    let mut lines = Vec::new();
+
+    while cycles_left > 0 {
+        if cycles_left >= 2 && rng.chance(2, 3) {
+            lines.push(format!("addx {}", rng.between(-10, 10)));
+            cycles_left -= 2;
+        } else {
+            lines.push("noop".to_string());
+            cycles_left -= 1;
+        }
+    }
+
+    join_lines(lines)
+}
+
+/// Notes on `size` monkeys, between 2 and 8 of them.
+pub fn day11(size: usize, rng: &mut Rng) -> String {
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19];
+    rng.shuffle(&mut divisors);
+
+    let monkey_count = size.clamp(2, divisors.len());
+
+    // Part 1 has no modulus to keep worry levels in check, so only one monkey
+    // multiplies them and none squares them.
+    let multiplying_monkey = rng.below(monkey_count);
+
+    let other_monkey =
+        |id: usize, rng: &mut Rng| (id + 1 + rng.below(monkey_count - 1)) % monkey_count;
+
+    (0..monkey_count)
+        .map(|id| {
+            let item_count = rng.between(1, 6);
+            let items = (0..item_count)
+                .map(|_| rng.between(50, 99).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let operation = if id == multiplying_monkey {
+                format!("old * {}", rng.between(2, 13))
+            } else {
+                format!("old + {}", rng.between(1, 8))
+            };
+
+            let if_true = other_monkey(id, rng);
+            let if_false = loop {
+                let if_false = other_monkey(id, rng);
+
+                if if_false != if_true || monkey_count == 2 {
+                    break if_false;
+                }
+            };
+
+            format!(
+                "Monkey {id}:
+  Starting items: {items}
+  Operation: new = {operation}
+  Test: divisible by {}
+    If true: throw to monkey {if_true}
+    If false: throw to monkey {if_false}",
+                divisors[id]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A heightmap `size` squares wide, rising from `S` on the left to `E` on the right.
+pub fn day12(size: usize, rng: &mut Rng) -> String {
+    let width = size.max(26);
+    let height = usize::max(5, width / 4);
+
+    // Elevations grow by at most one from column to column, and this row keeps
+    // them that way, so that there is always a way up.
+    let path_row = rng.below(height);
+
+    join_lines((0..height).map(|y| {
+        (0..width)
+            .map(|x| {
+                let mut elevation = x * 25 / (width - 1);
+
+                if y != path_row && rng.chance(1, 5) {
+                    elevation = rng.below(elevation + 1);
+                }
+
+                match (x, y) {
+                    (0, y) if y == path_row => 'S',
+                    (x, y) if x == width - 1 && y == path_row => 'E',
+                    _ => (b'a' + elevation as u8) as char,
+                }
+            })
+            .collect()
+    }))
+}
+
+/// `size` pairs of packets.
+pub fn day13(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}\n{}", packet(rng, 0), packet(rng, 0)))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn packet(rng: &mut Rng, depth: usize) -> String {
+    let mut items = Vec::new();
+
+    for _ in 0..rng.between(0, 4) {
+        if depth < 4 && rng.chance(1, 3) {
+            items.push(packet(rng, depth + 1));
+        } else {
+            items.push(rng.between(0, 10).to_string());
+        }
+    }
+
+    format!("[{}]", items.join(","))
+}
+
+/// A scan of `size` rock paths.
+pub fn day14(size: usize, rng: &mut Rng) -> String {
+    join_lines((0..size.max(1)).map(|_| {
+        let (mut x, mut y) = (rng.between(430, 570), rng.between(10, 160));
+        let mut horizontal = rng.chance(1, 2);
+        let mut points = vec![format!("{x},{y}")];
+
+        for _ in 0..rng.between(1, 5) {
+            let step = rng.between(1, 12) * if rng.chance(1, 2) { -1 } else { 1 };
+
+            if horizontal {
+                x = (x + step).clamp(400, 600);
+            } else {
+                y = (y + step).clamp(5, 170);
+            }
+
+            horizontal = !horizontal;
+            points.push(format!("{x},{y}"));
+        }
+
+        points.join(" -> ")
+    }))
+}
+
+/// About `size` sensors that leave a single position of the search space uncovered.
+pub fn day15(size: usize, rng: &mut Rng) -> String {
+    sensors(size, 4_000_000, rng)
+}
+
+fn sensors(size: usize, max_coordinate: i64, rng: &mut Rng) -> String {
+    let distance_to_search_space = |Point2 { x, y }: Point2<i64>| {
+        i64::max(0, i64::max(-x, x - max_coordinate))
+            + i64::max(0, i64::max(-y, y - max_coordinate))
+    };
+
+    // The distress beacon is not on the edge, where the solver would not find it.
+    let hidden = Point2::new(
+        rng.between(1, max_coordinate - 1),
+        rng.between(1, max_coordinate - 1),
+    );
+
+    // Diamonds of radius r centred on the lattice spanned by (r, r + 1) and
+    // (r + 1, -r) cover every position exactly once. Dropping the diamond
+    // centred on the hidden position and doubling the four next to it covers
+    // every position but that one. The other diamonds grow by at least one, so
+    // that the covered segments of a row overlap instead of just touching.
+    let radius = i64::max(
+        1,
+        (max_coordinate as f64 / (2.0 * size.max(1) as f64).sqrt()) as i64,
+    );
+    let steps = 2 * max_coordinate / radius + 2;
+    let mut lines = Vec::new();
+
+    for i in -steps..=steps {
+        for j in -steps..=steps {
+            if (i, j) == (0, 0) {
+                continue;
+            }
+
+            let sensor =
+                hidden + Point2::new(i * radius + j * (radius + 1), i * (radius + 1) - j * radius);
+            let min_radius = if i.abs() + j.abs() == 1 {
+                2 * radius
+            } else {
+                radius + 1
+            };
+
+            if distance_to_search_space(sensor) > min_radius {
+                continue;
+            }
+
+            let max_radius = i64::min(
+                sensor.manhattan_distance(hidden) - 1,
+                min_radius + radius / 2,
+            );
+            let sensor_radius = rng.between(min_radius, max_radius);
+
+            let dx = rng.between(-sensor_radius, sensor_radius);
+            let dy = (sensor_radius - dx.abs()) * if rng.chance(1, 2) { -1 } else { 1 };
+
+            lines.push(format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+                sensor.x,
+                sensor.y,
+                sensor.x + dx,
+                sensor.y + dy
+            ));
+        }
+    }
+
+    rng.shuffle(&mut lines);
+    join_lines(lines)
+}
+
+/// A network of `size` valves, at most 15 of them with a non-zero flow rate.
+pub fn day16(size: usize, rng: &mut Rng) -> String {
+    let valve_count = size.clamp(2, 26 * 26);
+
+    let mut labels: Vec<String> = ('A'..='Z')
+        .flat_map(|first| ('A'..='Z').map(move |second| format!("{first}{second}")))
+        .filter(|label| label != "AA")
+        .collect();
+    rng.shuffle(&mut labels);
+    labels.insert(0, "AA".to_string());
+
+    let mut tunnels: Vec<Vec<usize>> = vec![Vec::new(); valve_count];
+    let mut connect = |a: usize, b: usize| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    };
+
+    // A random tree keeps every valve reachable, and the extra tunnels add loops.
+    for valve in 1..valve_count {
+        connect(valve, rng.below(valve));
+    }
+
+    for _ in 0..valve_count / 2 {
+        connect(rng.below(valve_count), rng.below(valve_count));
+    }
+
+    // The starting valve AA is never worth opening, as in the real inputs.
+    let mut flow_rates = vec![0; valve_count];
+    let mut candidates: Vec<usize> = (1..valve_count).collect();
+    rng.shuffle(&mut candidates);
+
+    for &valve in candidates
+        .iter()
+        .take(usize::min(15, valve_count.div_ceil(3)))
+    {
+        flow_rates[valve] = rng.between(1, 25);
+    }
+
+    let mut lines: Vec<String> = (0..valve_count)
+        .map(|valve| {
+            let connected_valves = tunnels[valve]
+                .iter()
+                .map(|&connected_valve| labels[connected_valve].as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let tunnels_lead_to = if tunnels[valve].len() == 1 {
+                "tunnel leads to valve"
+            } else {
+                "tunnels lead to valves"
+            };
+
+            format!(
+                "Valve {} has flow rate={}; {tunnels_lead_to} {connected_valves}",
+                labels[valve], flow_rates[valve]
+            )
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+
+    join_lines(lines)
+}
+
+/// A jet pattern of `size` pushes, and at least 1000.
+///
+/// The tower of a random pattern of a few hundred pushes can still grow without
+/// ever closing off a row, and then part 2 never finds the cycle it relies on.
+pub fn day17(size: usize, rng: &mut Rng) -> String {
+    (0..size.max(1000))
+        .map(|_| if rng.chance(1, 2) { '<' } else { '>' })
+        .collect()
+}
+
+/// `size` distinct cubes, filling about half of a cube-shaped region.
+pub fn day18(size: usize, rng: &mut Rng) -> String {
+    let cube_count = size.max(1);
+    let side = (2.0 * cube_count as f64).cbrt().ceil() as i64 + 1;
+    let mut cubes = HashSet::with_capacity(cube_count);
+    let mut lines = Vec::with_capacity(cube_count);
+
+    while lines.len() < cube_count {
+        let cube = (
+            rng.between(0, side - 1),
+            rng.between(0, side - 1),
+            rng.between(0, side - 1),
+        );
+
+        if cubes.insert(cube) {
+            lines.push(format!("{},{},{}", cube.0, cube.1, cube.2));
+        }
+    }
+
+    join_lines(lines)
+}
+
+/// `size` blueprints with costs in the ranges of the real inputs.
+pub fn day19(size: usize, rng: &mut Rng) -> String {
+    join_lines((1..=size.max(1)).map(|id| {
+        format!(
+            "Blueprint {id}: \
+            Each ore robot costs {} ore. \
+            Each clay robot costs {} ore. \
+            Each obsidian robot costs {} ore and {} clay. \
+            Each geode robot costs {} ore and {} obsidian.",
+            rng.between(2, 4),
+            rng.between(2, 4),
+            rng.between(2, 4),
+            rng.between(5, 20),
+            rng.between(2, 4),
+            rng.between(5, 20),
+        )
+    }))
+}
+
+/// An encrypted file of `size` numbers, exactly one of them 0.
+pub fn day20(size: usize, rng: &mut Rng) -> String {
+    let length = size.max(1);
+    let zero_index = rng.below(length);
+
+    join_lines((0..length).map(|index| {
+        let number = if index == zero_index {
+            0
+        } else {
+            let number = rng.between(1, 10_000);
+
+            if rng.chance(1, 2) {
+                -number
+            } else {
+                number
+            }
+        };
+
+        number.to_string()
+    }))
+}
+
+/// About `size` monkeys doing math, with `root` comparing two sides that depend
+/// on `humn` through additions, subtractions and multiplications only, so that
+/// part 2 has an integer answer.
+pub fn day21(size: usize, rng: &mut Rng) -> String {
+    let mut builder = MonkeyMath {
+        rng,
+        names: HashSet::from(["root".to_string(), "humn".to_string()]),
+        lines: Vec::new(),
+    };
+
+    let budget = size.max(1);
+    let human_budget = builder.rng.below(budget);
+    let human_side_target = builder.rng.between(1, 1_000);
+    let (human_side, coefficient) = builder.human_subtree(human_side_target, human_budget, 1);
+
+    // The human side yells `target + coefficient * (humn - original)`, so
+    // shouting `original + shift` balances it with the other side.
+    let shift = builder.rng.between(1, 1_000);
+    let other_side = builder.subtree(
+        human_side_target + coefficient * shift,
+        budget - 1 - human_budget,
+    );
+
+    let (lhs, rhs) = if builder.rng.chance(1, 2) {
+        (human_side, other_side)
+    } else {
+        (other_side, human_side)
+    };
+
+    builder.lines.push(format!("root: {lhs} + {rhs}"));
+    builder.rng.shuffle(&mut builder.lines);
+
+    join_lines(builder.lines)
+}
+
+struct MonkeyMath<'a> {
+    rng: &'a mut Rng,
+    names: HashSet<String>,
+    lines: Vec<String>,
+}
+
+impl MonkeyMath<'_> {
+    const MAX_DIVIDEND: i64 = 1_000_000_000;
+    const MAX_COEFFICIENT: i64 = 1_000_000;
+
+    fn name(&mut self) -> String {
+        loop {
+            let name = random_word(self.rng, 4);
+
+            if self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    fn small_divisor(&mut self, target: i64) -> Option<i64> {
+        let divisors: Vec<_> = (2..=5)
+            .filter(|divisor| target != 0 && target % divisor == 0)
+            .collect();
+
+        (!divisors.is_empty()).then(|| *self.rng.choose(&divisors))
+    }
+
+    fn split(&mut self, budget: usize) -> (usize, usize) {
+        let lhs_budget = self.rng.below(budget);
+
+        (lhs_budget, budget - 1 - lhs_budget)
+    }
+
+    /// Adds monkeys that yell `target` using `budget` operations and returns the
+    /// name of the top one.
+    fn subtree(&mut self, target: i64, budget: usize) -> String {
+        let name = self.name();
+
+        if budget == 0 {
+            self.lines.push(format!("{name}: {target}"));
+            return name;
+        }
+
+        let (lhs_budget, rhs_budget) = self.split(budget);
+        let (lhs, operator, rhs) = match self.rng.below(4) {
+            1 => {
+                let rhs = self.rng.between(1, 100);
+                (target + rhs, '-', rhs)
+            }
+            2 if self.small_divisor(target).is_some() => {
+                let divisor = self.small_divisor(target).unwrap();
+                (target / divisor, '*', divisor)
+            }
+            3 if target.abs() < Self::MAX_DIVIDEND => {
+                let divisor = self.rng.between(2, 4);
+                (target * divisor, '/', divisor)
+            }
+            _ => {
+                let rhs = self.rng.between(1, 100);
+                (target - rhs, '+', rhs)
+            }
+        };
+
+        let lhs = self.subtree(lhs, lhs_budget);
+        let rhs = self.subtree(rhs, rhs_budget);
+        self.lines.push(format!("{name}: {lhs} {operator} {rhs}"));
+
+        name
+    }
+
+    /// Like `subtree`, with `humn` somewhere below, and also returns how much
+    /// the yelled number changes for each unit `humn` changes. `scale` is how
+    /// much the monkeys above already multiply that change.
+    fn human_subtree(&mut self, target: i64, budget: usize, scale: i64) -> (String, i64) {
+        if budget == 0 {
+            self.lines.push(format!("humn: {target}"));
+            return ("humn".to_string(), 1);
+        }
+
+        let name = self.name();
+        let (human_budget, other_budget) = self.split(budget);
+        let human_on_left = self.rng.chance(1, 2);
+
+        let (human_target, other, operator, factor) = match self.rng.below(3) {
+            1 if human_on_left => {
+                let other = self.rng.between(1, 100);
+                (target + other, other, '-', 1)
+            }
+            1 => {
+                let other = self.rng.between(1, 100);
+                (other - target, other, '-', -1)
+            }
+            2 if scale < Self::MAX_COEFFICIENT && self.small_divisor(target).is_some() => {
+                let divisor = self.small_divisor(target).unwrap();
+                (target / divisor, divisor, '*', divisor)
+            }
+            _ => {
+                let other = self.rng.between(1, 100);
+                (target - other, other, '+', 1)
+            }
+        };
+
+        let (human, coefficient) =
+            self.human_subtree(human_target, human_budget, scale * factor.abs());
+        let other = self.subtree(other, other_budget);
+
+        let (lhs, rhs) = if human_on_left {
+            (human, other)
+        } else {
+            (other, human)
+        };
+
+        self.lines.push(format!("{name}: {lhs} {operator} {rhs}"));
+
+        (name, coefficient * factor)
+    }
+}
+
+/// The cube net of the real inputs, with sides of 50 tiles, and a path of `size` moves.
+pub fn day22(size: usize, rng: &mut Rng) -> String {
+    const SIDE_SIZE: usize = 50;
+    const NET: [[bool; 3]; 4] = [
+        [false, true, true],
+        [false, true, false],
+        [true, true, false],
+        [true, false, false],
+    ];
+
+    let mut lines = Vec::with_capacity(4 * SIDE_SIZE + 2);
+
+    for faces in NET {
+        let columns = 3 - faces.iter().rev().take_while(|&&face| !face).count();
+
+        for _ in 0..SIDE_SIZE {
+            let line: String = faces[..columns]
+                .iter()
+                .flat_map(|&face| {
+                    (0..SIDE_SIZE)
+                        .map(|_| match face {
+                            false => ' ',
+                            true if rng.chance(1, 12) => '#',
+                            true => '.',
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            lines.push(line);
+        }
+    }
+
+    // You start on the leftmost open tile of the top row.
+    lines[0].replace_range(SIDE_SIZE..=SIDE_SIZE, ".");
+
+    let mut path = String::new();
+
+    for move_index in 0..size.max(1) {
+        if move_index > 0 {
+            path.push(*rng.choose(&['L', 'R']));
+        }
+
+        path.push_str(&rng.between(1, 50).to_string());
+    }
+
+    lines.push(String::new());
+    lines.push(path);
+
+    join_lines(lines)
+}
+
+/// A square grove `size` tiles wide, about half of them elves.
+pub fn day23(size: usize, rng: &mut Rng) -> String {
+    let size = size.max(1);
+    let center = size / 2;
+
+    join_lines((0..size).map(|y| {
+        (0..size)
+            .map(|x| {
+                if (x, y) == (center, center) || rng.chance(1, 2) {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect()
+    }))
+}
+
+/// A valley `size` tiles wide, and at least 60, and a fifth as high, half filled
+/// with blizzards.
+///
+/// Narrower valleys are sometimes impassable.
+pub fn day24(size: usize, rng: &mut Rng) -> String {
+    let columns = size.max(60);
+    let rows = columns / 5;
+
+    let mut lines = vec![format!("#.{}", "#".repeat(columns))];
+
+    for _ in 0..rows {
+        let tiles: String = (0..columns)
+            .map(|column| {
+                if !rng.chance(1, 2) {
+                    return '.';
+                }
+
+                // As in the real inputs, no blizzard crosses the entrance or
+                // the exit.
+                if column == 0 || column == columns - 1 {
+                    *rng.choose(&['<', '>'])
+                } else {
+                    *rng.choose(&['<', '>', '^', 'v'])
+                }
+            })
+            .collect();
+
+        lines.push(format!("#{tiles}#"));
+    }
+
+    lines.push(format!("{}.#", "#".repeat(columns)));
+
+    join_lines(lines)
+}
+
+/// `size` SNAFU numbers.
+pub fn day25(size: usize, rng: &mut Rng) -> String {
+    join_lines((0..size.max(1)).map(|_| {
+        SnafuNumber::try_from(integer(rng.between(1, 1_000_000_000_000)))
+            .unwrap()
+            .to_string()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{Answer, Part, DEFAULT_VARIANT};
+    use crate::{day15, solution};
+
+    /// Variants that take too long to solve in a debug build.
+    const SLOW_VARIANTS: [(u8, Part, &str); 4] = [
+        (11, Part::Two, "trillion"),
+        (15, Part::Two, DEFAULT_VARIANT),
+        (19, Part::One, DEFAULT_VARIANT),
+        (19, Part::Two, DEFAULT_VARIANT),
+    ];
+
+    #[test]
+    fn deterministic() {
+        for day in 1..=25 {
+            assert_eq!(generate(day, 20, 7), generate(day, 20, 7));
+            assert_ne!(generate(day, 20, 7), generate(day, 20, 8), "day {day}");
+        }
+
+        assert_eq!(generate(26, 20, 7), None);
+    }
+
+    /// Days whose generators only make solvable inputs from a minimum size on.
+    const SIZE_LIMITED_DAYS: [u8; 2] = [17, 24];
+
+    #[test]
+    fn solvable() {
+        for day in 1..=25 {
+            let solution = solution(day).unwrap();
+            let seeds = if SIZE_LIMITED_DAYS.contains(&day) {
+                0..10
+            } else {
+                2022..2023
+            };
+
+            for (size, seed) in [0, 1, 2, 20]
+                .into_iter()
+                .flat_map(|size| seeds.clone().map(move |seed| (size, seed)))
+            {
+                let input = generate(day, size, seed).unwrap();
+                let parsed_input = solution
+                    .parse(&input)
+                    .unwrap_or_else(|error| panic!("size {size}, seed {seed}: {error}"));
+
+                for part in Part::ALL {
+                    for &variant in solution.variants(part) {
+                        if SLOW_VARIANTS.contains(&(day, part, variant)) {
+                            continue;
+                        }
+
+                        assert_ne!(
+                            parsed_input.solve(part, variant),
+                            Some(Answer::NotFound),
+                            "day {day}, part {part}, size {size}, seed {seed}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sensors_hide_one_position() {
+        const MAX_COORDINATE: i64 = 60;
+
+        for seed in 0..10 {
+            let coordinates =
+                day15::parse(&sensors(12, MAX_COORDINATE, &mut Rng::new(seed))).unwrap();
+            let uncovered: Vec<_> = (0..=MAX_COORDINATE)
+                .flat_map(|y| (0..=MAX_COORDINATE).map(move |x| (x, y)))
+                .filter(|&(x, y)| {
+                    coordinates.iter().all(|&(sensor, beacon)| {
+                        sensor.manhattan_distance(Point2::new(x, y))
+                            > sensor.manhattan_distance(beacon)
+                    })
+                })
+                .collect();
+
+            assert_eq!(uncovered.len(), 1);
+            assert_eq!(
+                day15::tuning_frequency(&coordinates, MAX_COORDINATE),
+                Ok(Some(integer(uncovered[0].0 * 4_000_000 + uncovered[0].1)))
+            );
+        }
+    }
+
+    #[test]
+    fn monkey_math_balances() {
+        let monkey_math = solution(21).unwrap();
+
+        for seed in 0..10 {
+            let input = day21(50, &mut Rng::new(seed));
+            let Some(Answer::Integer(human_number)) = monkey_math
+                .parse(&input)
+                .unwrap()
+                .solve(Part::Two, DEFAULT_VARIANT)
+            else {
+                panic!("expected a number");
+            };
+
+            // With the right number, both sides of the root monkey are equal.
+            let balanced_input = input
+                .lines()
+                .map(|line| {
+                    if line.starts_with("humn: ") {
+                        format!("humn: {human_number}")
+                    } else if line.starts_with("root: ") {
+                        line.replace(" + ", " - ")
+                    } else {
+                        line.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            assert_eq!(
+                monkey_math
+                    .parse(&balanced_input)
+                    .unwrap()
+                    .solve(Part::One, DEFAULT_VARIANT),
+                Some(Answer::Integer(0))
+            );
+        }
+    }
+}
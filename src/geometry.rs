@@ -0,0 +1,515 @@
+//! Points, directions and grids shared by the puzzles set on a map or in space.
+//!
+//! The y axis points down, as in the puzzle inputs, so going [`Direction::Up`]
+//! decreases y.
+
+use crate::error::ParseError;
+use num_traits::Signed;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub, SubAssign};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Ord, PartialOrd)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Ord, PartialOrd)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// The offsets of the eight neighbors of a point, clockwise from the top left.
+const NEIGHBORS8: [(i8, i8); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+/// The offsets of the six neighbors of a point in space.
+const NEIGHBORS6: [(i8, i8, i8); 6] = [
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, -1, 0),
+    (0, 1, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+impl<T: Signed + Copy + From<i8>> Point2<T> {
+    pub fn manhattan_distance(self, other: Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// Returns the point with each coordinate replaced by its sign.
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Returns the points one step away in each [`Direction`].
+    pub fn neighbors4(self) -> impl Iterator<Item = Self> {
+        Direction::ALL
+            .into_iter()
+            .map(move |direction| self + direction.offset())
+    }
+
+    /// Returns the points one step away, diagonals included.
+    pub fn neighbors8(self) -> impl Iterator<Item = Self> {
+        NEIGHBORS8
+            .into_iter()
+            .map(move |(x, y)| self + Point2::new(x.into(), y.into()))
+    }
+}
+
+impl<T: Signed + Copy + From<i8>> Point3<T> {
+    pub fn manhattan_distance(self, other: Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    /// Returns the points sharing a face with this one.
+    pub fn neighbors6(self) -> impl Iterator<Item = Self> {
+        NEIGHBORS6
+            .into_iter()
+            .map(move |(x, y, z)| self + Point3::new(x.into(), y.into(), z.into()))
+    }
+}
+
+macro_rules! impl_operators {
+    ($point:ident { $($coordinate:ident),* }) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($coordinate: self.$coordinate + other.$coordinate),* }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($coordinate: self.$coordinate - other.$coordinate),* }
+            }
+        }
+
+        impl<T: Mul<Output = T> + Copy> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                Self { $($coordinate: self.$coordinate * factor),* }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: Self) {
+                $(self.$coordinate += other.$coordinate;)*
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: Self) {
+                $(self.$coordinate -= other.$coordinate;)*
+            }
+        }
+    };
+}
+
+impl_operators!(Point2 { x, y });
+impl_operators!(Point3 { x, y, z });
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Rotation {
+    Clockwise,
+    Counterclockwise,
+}
+
+impl Direction {
+    /// Every direction, clockwise from `Up`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// Returns the step of length one in this direction.
+    pub fn offset<T: Signed>(self) -> Point2<T> {
+        match self {
+            Direction::Up => Point2::new(T::zero(), -T::one()),
+            Direction::Right => Point2::new(T::one(), T::zero()),
+            Direction::Down => Point2::new(T::zero(), T::one()),
+            Direction::Left => Point2::new(-T::one(), T::zero()),
+        }
+    }
+
+    pub fn rotate(self, rotation: Rotation) -> Self {
+        let quarter_turns = match rotation {
+            Rotation::Clockwise => 1,
+            Rotation::Counterclockwise => 3,
+        };
+
+        Direction::ALL[(self as usize + quarter_turns) % 4]
+    }
+
+    pub fn reverse(self) -> Self {
+        Direction::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// Component-wise operations on the coordinates of a point.
+pub trait Coordinates: Copy + Add<Output = Self> + Sub<Output = Self> {
+    type Coordinate;
+
+    /// Returns the point with every coordinate equal to `coordinate`.
+    fn splat(coordinate: Self::Coordinate) -> Self;
+
+    fn component_min(self, other: Self) -> Self;
+
+    fn component_max(self, other: Self) -> Self;
+
+    /// Returns whether every coordinate is at most the matching one of `other`.
+    fn all_le(self, other: Self) -> bool;
+}
+
+macro_rules! impl_coordinates {
+    ($point:ident { $($coordinate:ident),* }) => {
+        impl<T: Ord + Copy + Add<Output = T> + Sub<Output = T>> Coordinates for $point<T> {
+            type Coordinate = T;
+
+            fn splat(coordinate: T) -> Self {
+                Self { $($coordinate: coordinate),* }
+            }
+
+            fn component_min(self, other: Self) -> Self {
+                Self { $($coordinate: self.$coordinate.min(other.$coordinate)),* }
+            }
+
+            fn component_max(self, other: Self) -> Self {
+                Self { $($coordinate: self.$coordinate.max(other.$coordinate)),* }
+            }
+
+            fn all_le(self, other: Self) -> bool {
+                $(self.$coordinate <= other.$coordinate)&&*
+            }
+        }
+    };
+}
+
+impl_coordinates!(Point2 { x, y });
+impl_coordinates!(Point3 { x, y, z });
+
+/// The smallest box, bounds included, containing a set of points.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BoundingBox<P> {
+    pub min: P,
+    pub max: P,
+}
+
+impl<P: Coordinates> BoundingBox<P> {
+    /// Returns the bounding box of `points`, or `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = P>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(
+            BoundingBox {
+                min: first,
+                max: first,
+            },
+            |BoundingBox { min, max }, point| BoundingBox {
+                min: min.component_min(point),
+                max: max.component_max(point),
+            },
+        ))
+    }
+
+    pub fn contains(&self, point: P) -> bool {
+        self.min.all_le(point) && point.all_le(self.max)
+    }
+
+    /// Returns the box grown by `margin` on every side.
+    pub fn expand(&self, margin: P::Coordinate) -> Self {
+        let margin = P::splat(margin);
+
+        BoundingBox {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+}
+
+impl<T: Signed + Copy> BoundingBox<Point2<T>> {
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::one()
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::one()
+    }
+
+    pub fn area(&self) -> T {
+        self.width() * self.height()
+    }
+}
+
+/// A dense rectangular grid, indexed by `Point2<usize>` with the origin at the top left.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    tiles: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, tile: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            tiles: vec![tile; width * height],
+        }
+    }
+
+    /// Builds a grid from its rows, or returns `None` if they are empty or of different lengths.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first()?.len();
+
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        Some(Grid {
+            width,
+            height: rows.len(),
+            tiles: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses one row of tiles per line, one tile per character.
+    ///
+    /// `parse_tile` returns `None` for characters that are not a tile, which are
+    /// then reported as not being the `expected` tile.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        expected: &str,
+        mut parse_tile: impl FnMut(Point2<usize>, char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let mut tiles = Vec::with_capacity(input.len());
+        let mut width = None;
+        let mut height = 0;
+
+        for (y, line) in input.lines().enumerate() {
+            for (x, char) in line.chars().enumerate() {
+                tiles.push(
+                    parse_tile(Point2::new(x, y), char)
+                        .ok_or_else(|| ParseError::at_char(day, y, x, char, expected))?,
+                );
+            }
+
+            let line_width = line.chars().count();
+
+            match width {
+                None if line_width > 0 => width = Some(line_width),
+                Some(width) if line_width == width => {}
+                _ => {
+                    return Err(ParseError::at_end_of_line(
+                        day,
+                        y,
+                        line,
+                        match width {
+                            Some(width) => format!("a row of {width} tiles"),
+                            None => expected.to_string(),
+                        },
+                    ))
+                }
+            }
+
+            height += 1;
+        }
+
+        let width = width.ok_or_else(|| ParseError::at_end_of_line(day, 0, "", expected))?;
+
+        Ok(Grid {
+            width,
+            height,
+            tiles,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point2<usize>) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    pub fn get(&self, point: Point2<usize>) -> Option<&T> {
+        self.contains(point)
+            .then(|| &self.tiles[point.y * self.width + point.x])
+    }
+
+    pub fn get_mut(&mut self, point: Point2<usize>) -> Option<&mut T> {
+        self.contains(point)
+            .then(|| &mut self.tiles[point.y * self.width + point.x])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.tiles.chunks(self.width)
+    }
+
+    /// Returns every point of the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point2<usize>> {
+        let width = self.width;
+
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point2::new(x, y)))
+    }
+
+    /// Returns the neighbors of `point` in each [`Direction`] that are inside the grid.
+    pub fn neighbors4(&self, point: Point2<usize>) -> impl Iterator<Item = Point2<usize>> + '_ {
+        Direction::ALL.into_iter().filter_map(move |direction| {
+            let offset = direction.offset::<isize>();
+            let neighbor = Point2::new(
+                point.x.checked_add_signed(offset.x)?,
+                point.y.checked_add_signed(offset.y)?,
+            );
+
+            self.contains(neighbor).then_some(neighbor)
+        })
+    }
+}
+
+impl<T> Index<Point2<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point2<usize>) -> &T {
+        assert!(self.contains(point), "{point:?} is outside the grid");
+
+        &self.tiles[point.y * self.width + point.x]
+    }
+}
+
+impl<T> IndexMut<Point2<usize>> for Grid<T> {
+    fn index_mut(&mut self, point: Point2<usize>) -> &mut T {
+        assert!(self.contains(point), "{point:?} is outside the grid");
+
+        &mut self.tiles[point.y * self.width + point.x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors() {
+        let point = Point2::new(3, -2);
+
+        assert_eq!(
+            point.neighbors4().collect::<Vec<_>>(),
+            [
+                Point2::new(3, -3),
+                Point2::new(4, -2),
+                Point2::new(3, -1),
+                Point2::new(2, -2)
+            ]
+        );
+        assert_eq!(point.neighbors8().count(), 8);
+        assert!(point
+            .neighbors8()
+            .all(|neighbor| neighbor != point && (neighbor - point).signum() == neighbor - point));
+        assert!(Point3::new(0, 0, 0)
+            .neighbors6()
+            .all(|neighbor| neighbor.manhattan_distance(Point3::new(0, 0, 0)) == 1));
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::Up.rotate(Rotation::Clockwise), Direction::Right);
+        assert_eq!(
+            Direction::Up.rotate(Rotation::Counterclockwise),
+            Direction::Left
+        );
+        assert_eq!(Direction::Left.reverse(), Direction::Right);
+        assert_eq!(Direction::Down.offset::<i32>() * 3, Point2::new(0, 3));
+    }
+
+    #[test]
+    fn bounding_box() {
+        let bounding_box =
+            BoundingBox::from_points([Point2::new(2, 1), Point2::new(-1, 4), Point2::new(0, 0)])
+                .unwrap();
+
+        assert_eq!(bounding_box.min, Point2::new(-1, 0));
+        assert_eq!(bounding_box.area(), 20);
+        assert!(bounding_box.contains(Point2::new(2, 4)));
+        assert!(!bounding_box.contains(Point2::new(3, 4)));
+        assert!(bounding_box.expand(1).contains(Point2::new(3, 4)));
+        assert_eq!(BoundingBox::<Point2<i32>>::from_points([]), None);
+    }
+
+    #[test]
+    fn parse_grid() {
+        let grid = Grid::parse(8, "123\n456", "a digit", |_, char| char.to_digit(10)).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point2::new(2, 1)], 6);
+        assert_eq!(grid.get(Point2::new(3, 1)), None);
+        assert_eq!(
+            grid.neighbors4(Point2::new(0, 0)).collect::<Vec<_>>(),
+            [Point2::new(1, 0), Point2::new(0, 1)]
+        );
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            [[1, 2, 3].as_slice(), [4, 5, 6].as_slice()]
+        );
+    }
+
+    #[test]
+    fn parse_malformed_grid() {
+        let parse = |input| Grid::parse(8, input, "a digit", |_, char| char.to_digit(10));
+
+        assert_eq!(
+            parse("123\n4x6").unwrap_err().to_string(),
+            r#"day 8, line 2, column 2: expected a digit, found "x""#
+        );
+        assert_eq!(
+            parse("123\n45").unwrap_err().to_string(),
+            "day 8, line 2, column 3: expected a row of 3 tiles, found end of line"
+        );
+        assert!(parse("").is_err());
+    }
+}
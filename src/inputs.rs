@@ -0,0 +1,510 @@
+//! Puzzle inputs, downloaded from adventofcode.com with a session token and
+//! kept in a local cache so that each of them is only downloaded once.
+//!
+//! The cache is the directory aoc-runner and `aoc2022 run --all` read the
+//! inputs from, named `day1.txt` to `day25.txt`. A `SHA256SUMS` file next to
+//! them records the checksum of every download in the format of
+//! `sha256sum --check`, so that an input edited or cut short by accident is
+//! noticed instead of giving wrong answers.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+/// The shortest time between two downloads, to go easy on the server.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+pub(crate) const YEAR: u16 = 2022;
+
+/// Identifies the tool making the requests, as the Advent of Code team asks.
+const USER_AGENT: &str = "github.com/ropewalker/advent_of_code_2022";
+
+const CHECKSUMS: &str = "SHA256SUMS";
+
+/// Holds the time of the last request, so that the interval between downloads
+/// is kept across runs of the program.
+const LAST_REQUEST: &str = ".last-request";
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(PathBuf, io::Error),
+    /// A cached input no longer matches the checksum recorded when it was downloaded.
+    ChecksumMismatch(PathBuf),
+    /// The input is not in the cache and there is no session token to download it with.
+    MissingSession,
+    /// The server did not accept the session token.
+    InvalidSession,
+    /// The puzzle of the day has not been unlocked yet.
+    NotAvailable(u8),
+    RateLimited(Option<Duration>),
+    Status(u16),
+    Transport(String),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            InputError::ChecksumMismatch(path) => write!(
+                f,
+                "{}: does not match its checksum in {CHECKSUMS}, delete it to download it again",
+                path.display()
+            ),
+            InputError::MissingSession => write!(f, "no session token to download inputs with"),
+            InputError::InvalidSession => write!(f, "the session token was not accepted"),
+            InputError::NotAvailable(day) => write!(f, "the input of day {day} is not available"),
+            InputError::RateLimited(Some(retry_after)) => write!(
+                f,
+                "too many requests, retry in {} seconds",
+                retry_after.as_secs()
+            ),
+            InputError::RateLimited(None) => write!(f, "too many requests, retry later"),
+            InputError::Status(status) => write!(f, "the server answered with status {status}"),
+            InputError::Transport(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+/// A directory of inputs with their checksums.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(file_name(day))
+    }
+
+    /// Returns the cached input of `day`, if there is one. Inputs put in the
+    /// directory by hand have no checksum and are taken as they are.
+    pub fn get(&self, day: u8) -> Result<Option<String>, InputError> {
+        let path = self.path(day);
+
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(InputError::Io(path, error)),
+        };
+
+        match self.checksums()?.get(&file_name(day)) {
+            Some(checksum) if *checksum != sha256(&input) => {
+                Err(InputError::ChecksumMismatch(path))
+            }
+            _ => Ok(Some(input)),
+        }
+    }
+
+    /// Saves the input of `day` and records its checksum.
+    pub fn insert(&self, day: u8, input: &str) -> Result<(), InputError> {
+        fs::create_dir_all(&self.dir).map_err(|error| InputError::Io(self.dir.clone(), error))?;
+
+        let mut checksums = self.checksums()?;
+        checksums.insert(file_name(day), sha256(input));
+
+        write_atomically(&self.path(day), input)?;
+        write_atomically(
+            &self.dir.join(CHECKSUMS),
+            &checksums
+                .iter()
+                .map(|(file_name, checksum)| format!("{checksum}  {file_name}\n"))
+                .collect::<String>(),
+        )
+    }
+
+    /// Reads the checksums, by file name.
+    fn checksums(&self) -> Result<BTreeMap<String, String>, InputError> {
+        let path = self.dir.join(CHECKSUMS);
+
+        match fs::read_to_string(&path) {
+            Ok(checksums) => Ok(checksums
+                .lines()
+                .filter_map(|line| line.split_once("  "))
+                .map(|(checksum, file_name)| (file_name.to_string(), checksum.to_string()))
+                .collect()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(error) => Err(InputError::Io(path, error)),
+        }
+    }
+}
+
+fn file_name(day: u8) -> String {
+    format!("day{day}.txt")
+}
+
+fn sha256(input: &str) -> String {
+    Sha256::digest(input)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Writes `contents` next to `path` first, so that an interrupted write does
+/// not leave half an input behind.
+fn write_atomically(path: &Path, contents: &str) -> Result<(), InputError> {
+    let mut partial_path = path.as_os_str().to_owned();
+    partial_path.push(".partial");
+
+    fs::write(&partial_path, contents)
+        .and_then(|()| fs::rename(&partial_path, path))
+        .map_err(|error| InputError::Io(path.to_path_buf(), error))
+}
+
+/// An input, and whether it had to be downloaded.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Fetched {
+    pub input: String,
+    pub downloaded: bool,
+}
+
+/// Gets inputs from the cache, downloading the missing ones.
+pub struct Downloader {
+    cache: Cache,
+    session: Option<String>,
+    base_url: String,
+    min_interval: Duration,
+    agent: ureq::Agent,
+}
+
+impl Downloader {
+    pub fn new(cache: Cache, session: Option<String>) -> Self {
+        Downloader {
+            cache,
+            session,
+            base_url: BASE_URL.to_string(),
+            min_interval: MIN_INTERVAL,
+            agent: agent(None),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Sets how the Advent of Code team can reach whoever runs the downloads.
+    pub fn with_contact(mut self, contact: Option<&str>) -> Self {
+        self.agent = agent(contact);
+        self
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// Returns the input of `day`, downloading it only if it is not cached.
+    pub fn fetch(&self, day: u8) -> Result<Fetched, InputError> {
+        if let Some(input) = self.cache.get(day)? {
+            return Ok(Fetched {
+                input,
+                downloaded: false,
+            });
+        }
+
+        let input = self.download(day)?;
+        self.cache.insert(day, &input)?;
+
+        Ok(Fetched {
+            input,
+            downloaded: true,
+        })
+    }
+
+    fn download(&self, day: u8) -> Result<String, InputError> {
+        let session = self.session.as_ref().ok_or(InputError::MissingSession)?;
+
+        self.wait_for_turn()?;
+
+        let response = self
+            .agent
+            .get(&format!("{}/{YEAR}/day/{day}/input", self.base_url))
+            .set("Cookie", &format!("session={session}"))
+            .call();
+
+        self.record_request()?;
+
+        match response {
+            Ok(response) => response
+                .into_string()
+                .map_err(|error| InputError::Transport(error.to_string())),
+            // The server asks to log in when the token is missing or has expired.
+            Err(ureq::Error::Status(400, _)) => Err(InputError::InvalidSession),
+            Err(ureq::Error::Status(404, _)) => Err(InputError::NotAvailable(day)),
+            Err(ureq::Error::Status(429, response)) => Err(InputError::RateLimited(
+                response
+                    .header("Retry-After")
+                    .and_then(|seconds| seconds.parse().ok())
+                    .map(Duration::from_secs),
+            )),
+            Err(ureq::Error::Status(status, _)) => Err(InputError::Status(status)),
+            Err(ureq::Error::Transport(error)) => Err(InputError::Transport(error.to_string())),
+        }
+    }
+
+    /// Sleeps until `min_interval` has passed since the last request.
+    fn wait_for_turn(&self) -> Result<(), InputError> {
+        let path = self.cache.dir.join(LAST_REQUEST);
+
+        let last_request = match fs::read_to_string(&path) {
+            Ok(milliseconds) => milliseconds.trim().parse().ok().map(Duration::from_millis),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(InputError::Io(path, error)),
+        };
+
+        if let Some(last_request) = last_request {
+            let elapsed = since_epoch().saturating_sub(last_request);
+            thread::sleep(self.min_interval.saturating_sub(elapsed));
+        }
+
+        Ok(())
+    }
+
+    fn record_request(&self) -> Result<(), InputError> {
+        let path = self.cache.dir.join(LAST_REQUEST);
+
+        fs::create_dir_all(&self.cache.dir)
+            .and_then(|()| fs::write(&path, since_epoch().as_millis().to_string()))
+            .map_err(|error| InputError::Io(path, error))
+    }
+}
+
+/// An HTTP client that identifies this tool to the server.
+pub(crate) fn agent(contact: Option<&str>) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(30))
+        .user_agent(&user_agent(contact))
+        .build()
+}
+
+/// Names this tool, followed by `contact`, e.g. an email address, when there is
+/// one. The user agent cannot name whoever runs the tool without it.
+fn user_agent(contact: Option<&str>) -> String {
+    match contact {
+        Some(contact) => format!("{USER_AGENT} by {contact}"),
+        None => USER_AGENT.to_string(),
+    }
+}
+
+pub(crate) fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Serves one response per connection, in order, and returns the requests it
+/// received, headers and body, once all responses are sent.
+#[cfg(test)]
+pub(crate) fn mock_server(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let mut requests = Vec::new();
+
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut content_length = 0;
+
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+
+                if line.trim_end().is_empty() {
+                    break;
+                }
+
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+
+                request.push_str(&line);
+            }
+
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            request.push_str(&String::from_utf8(request_body).unwrap());
+
+            requests.push(request);
+
+            write!(
+                stream,
+                "HTTP/1.1 {status} Mock\r\nRetry-After: 60\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+
+        requests
+    });
+
+    (base_url, server)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn cache(name: &str) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("aoc2022-inputs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        Cache::new(dir)
+    }
+
+    fn downloader(name: &str, base_url: String) -> Downloader {
+        Downloader::new(cache(name), Some("53616c7465645f5f".to_string()))
+            .with_base_url(base_url)
+            .with_min_interval(Duration::ZERO)
+    }
+
+    #[test]
+    fn downloads_once() {
+        let (base_url, server) = mock_server(vec![(200, "1000\n2000\n\n3000\n")]);
+        let downloader = downloader("once", base_url);
+
+        let first = downloader.fetch(1).unwrap();
+        let second = downloader.fetch(1).unwrap();
+
+        assert!(first.downloaded);
+        assert!(!second.downloaded);
+        assert_eq!(second.input, "1000\n2000\n\n3000\n");
+
+        let requests = server.join().unwrap();
+
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2022/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("session=53616c7465645f5f"));
+        assert!(requests[0].contains(&format!("User-Agent: {USER_AGENT}\r\n")));
+
+        let checksums = fs::read_to_string(downloader.cache().dir.join(CHECKSUMS)).unwrap();
+
+        assert_eq!(
+            checksums,
+            format!("{}  day1.txt\n", sha256("1000\n2000\n\n3000\n"))
+        );
+    }
+
+    #[test]
+    fn contact_in_user_agent() {
+        let (base_url, server) = mock_server(vec![(200, "A Y\n")]);
+
+        downloader("contact", base_url)
+            .with_contact(Some("someone@example.com"))
+            .fetch(2)
+            .unwrap();
+
+        assert!(server.join().unwrap()[0].contains(&format!(
+            "User-Agent: {USER_AGENT} by someone@example.com\r\n"
+        )));
+    }
+
+    #[test]
+    fn detects_corrupted_inputs() {
+        let cache = cache("corrupted");
+        cache.insert(6, "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n").unwrap();
+        fs::write(cache.path(6), "mjqjpqmgbljsphdztnvjf").unwrap();
+
+        assert!(matches!(cache.get(6), Err(InputError::ChecksumMismatch(_))));
+    }
+
+    #[test]
+    fn takes_inputs_put_in_by_hand() {
+        let cache = cache("by-hand");
+        fs::create_dir_all(&cache.dir).unwrap();
+        fs::write(cache.path(2), "A Y\nB X\nC Z\n").unwrap();
+
+        let downloader = Downloader::new(cache, None);
+
+        assert_eq!(
+            downloader.fetch(2).unwrap(),
+            Fetched {
+                input: "A Y\nB X\nC Z\n".to_string(),
+                downloaded: false
+            }
+        );
+        assert!(matches!(
+            downloader.fetch(3),
+            Err(InputError::MissingSession)
+        ));
+    }
+
+    #[test]
+    fn error_statuses() {
+        let (base_url, server) = mock_server(vec![
+            (
+                404,
+                "Please don't repeatedly request this endpoint before it unlocks!",
+            ),
+            (
+                400,
+                "Puzzle inputs differ by user.  Please log in to get your puzzle input.",
+            ),
+            (429, ""),
+            (503, ""),
+        ]);
+        let downloader = downloader("errors", base_url);
+
+        assert!(matches!(
+            downloader.fetch(25),
+            Err(InputError::NotAvailable(25))
+        ));
+        assert!(matches!(
+            downloader.fetch(25),
+            Err(InputError::InvalidSession)
+        ));
+        assert!(matches!(
+            downloader.fetch(25),
+            Err(InputError::RateLimited(Some(retry_after))) if retry_after.as_secs() == 60
+        ));
+        assert!(matches!(downloader.fetch(25), Err(InputError::Status(503))));
+        assert_eq!(downloader.cache().get(25).unwrap(), None);
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn waits_between_requests() {
+        let (base_url, server) =
+            mock_server(vec![(200, "A Y\n"), (200, "vJrwpWtwJgWrhcsFMMfFFhFp\n")]);
+        let downloader =
+            downloader("interval", base_url).with_min_interval(Duration::from_millis(300));
+
+        let start = Instant::now();
+        downloader.fetch(2).unwrap();
+        downloader.fetch(3).unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+}
@@ -1,3 +1,817 @@
-use aoc_runner_derive::aoc_main;
+use advent_of_code_2022::inputs::{Cache, Downloader, InputError};
+use advent_of_code_2022::preprocess;
+use advent_of_code_2022::runner::{self, Job, Run, Summary};
+use advent_of_code_2022::solution::{Answer, Part};
+use advent_of_code_2022::stepper::{self, Stepper, Timeline};
+use advent_of_code_2022::submit::{History, Submitter, Verdict};
+use advent_of_code_2022::verify::{self, ExpectedAnswers, Outcome};
+use advent_of_code_2022::visualize;
+use advent_of_code_2022::{solution, SOLUTIONS};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
 
-aoc_main! { lib = advent_of_code_2022 }
+const DEFAULT_INPUT_DIR: &str = "input/2022";
+const DEFAULT_ANSWERS: &str = "answers.toml";
+const SESSION_VARIABLE: &str = "AOC_SESSION";
+const HISTORY_FILE: &str = "submissions.jsonl";
+const SESSION_HELP: &str = "set AOC_SESSION to the session cookie of adventofcode.com, \
+                            or save it to a file for --session-file";
+const CONTACT_VARIABLE: &str = "AOC_CONTACT";
+const CONTACT_HELP: &str = "set AOC_CONTACT to an email address or a profile URL to send it \
+                            with the requests, so that the Advent of Code team can reach you";
+
+#[derive(Parser)]
+#[command(name = "aoc2022", about = "Solutions for Advent of Code 2022")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one day, or every day with --all
+    Run(RunArgs),
+    /// Check the answers for the inputs in --input-dir against an answers file
+    Verify(VerifyArgs),
+    /// Watch a simulation in the terminal or save it as an animation
+    Visualize(VisualizeArgs),
+    /// Step through a simulation in the terminal, forward and back, and inspect its cells
+    Step(StepArgs),
+    /// Download puzzle inputs into --input-dir, skipping the ones already there
+    Download(DownloadArgs),
+    /// Submit an answer, found by solving the input in --input-dir unless given
+    Submit(SubmitArgs),
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Day to solve
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u8).range(1..=25),
+        required_unless_present = "all"
+    )]
+    day: Option<u8>,
+
+    /// Solve every day, reading the inputs from --input-dir
+    #[arg(long, conflicts_with_all = ["day", "input"])]
+    all: bool,
+
+    /// Part to solve [default: both]
+    #[arg(short, long, value_parser = parse_part)]
+    part: Option<Part>,
+
+    /// Implementation to use instead of the default one
+    #[arg(short, long)]
+    variant: Option<String>,
+
+    /// Input file, `-` for stdin [default: stdin]
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Directory with the inputs of --all, named day1.txt to day25.txt
+    #[arg(long, default_value = DEFAULT_INPUT_DIR, requires = "all")]
+    input_dir: PathBuf,
+
+    /// Days of --all to solve at the same time [default: one per CPU]
+    #[arg(short, long, conflicts_with = "day")]
+    jobs: Option<NonZeroUsize>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, ValueEnum)]
+enum Format {
+    /// Answers and timings for humans
+    Text,
+    /// A JSON array with one object per solved part
+    Json,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// TOML file with the expected answers, e.g. `[day1]` with `part1 = 24000`
+    #[arg(short, long, default_value = DEFAULT_ANSWERS)]
+    answers: PathBuf,
+
+    /// Directory with the inputs, named day1.txt to day25.txt
+    #[arg(long, default_value = DEFAULT_INPUT_DIR)]
+    input_dir: PathBuf,
+
+    /// Only verify this day
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: Option<u8>,
+}
+
+#[derive(Args)]
+struct VisualizeArgs {
+    /// Day to visualize: 9, 14, 17, 22, 23 or 24
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Part to visualize
+    #[arg(short, long, value_parser = parse_part, default_value = "1")]
+    part: Part,
+
+    /// Input file, `-` for stdin [default: stdin]
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Save the frames to a `.gif` or `.ppm` file instead of playing them in the terminal
+    #[arg(short, long, value_parser = parse_output)]
+    output: Option<PathBuf>,
+
+    /// Milliseconds between frames
+    #[arg(long, default_value_t = 100)]
+    delay: u64,
+
+    /// Pixels per tile in the saved images
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..))]
+    scale: u8,
+}
+
+#[derive(Args)]
+struct StepArgs {
+    /// Day to step through: 9, 14, 17, 22, 23 or 24
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Part to step through
+    #[arg(short, long, value_parser = parse_part, default_value = "1")]
+    part: Part,
+
+    /// Input file, `-` for stdin [default: stdin]
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Milliseconds between ticks while playing
+    #[arg(long, default_value_t = 100)]
+    delay: u64,
+}
+
+#[derive(Args)]
+struct DownloadArgs {
+    /// Day to download
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u8).range(1..=25),
+        required_unless_present = "all"
+    )]
+    day: Option<u8>,
+
+    /// Download every day
+    #[arg(long, conflicts_with = "day")]
+    all: bool,
+
+    /// Directory to save the inputs in, named day1.txt to day25.txt
+    #[arg(long, default_value = DEFAULT_INPUT_DIR)]
+    input_dir: PathBuf,
+
+    /// File with the session cookie of adventofcode.com [default: $AOC_SESSION]
+    #[arg(long)]
+    session_file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SubmitArgs {
+    /// Day of the puzzle
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Part of the puzzle
+    #[arg(short, long, value_parser = parse_part)]
+    part: Part,
+
+    /// Answer to submit [default: the answer for the input of the day]
+    #[arg(short, long)]
+    answer: Option<String>,
+
+    /// Directory with the inputs, named day1.txt to day25.txt
+    #[arg(long, default_value = DEFAULT_INPUT_DIR)]
+    input_dir: PathBuf,
+
+    /// File to record the attempts in [default: submissions.jsonl in --input-dir]
+    #[arg(long)]
+    history: Option<PathBuf>,
+
+    /// File with the session cookie of adventofcode.com [default: $AOC_SESSION]
+    #[arg(long)]
+    session_file: Option<PathBuf>,
+}
+
+fn parse_part(part: &str) -> Result<Part, String> {
+    part.parse()
+        .map_err(|_| format!("expected 1 or 2, found {part:?}"))
+}
+
+fn parse_output(output: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(output);
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gif" | "ppm") => Ok(path),
+        _ => Err(format!("expected a .gif or .ppm file, found {output:?}")),
+    }
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Run(args) => run(&args),
+        Command::Verify(args) => verify(&args),
+        Command::Visualize(args) => visualize(&args),
+        Command::Step(args) => step(&args),
+        Command::Download(args) => download(&args),
+        Command::Submit(args) => submit(&args),
+    }
+}
+
+fn run(args: &RunArgs) -> ExitCode {
+    let solutions = match args.day {
+        Some(day) => vec![solution(day).expect("days are validated by the argument parser")],
+        None => SOLUTIONS.to_vec(),
+    };
+
+    let mut failed = false;
+    let mut solved_any = false;
+    let mut jobs = Vec::new();
+
+    for solution in solutions {
+        let selection = runner::select(solution, args.part, args.variant.as_deref());
+
+        if selection.is_empty() {
+            continue;
+        }
+
+        solved_any = true;
+
+        match read_input(solution.day(), args) {
+            Ok(input) => {
+                report_normalization(solution.day(), &input);
+                jobs.push(Job {
+                    solution,
+                    input,
+                    selection,
+                });
+            }
+            Err(error) => {
+                eprintln!("error: {error}");
+                failed = true;
+            }
+        }
+    }
+
+    let threads = match args.jobs {
+        Some(jobs) => jobs,
+        None => thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+    };
+
+    let start = Instant::now();
+    let results = runner::run_parallel(&jobs, threads.get());
+    let wall_time = start.elapsed();
+
+    let mut runs = Vec::new();
+
+    for result in results {
+        match result {
+            Ok(day_runs) => runs.extend(day_runs),
+            Err(error) => {
+                eprintln!("error: {error}");
+                failed = true;
+            }
+        }
+    }
+
+    if !solved_any {
+        eprintln!("error: {}", nothing_to_solve(args));
+        failed = true;
+    }
+
+    match args.format {
+        Format::Text => {
+            runs.iter().for_each(print_run);
+
+            if args.all {
+                println!("\n{}", Summary::new(&runs, wall_time, threads.get()));
+            }
+        }
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&runs).expect("runs serialize to JSON")
+        ),
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn verify(args: &VerifyArgs) -> ExitCode {
+    let expected_answers = match read_file(&args.answers).and_then(|answers| {
+        ExpectedAnswers::parse(&answers)
+            .map_err(|error| format!("{}: {error}", args.answers.display()))
+    }) {
+        Ok(expected_answers) => expected_answers,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (mut passed, mut mismatched, mut failed) = (0, 0, 0);
+
+    for day in expected_answers.days() {
+        if args.day.is_some_and(|selected| selected != day) {
+            continue;
+        }
+
+        let solution = solution(day).expect("answers are only parsed for existing days");
+        let checks = match read_file(&args.input_dir.join(format!("day{day}.txt"))) {
+            Ok(input) => {
+                report_normalization(day, &input);
+                verify::verify(solution, &input, &expected_answers)
+            }
+            Err(error) => verify::failed(day, &expected_answers, &error),
+        };
+
+        for check in checks {
+            println!("{check}");
+
+            match check.outcome {
+                Outcome::Pass => passed += 1,
+                Outcome::Mismatch { .. } => mismatched += 1,
+                Outcome::Fail(_) => failed += 1,
+            }
+        }
+    }
+
+    println!("{passed} passed, {mismatched} mismatched, {failed} failed");
+
+    if mismatched + failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn visualize(args: &VisualizeArgs) -> ExitCode {
+    let solution = solution(args.day).expect("days are validated by the argument parser");
+
+    let result = read_file_or_stdin(args.input.as_deref()).and_then(|input| {
+        report_normalization(args.day, &input);
+
+        let parsed_input = solution.parse(&input).map_err(|error| error.to_string())?;
+        let frames = parsed_input
+            .frames(args.part)
+            .ok_or_else(|| format!("day {} has no visualization", args.day))?;
+
+        let delay = Duration::from_millis(args.delay);
+        let scale = usize::from(args.scale);
+
+        let Some(path) = &args.output else {
+            return visualize::play(frames, delay, &mut io::stdout().lock())
+                .map_err(|error| format!("stdout: {error}"));
+        };
+
+        let mut out = File::create(path)
+            .map(BufWriter::new)
+            .map_err(|error| format!("{}: {error}", path.display()))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gif") => visualize::write_gif(frames, scale, delay, &mut out),
+            _ => visualize::write_ppm(frames, scale, &mut out),
+        }
+        .and_then(|()| out.flush())
+        .map_err(|error| format!("{}: {error}", path.display()))
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn step(args: &StepArgs) -> ExitCode {
+    let solution = solution(args.day).expect("days are validated by the argument parser");
+
+    let result = read_file_or_stdin(args.input.as_deref()).and_then(|input| {
+        report_normalization(args.day, &input);
+
+        let parsed_input = solution.parse(&input).map_err(|error| error.to_string())?;
+        let frames = || parsed_input.frames(args.part);
+
+        if frames().is_none() {
+            return Err(format!("day {} has no visualization", args.day));
+        }
+
+        let timeline = Timeline::new(|| frames().unwrap(), stepper::HISTORY)
+            .ok_or_else(|| format!("day {} part {} has nothing to show", args.day, args.part))?;
+        let mut stepper = Stepper::new(format!("Day {} part {}", args.day, args.part), timeline);
+
+        stepper::run(
+            &mut stepper,
+            Duration::from_millis(args.delay),
+            &mut io::stdout().lock(),
+        )
+        .map_err(|error| format!("terminal: {error}"))
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn download(args: &DownloadArgs) -> ExitCode {
+    let session = match read_session(args.session_file.as_deref()) {
+        Ok(session) => session,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let downloader = Downloader::new(Cache::new(&args.input_dir), session)
+        .with_contact(read_contact().as_deref());
+    let days = match args.day {
+        Some(day) => vec![day],
+        None => (1..=25).collect(),
+    };
+
+    let mut failed = false;
+
+    for day in days {
+        let path = downloader.cache().path(day);
+
+        match downloader.fetch(day) {
+            Ok(fetched) if fetched.downloaded => {
+                println!("day {day}: downloaded to {}", path.display())
+            }
+            Ok(_) => println!("day {day}: already in {}", path.display()),
+            Err(error) => {
+                eprintln!("error: day {day}: {error}");
+                failed = true;
+
+                if matches!(error, InputError::MissingSession) {
+                    eprintln!("note: {SESSION_HELP}");
+                }
+
+                // Later days would fail the same way.
+                if matches!(
+                    error,
+                    InputError::MissingSession
+                        | InputError::InvalidSession
+                        | InputError::RateLimited(_)
+                ) {
+                    break;
+                }
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn submit(args: &SubmitArgs) -> ExitCode {
+    let result = read_session(args.session_file.as_deref()).and_then(|session| {
+        let session = session.ok_or_else(|| format!("no session token, {SESSION_HELP}"))?;
+
+        let answer = match &args.answer {
+            Some(answer) => answer.clone(),
+            None => solve(args)?,
+        };
+
+        let history_path = match &args.history {
+            Some(path) => path.clone(),
+            None => args.input_dir.join(HISTORY_FILE),
+        };
+        let history = History::load(history_path).map_err(|error| error.to_string())?;
+
+        let verdict = Submitter::new(session, history)
+            .with_contact(read_contact().as_deref())
+            .submit(args.day, args.part, &answer)
+            .map_err(|error| format!("day {} part {}: {error}", args.day, args.part))?;
+
+        println!(
+            "Day {} - Part {}: {answer} is {verdict}",
+            args.day, args.part
+        );
+
+        Ok(verdict)
+    });
+
+    match result {
+        Ok(Verdict::Correct) => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Solves the part to submit with the input of the day in --input-dir.
+fn solve(args: &SubmitArgs) -> Result<String, String> {
+    let solution = solution(args.day).expect("days are validated by the argument parser");
+    let input = read_file(&args.input_dir.join(format!("day{}.txt", args.day)))?;
+    report_normalization(args.day, &input);
+
+    let selection = runner::select(solution, Some(args.part), None);
+    let runs = runner::run(solution, &input, &selection).map_err(|error| error.to_string())?;
+
+    match runs.into_iter().next().map(|run| run.answer) {
+        Some(Answer::NotFound) => Err(format!(
+            "day {} part {} has no answer for this input",
+            args.day, args.part
+        )),
+        Some(Answer::Overflow) => Err(format!(
+            "day {} part {} overflows with this input, build with --features bigint",
+            args.day, args.part
+        )),
+        Some(Answer::Text(text)) if text.contains('\n') => Err(format!(
+            "day {} part {} answers with a picture, read it and pass it with --answer:\n{text}",
+            args.day, args.part
+        )),
+        Some(answer) => Ok(answer.to_string()),
+        None => Err(format!("day {} has no part {}", args.day, args.part)),
+    }
+}
+
+/// Reads the session token from `path`, or from the environment without one.
+fn read_session(path: Option<&Path>) -> Result<Option<String>, String> {
+    match path {
+        Some(path) => read_file(path).map(|session| Some(session.trim().to_string())),
+        None => Ok(std::env::var(SESSION_VARIABLE).ok()),
+    }
+}
+
+/// Reads how the Advent of Code team can reach the user from the environment,
+/// with a note when it is not set.
+fn read_contact() -> Option<String> {
+    let contact = std::env::var(CONTACT_VARIABLE)
+        .ok()
+        .filter(|contact| !contact.trim().is_empty());
+
+    if contact.is_none() {
+        eprintln!("note: {CONTACT_HELP}");
+    }
+
+    contact
+}
+
+fn read_input(day: u8, args: &RunArgs) -> Result<String, String> {
+    if args.all {
+        return read_file(&args.input_dir.join(format!("day{day}.txt")));
+    }
+
+    read_file_or_stdin(args.input.as_deref())
+}
+
+fn read_file_or_stdin(path: Option<&Path>) -> Result<String, String> {
+    match path {
+        Some(path) if path != Path::new("-") => read_file(path),
+        _ => {
+            let mut input = String::new();
+
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|error| format!("stdin: {error}"))?;
+
+            Ok(input)
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))
+}
+
+/// Tells on stderr how the input was cleaned up before parsing, e.g. that it had CRLF line endings.
+fn report_normalization(day: u8, input: &str) {
+    let changes = preprocess::changes(day, input);
+
+    if !changes.is_empty() {
+        eprintln!("note: day {day} input: {changes}");
+    }
+}
+
+fn nothing_to_solve(args: &RunArgs) -> String {
+    let variant = args.variant.as_deref().unwrap_or_default();
+
+    match args.day.and_then(solution) {
+        Some(solution) => {
+            let available = Part::ALL
+                .into_iter()
+                .filter(|&part| args.part.is_none_or(|selected| selected == part))
+                .flat_map(|part| solution.variants(part))
+                .copied()
+                .collect::<Vec<_>>();
+
+            if available.is_empty() {
+                format!("day {} has no such part", solution.day())
+            } else {
+                format!(
+                    "day {} has no variant {variant:?}, available: {}",
+                    solution.day(),
+                    available.join(", ")
+                )
+            }
+        }
+        None => format!("no day has a variant {variant:?}"),
+    }
+}
+
+fn print_run(run: &Run) {
+    let answer = run.answer.to_string();
+    let separator = if answer.contains('\n') { "\n" } else { " " };
+
+    println!(
+        "Day {} - Part {} - {}:{separator}{answer}",
+        run.day, run.part, run.variant
+    );
+    println!("\tparse: {:?}, solve: {:?}", run.parse_time, run.solve_time);
+
+    for line in run.trace.to_string().lines() {
+        println!("\t{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn run_args(args: &[&str]) -> Result<RunArgs, clap::Error> {
+        let cli = Cli::try_parse_from(["aoc2022", "run"].iter().chain(args))?;
+
+        match cli.command {
+            Command::Run(args) => Ok(args),
+            _ => panic!("expected the run command"),
+        }
+    }
+
+    #[test]
+    fn command() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn single_day() {
+        let args = run_args(&["--day", "16", "--part", "2", "--input", "day16.txt"]).unwrap();
+
+        assert_eq!(args.day, Some(16));
+        assert_eq!(args.part, Some(Part::Two));
+        assert_eq!(args.input, Some(PathBuf::from("day16.txt")));
+        assert!(!args.all);
+    }
+
+    #[test]
+    fn all_days() {
+        let args = run_args(&["--all", "--variant", "naive"]).unwrap();
+
+        assert!(args.all);
+        assert_eq!(args.variant.as_deref(), Some("naive"));
+        assert_eq!(args.input_dir, PathBuf::from(DEFAULT_INPUT_DIR));
+        assert_eq!(args.format, Format::Text);
+        assert_eq!(args.jobs, None);
+
+        let args = run_args(&["--all", "--jobs", "4"]).unwrap();
+
+        assert_eq!(args.jobs, NonZeroUsize::new(4));
+        assert!(run_args(&["--all", "--jobs", "0"]).is_err());
+        assert!(run_args(&["--day", "1", "--jobs", "4"]).is_err());
+    }
+
+    #[test]
+    fn json_format() {
+        let args = run_args(&["--day", "6", "--format", "json"]).unwrap();
+
+        assert_eq!(args.format, Format::Json);
+        assert!(run_args(&["--day", "6", "--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(run_args(&[]).is_err());
+        assert!(run_args(&["--day", "26"]).is_err());
+        assert!(run_args(&["--day", "1", "--part", "3"]).is_err());
+        assert!(run_args(&["--day", "1", "--all"]).is_err());
+        assert!(run_args(&["--all", "--input", "day1.txt"]).is_err());
+    }
+
+    #[test]
+    fn verify_defaults() {
+        let cli = Cli::try_parse_from(["aoc2022", "verify", "--day", "5"]).unwrap();
+
+        match cli.command {
+            Command::Verify(args) => {
+                assert_eq!(args.answers, PathBuf::from(DEFAULT_ANSWERS));
+                assert_eq!(args.input_dir, PathBuf::from(DEFAULT_INPUT_DIR));
+                assert_eq!(args.day, Some(5));
+            }
+            _ => panic!("expected the verify command"),
+        }
+    }
+
+    #[test]
+    fn visualize_arguments() {
+        let cli =
+            Cli::try_parse_from(["aoc2022", "visualize", "-d", "14", "-o", "sand.gif"]).unwrap();
+
+        match cli.command {
+            Command::Visualize(args) => {
+                assert_eq!(args.day, 14);
+                assert_eq!(args.part, Part::One);
+                assert_eq!(args.output, Some(PathBuf::from("sand.gif")));
+                assert_eq!(args.scale, 4);
+            }
+            _ => panic!("expected the visualize command"),
+        }
+
+        assert!(
+            Cli::try_parse_from(["aoc2022", "visualize", "-d", "14", "-o", "sand.png"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["aoc2022", "visualize", "-d", "14", "--scale", "0"]).is_err());
+    }
+
+    #[test]
+    fn step_arguments() {
+        let cli = Cli::try_parse_from(["aoc2022", "step", "-d", "17", "-i", "day17.txt"]).unwrap();
+
+        match cli.command {
+            Command::Step(args) => {
+                assert_eq!(args.day, 17);
+                assert_eq!(args.part, Part::One);
+                assert_eq!(args.input, Some(PathBuf::from("day17.txt")));
+                assert_eq!(args.delay, 100);
+            }
+            _ => panic!("expected the step command"),
+        }
+
+        assert!(Cli::try_parse_from(["aoc2022", "step", "-d", "17", "-p", "3"]).is_err());
+    }
+
+    #[test]
+    fn download_arguments() {
+        let cli = Cli::try_parse_from(["aoc2022", "download", "--all"]).unwrap();
+
+        match cli.command {
+            Command::Download(args) => {
+                assert!(args.all);
+                assert_eq!(args.input_dir, PathBuf::from(DEFAULT_INPUT_DIR));
+                assert_eq!(args.session_file, None);
+            }
+            _ => panic!("expected the download command"),
+        }
+
+        assert!(Cli::try_parse_from(["aoc2022", "download"]).is_err());
+        assert!(Cli::try_parse_from(["aoc2022", "download", "-d", "3", "--all"]).is_err());
+    }
+
+    #[test]
+    fn submit_arguments() {
+        let cli = Cli::try_parse_from(["aoc2022", "submit", "-d", "1", "-p", "2", "-a", "45000"])
+            .unwrap();
+
+        match cli.command {
+            Command::Submit(args) => {
+                assert_eq!((args.day, args.part), (1, Part::Two));
+                assert_eq!(args.answer.as_deref(), Some("45000"));
+                assert_eq!(args.history, None);
+            }
+            _ => panic!("expected the submit command"),
+        }
+
+        assert!(Cli::try_parse_from(["aoc2022", "submit", "-d", "1"]).is_err());
+    }
+
+    #[test]
+    fn unknown_variant() {
+        let args = run_args(&["--day", "8", "--part", "1", "--variant", "naive"]).unwrap();
+
+        assert_eq!(
+            nothing_to_solve(&args),
+            r#"day 8 has no variant "naive", available: default"#
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Normalization of puzzle inputs before they reach the parsers.
+//!
+//! Inputs saved on Windows or pasted from a browser may start with a byte
+//! order mark, end their lines with CRLF, have spaces at the end of lines,
+//! blank lines made of spaces or end with several blank lines. The parsers
+//! split sections on `"\n\n"` and index characters by offset, so every day's
+//! `parse` normalizes its input first. The final line break is dropped as
+//! well, since none of the parsers need it.
+//!
+//! Day 5 draws its stacks with spaces, so the ends of the lines of its drawing
+//! are kept as they are.
+
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
+
+/// What [`normalize`] changed in an input, besides dropping the final line break.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Changes {
+    pub byte_order_mark: bool,
+    pub crlf_line_endings: usize,
+    /// Lines with nothing but whitespace, which are made empty.
+    pub whitespace_lines: usize,
+    /// Other lines ending with whitespace, which is trimmed.
+    pub trailing_whitespace_lines: usize,
+    pub trailing_blank_lines: usize,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        *self == Changes::default()
+    }
+}
+
+impl Display for Changes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut changes = Vec::new();
+
+        if self.byte_order_mark {
+            changes.push("removed the byte order mark".to_string());
+        }
+
+        for (count, action, noun) in [
+            (self.crlf_line_endings, "converted", "CRLF line ending"),
+            (self.whitespace_lines, "emptied", "whitespace-only line"),
+            (self.trailing_whitespace_lines, "trimmed", "padded line"),
+            (self.trailing_blank_lines, "removed", "trailing blank line"),
+        ] {
+            if count > 0 {
+                let plural = if count == 1 { "" } else { "s" };
+                changes.push(format!("{action} {count} {noun}{plural}"));
+            }
+        }
+
+        if changes.is_empty() {
+            write!(f, "no changes")
+        } else {
+            write!(f, "{}", changes.join(", "))
+        }
+    }
+}
+
+/// Which lines keep the whitespace at their end.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum LineEnds {
+    TrimAll,
+    KeepInFirstSection,
+}
+
+const DRAWING_DAY: u8 = 5;
+
+/// Returns `input` with LF line endings, without a byte order mark, whitespace
+/// at the end of lines, trailing blank lines or a final line break, and with
+/// whitespace-only lines emptied.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    scan(input, LineEnds::TrimAll).0
+}
+
+/// Normalizes `input` like [`normalize`], but keeps the ends of the lines
+/// before the first blank line, e.g. the stacks drawing of day 5.
+pub fn normalize_drawing(input: &str) -> Cow<'_, str> {
+    scan(input, LineEnds::KeepInFirstSection).0
+}
+
+/// Reports what the parser of `day` changes in `input` when it normalizes it.
+pub fn changes(day: u8, input: &str) -> Changes {
+    let line_ends = if day == DRAWING_DAY {
+        LineEnds::KeepInFirstSection
+    } else {
+        LineEnds::TrimAll
+    };
+
+    scan(input, line_ends).1
+}
+
+fn scan(input: &str, line_ends: LineEnds) -> (Cow<'_, str>, Changes) {
+    let mut changes = Changes::default();
+
+    let input = match input.strip_prefix(BYTE_ORDER_MARK) {
+        Some(input) => {
+            changes.byte_order_mark = true;
+            input
+        }
+        None => input,
+    };
+
+    let mut lines: Vec<&str> = if input.is_empty() {
+        Vec::new()
+    } else {
+        // The final line break ends the last line rather than starting another one.
+        let body = input.strip_suffix('\n').unwrap_or(input);
+
+        body.split('\n')
+            .map(|line| match line.strip_suffix('\r') {
+                Some(line) => {
+                    changes.crlf_line_endings += 1;
+                    line
+                }
+                None => line,
+            })
+            .collect()
+    };
+
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+        changes.trailing_blank_lines += 1;
+    }
+
+    let mut in_first_section = true;
+
+    for line in lines.iter_mut() {
+        if line.trim().is_empty() {
+            in_first_section = false;
+
+            if !line.is_empty() {
+                *line = "";
+                changes.whitespace_lines += 1;
+            }
+        } else if line.len() != line.trim_end().len()
+            && !(in_first_section && line_ends == LineEnds::KeepInFirstSection)
+        {
+            *line = line.trim_end();
+            changes.trailing_whitespace_lines += 1;
+        }
+    }
+
+    if changes.is_empty() {
+        let input = input.strip_suffix('\n').unwrap_or(input);
+        (Cow::Borrowed(input), changes)
+    } else {
+        (Cow::Owned(lines.join("\n")), changes)
+    }
+}
+
+/// Turns an example into what a Windows editor might save: a byte order mark,
+/// spaces and tabs at the end of lines, CRLF line endings and a few trailing
+/// blank lines.
+#[cfg(test)]
+pub fn windows(input: &str) -> String {
+    let lines: Vec<_> = input
+        .split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{line} \t")
+            }
+        })
+        .collect();
+
+    format!("{BYTE_ORDER_MARK}{}\r\n\r\n  \r\n", lines.join("\r\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_input_is_borrowed() {
+        assert!(matches!(normalize("1000\n2000\n\n3000"), Cow::Borrowed(_)));
+        assert_eq!(normalize("1000\n2000\n\n3000\n"), "1000\n2000\n\n3000");
+        assert!(changes(1, "1000\n2000\n\n3000\n").is_empty());
+    }
+
+    #[test]
+    fn windows_input() {
+        let input = windows("1000\n2000\n\n3000");
+
+        assert_eq!(normalize(&input), "1000\n2000\n\n3000");
+        assert_eq!(
+            changes(1, &input),
+            Changes {
+                byte_order_mark: true,
+                crlf_line_endings: 6,
+                whitespace_lines: 0,
+                trailing_whitespace_lines: 3,
+                trailing_blank_lines: 2,
+            }
+        );
+        assert_eq!(
+            changes(1, &input).to_string(),
+            "removed the byte order mark, converted 6 CRLF line endings, \
+             trimmed 3 padded lines, removed 2 trailing blank lines"
+        );
+    }
+
+    #[test]
+    fn whitespace_lines() {
+        let input = "1000\n \t\n2000\n";
+
+        assert_eq!(normalize(input), "1000\n\n2000");
+        assert_eq!(
+            changes(1, input).to_string(),
+            "emptied 1 whitespace-only line"
+        );
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        let input = "1000 \n2-4,6-8\t\n\n30373  ";
+
+        assert_eq!(normalize(input), "1000\n2-4,6-8\n\n30373");
+        assert_eq!(changes(1, input).to_string(), "trimmed 3 padded lines");
+    }
+
+    #[test]
+    fn leading_whitespace_is_kept() {
+        assert_eq!(normalize("    [D]\r\n[N] [C]"), "    [D]\n[N] [C]");
+    }
+
+    #[test]
+    fn drawing_line_ends_are_kept() {
+        let input = "    [D]    \r\n[N] [C]    \n 1   2   3 \n\nmove 1 from 2 to 1 \n";
+
+        assert_eq!(
+            normalize_drawing(input),
+            "    [D]    \n[N] [C]    \n 1   2   3 \n\nmove 1 from 2 to 1"
+        );
+        assert_eq!(changes(5, input).trailing_whitespace_lines, 1);
+        assert_eq!(changes(4, input).trailing_whitespace_lines, 4);
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(normalize(""), "");
+        assert!(changes(1, "").is_empty());
+        assert_eq!(normalize("\n\n"), "");
+        assert_eq!(changes(1, "\n\n").trailing_blank_lines, 2);
+    }
+}
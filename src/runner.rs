@@ -0,0 +1,387 @@
+use crate::error::ParseError;
+use crate::solution::{Answer, DynSolution, Part};
+use crate::trace::{self, Trace};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The outcome of solving one part of a day with one variant.
+#[derive(Clone, Debug, Serialize)]
+pub struct Run {
+    pub day: u8,
+    pub part: Part,
+    pub variant: &'static str,
+    pub answer: Answer,
+    #[serde(rename = "parse_time_ns", serialize_with = "nanoseconds")]
+    pub parse_time: Duration,
+    #[serde(rename = "solve_time_ns", serialize_with = "nanoseconds")]
+    pub solve_time: Duration,
+    /// What the solver recorded with the `trace` feature, empty without it.
+    #[serde(skip_serializing_if = "Trace::is_empty")]
+    pub trace: Trace,
+}
+
+fn nanoseconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX))
+}
+
+/// Picks the parts and variants to run.
+///
+/// Without a `variant`, every selected part runs its default variant. With one,
+/// only the selected parts that implement it run.
+pub fn select(
+    solution: &dyn DynSolution,
+    part: Option<Part>,
+    variant: Option<&str>,
+) -> Vec<(Part, &'static str)> {
+    Part::ALL
+        .into_iter()
+        .filter(|&candidate| part.is_none_or(|part| part == candidate))
+        .filter_map(|part| {
+            let variants = solution.variants(part);
+
+            let selected = match variant {
+                None => variants.first(),
+                Some(variant) => variants.iter().find(|&&candidate| candidate == variant),
+            };
+
+            selected.map(|&variant| (part, variant))
+        })
+        .collect()
+}
+
+/// Parses `input` once and solves every selected part and variant with it.
+pub fn run(
+    solution: &dyn DynSolution,
+    input: &str,
+    selection: &[(Part, &'static str)],
+) -> Result<Vec<Run>, ParseError> {
+    let start = Instant::now();
+    let parsed_input = solution.parse(input)?;
+    let parse_time = start.elapsed();
+
+    Ok(selection
+        .iter()
+        .filter_map(|&(part, variant)| {
+            trace::take();
+            let start = Instant::now();
+            let answer = parsed_input.solve(part, variant)?;
+            let solve_time = start.elapsed();
+
+            Some(Run {
+                day: solution.day(),
+                part,
+                variant,
+                answer,
+                parse_time,
+                solve_time,
+                trace: trace::take(),
+            })
+        })
+        .collect())
+}
+
+/// A day to solve, with its input and the parts and variants to run.
+pub struct Job {
+    pub solution: &'static dyn DynSolution,
+    pub input: String,
+    pub selection: Vec<(Part, &'static str)>,
+}
+
+/// Runs the jobs on up to `threads` threads, each taking the next day to solve
+/// as soon as it is done with one, and returns the results in the order of `jobs`.
+pub fn run_parallel(jobs: &[Job], threads: usize) -> Vec<Result<Vec<Run>, ParseError>> {
+    let next_job = AtomicUsize::new(0);
+    let mut results: Vec<_> = jobs.iter().map(|_| None).collect();
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.clamp(1, jobs.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+
+                    loop {
+                        let index = next_job.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(index) else {
+                            return results;
+                        };
+
+                        results.push((index, run(job.solution, &job.input, &job.selection)));
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            // A solver that panics takes the whole run down, as it would on one thread.
+            let worker_results = worker
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload));
+
+            for (index, result) in worker_results {
+                results[index] = Some(result);
+            }
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every job is run"))
+        .collect()
+}
+
+struct DayTimes {
+    day: u8,
+    name: &'static str,
+    parse_time: Duration,
+    solve_times: [Option<Duration>; 2],
+}
+
+impl DayTimes {
+    fn total(&self) -> Duration {
+        self.parse_time + self.solve_times.iter().flatten().sum::<Duration>()
+    }
+}
+
+/// The times of a run of several days, shown as a table with the slowest day
+/// first and the totals at the bottom.
+pub struct Summary {
+    days: Vec<DayTimes>,
+    wall_time: Duration,
+    threads: usize,
+}
+
+impl Summary {
+    /// Sums up `runs`, which took `wall_time` on `threads` threads.
+    pub fn new(runs: &[Run], wall_time: Duration, threads: usize) -> Self {
+        let mut days: BTreeMap<u8, DayTimes> = BTreeMap::new();
+
+        for run in runs {
+            let day = days.entry(run.day).or_insert_with(|| DayTimes {
+                day: run.day,
+                name: crate::solution(run.day).map_or("", |solution| solution.name()),
+                parse_time: run.parse_time,
+                solve_times: [None; 2],
+            });
+
+            let solve_time = &mut day.solve_times[run.part as usize];
+            *solve_time = Some(solve_time.unwrap_or_default() + run.solve_time);
+        }
+
+        let mut days: Vec<_> = days.into_values().collect();
+        days.sort_by_key(|day| std::cmp::Reverse(day.total()));
+
+        Summary {
+            days,
+            wall_time,
+            threads,
+        }
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name_width = self
+            .days
+            .iter()
+            .map(|day| day.name.len())
+            .chain(["Total".len()])
+            .max()
+            .unwrap_or_default();
+        let time =
+            |time: Option<Duration>| time.map_or("-".to_string(), |time| format!("{time:.2?}"));
+
+        writeln!(
+            f,
+            "Day  {:name_width$}  {:>10}  {:>10}  {:>10}  {:>10}",
+            "Name", "Parse", "Part 1", "Part 2", "Total"
+        )?;
+
+        for day in &self.days {
+            writeln!(
+                f,
+                "{:>3}  {:name_width$}  {:>10}  {:>10}  {:>10}  {:>10}",
+                day.day,
+                day.name,
+                time(Some(day.parse_time)),
+                time(day.solve_times[0]),
+                time(day.solve_times[1]),
+                time(Some(day.total()))
+            )?;
+        }
+
+        let sum =
+            |time: fn(&DayTimes) -> Option<Duration>| Some(self.days.iter().filter_map(time).sum());
+
+        writeln!(
+            f,
+            "     {:name_width$}  {:>10}  {:>10}  {:>10}  {:>10}",
+            "Total",
+            time(sum(|day| Some(day.parse_time))),
+            time(sum(|day| day.solve_times[0])),
+            time(sum(|day| day.solve_times[1])),
+            time(sum(|day| Some(day.total())))
+        )?;
+
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+
+        write!(
+            f,
+            "{} day{} in {:.2?} on {} thread{}",
+            self.days.len(),
+            plural(self.days.len()),
+            self.wall_time,
+            self.threads,
+            plural(self.threads)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+    use crate::solution::DEFAULT_VARIANT;
+
+    #[test]
+    fn select_default_variants() {
+        assert_eq!(
+            select(solution(8).unwrap(), None, None),
+            [(Part::One, DEFAULT_VARIANT), (Part::Two, "clever")]
+        );
+        assert_eq!(
+            select(solution(25).unwrap(), None, None),
+            [(Part::One, DEFAULT_VARIANT)]
+        );
+    }
+
+    #[test]
+    fn select_variant() {
+        assert_eq!(
+            select(solution(8).unwrap(), None, Some("naive")),
+            [(Part::Two, "naive")]
+        );
+        assert_eq!(
+            select(solution(8).unwrap(), Some(Part::One), Some("naive")),
+            []
+        );
+    }
+
+    #[test]
+    fn run_both_parts() {
+        let solution = solution(6).unwrap();
+        let selection = select(solution, None, None);
+        let runs = run(solution, "mjqjpqmgbljsphdztnvjfqwrcgsmlb", &selection).unwrap();
+
+        assert_eq!(
+            runs.iter().map(|run| &run.answer).collect::<Vec<_>>(),
+            [&Answer::Integer(7), &Answer::Integer(19)]
+        );
+        assert_eq!(runs[0].parse_time, runs[1].parse_time);
+    }
+
+    #[test]
+    fn serialize_run() {
+        let run = Run {
+            day: 24,
+            part: Part::Two,
+            variant: DEFAULT_VARIANT,
+            answer: Answer::NotFound,
+            parse_time: Duration::from_micros(15),
+            solve_time: Duration::from_millis(2),
+            trace: Trace::default(),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&run).unwrap(),
+            r#"{"day":24,"part":2,"variant":"default","answer":null,"parse_time_ns":15000,"solve_time_ns":2000000}"#
+        );
+    }
+
+    #[test]
+    fn run_malformed_input() {
+        let solution = solution(6).unwrap();
+
+        assert!(run(solution, "mjq1", &select(solution, None, None)).is_err());
+    }
+
+    #[test]
+    fn run_jobs_in_parallel() {
+        let jobs: Vec<Job> = [
+            (6, "mjqjpqmgbljsphdztnvjfqwrcgsmlb"),
+            (4, "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8"),
+            (6, "mjq1"),
+            (1, "1000\n2000\n\n3000"),
+        ]
+        .into_iter()
+        .map(|(day, input)| {
+            let solution = solution(day).unwrap();
+
+            Job {
+                solution,
+                input: input.to_string(),
+                selection: select(solution, None, None),
+            }
+        })
+        .collect();
+
+        let results = run_parallel(&jobs, 3);
+
+        assert_eq!(results.len(), 4);
+        assert!(results[2].is_err());
+
+        let answers: Vec<_> = results
+            .iter()
+            .flatten()
+            .map(|runs| {
+                runs.iter()
+                    .map(|run| (run.day, run.answer.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        assert_eq!(
+            answers,
+            [
+                vec![(6, Answer::Integer(7)), (6, Answer::Integer(19))],
+                vec![(4, Answer::Integer(2)), (4, Answer::Integer(4))],
+                vec![(1, Answer::Integer(3000)), (1, Answer::Integer(6000))],
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_table() {
+        let run = |day, part, parse_time, solve_time| Run {
+            day,
+            part,
+            variant: DEFAULT_VARIANT,
+            answer: Answer::Integer(0),
+            parse_time: Duration::from_micros(parse_time),
+            solve_time: Duration::from_micros(solve_time),
+            trace: Trace::default(),
+        };
+        let runs = [
+            run(1, Part::One, 20, 5),
+            run(1, Part::Two, 20, 7),
+            run(16, Part::One, 100, 150_000),
+            run(16, Part::Two, 100, 1_200_000),
+            run(25, Part::One, 10, 30),
+        ];
+
+        assert_eq!(
+            Summary::new(&runs, Duration::from_millis(1_300), 4).to_string(),
+            "\
+Day  Name                        Parse      Part 1      Part 2       Total
+ 16  Proboscidea Volcanium    100.00µs    150.00ms       1.20s       1.35s
+ 25  Full of Hot Air           10.00µs     30.00µs           -     40.00µs
+  1  Calorie Counting          20.00µs      5.00µs      7.00µs     32.00µs
+     Total                    130.00µs    150.04ms       1.20s       1.35s
+3 days in 1.30s on 4 threads"
+        );
+    }
+}
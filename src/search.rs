@@ -0,0 +1,487 @@
+//! Searches over graphs given by a successor function, and all-pairs shortest
+//! paths over graphs with numbered nodes. Every search remembers which node
+//! each node was reached from, so the paths can be rebuilt.
+//!
+//! All searches start from any number of nodes at once and stop as soon as
+//! they reach a node for which `is_goal` holds; pass `|_| false` to explore
+//! everything reachable.
+
+use num_traits::Zero;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+struct Visit<N, C> {
+    node: N,
+    parent: Option<usize>,
+    cost: C,
+    /// Whether `cost` is known to be the lowest one.
+    settled: bool,
+}
+
+/// The nodes reached by a search, with the cost of the cheapest path to each
+/// of them and the node that path comes from.
+pub struct SearchTree<N, C> {
+    visits: Vec<Visit<N, C>>,
+    indices: HashMap<N, usize>,
+    goal: Option<usize>,
+}
+
+impl<N, C> SearchTree<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord,
+{
+    fn new() -> Self {
+        SearchTree {
+            visits: Vec::new(),
+            indices: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    /// Records that `node` can be reached at `cost`, unless it is already known
+    /// to be reachable as cheaply. Returns the index of the node if it was recorded.
+    fn relax(&mut self, node: N, parent: Option<usize>, cost: C) -> Option<usize> {
+        match self.indices.get(&node) {
+            Some(&index) => {
+                let visit = &mut self.visits[index];
+
+                if visit.settled || visit.cost <= cost {
+                    return None;
+                }
+
+                visit.parent = parent;
+                visit.cost = cost;
+
+                Some(index)
+            }
+            None => {
+                let index = self.visits.len();
+
+                self.indices.insert(node.clone(), index);
+                self.visits.push(Visit {
+                    node,
+                    parent,
+                    cost,
+                    settled: false,
+                });
+
+                Some(index)
+            }
+        }
+    }
+
+    fn settled(&self, node: &N) -> Option<usize> {
+        self.indices
+            .get(node)
+            .copied()
+            .filter(|&index| self.visits[index].settled)
+    }
+
+    fn path_to(&self, mut index: usize) -> Vec<N> {
+        let mut path = vec![self.visits[index].node.clone()];
+
+        while let Some(parent) = self.visits[index].parent {
+            path.push(self.visits[parent].node.clone());
+            index = parent;
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Returns the cost of the cheapest path from a start to `node`, or `None`
+    /// if the search did not get to the bottom of it.
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.settled(node).map(|index| self.visits[index].cost)
+    }
+
+    /// Returns the cheapest path from a start to `node`, both included.
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        self.settled(node).map(|index| self.path_to(index))
+    }
+
+    /// Returns the goal the search stopped at, if it found one.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.map(|index| &self.visits[index].node)
+    }
+
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goal.map(|index| self.visits[index].cost)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.goal.map(|index| self.path_to(index))
+    }
+
+    /// Returns the nodes whose cheapest path is known, in the order they were found.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.visits
+            .iter()
+            .filter(|visit| visit.settled)
+            .map(|visit| &visit.node)
+    }
+}
+
+/// Breadth-first search, where every step costs 1.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> SearchTree<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut tree = SearchTree::new();
+    let mut queue = VecDeque::new();
+
+    // Nodes are settled as soon as they are found, as the queue holds them in
+    // order of their distance.
+    for start in starts {
+        if let Some(index) = tree.relax(start, None, 0) {
+            tree.visits[index].settled = true;
+            queue.push_back(index);
+        }
+    }
+
+    while let Some(index) = queue.pop_front() {
+        let node = tree.visits[index].node.clone();
+
+        if is_goal(&node) {
+            tree.goal = Some(index);
+            break;
+        }
+
+        let cost = tree.visits[index].cost + 1;
+
+        for successor in successors(&node) {
+            if let Some(successor_index) = tree.relax(successor, Some(index), cost) {
+                tree.visits[successor_index].settled = true;
+                queue.push_back(successor_index);
+            }
+        }
+    }
+
+    tree
+}
+
+/// Dijkstra's algorithm, for successors given with the cost of the step to them.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> SearchTree<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, successors, |_| C::zero(), is_goal)
+}
+
+/// A* search, guided by a `heuristic` that must never overestimate the cost
+/// of reaching a goal, nor drop by more than the cost of a step.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> SearchTree<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut tree = SearchTree::new();
+    let mut heap = BinaryHeap::new();
+
+    for start in starts {
+        let estimate = heuristic(&start);
+
+        if let Some(index) = tree.relax(start, None, C::zero()) {
+            heap.push(Reverse((estimate, index)));
+        }
+    }
+
+    // A node may be queued several times as cheaper paths to it are found;
+    // only the first time it is popped counts.
+    while let Some(Reverse((_, index))) = heap.pop() {
+        if tree.visits[index].settled {
+            continue;
+        }
+
+        tree.visits[index].settled = true;
+
+        let node = tree.visits[index].node.clone();
+        let cost = tree.visits[index].cost;
+
+        if is_goal(&node) {
+            tree.goal = Some(index);
+            break;
+        }
+
+        for (successor, step_cost) in successors(&node) {
+            let estimate = heuristic(&successor);
+
+            if let Some(successor_index) = tree.relax(successor, Some(index), cost + step_cost) {
+                heap.push(Reverse((cost + step_cost + estimate, successor_index)));
+            }
+        }
+    }
+
+    tree
+}
+
+/// The shortest paths between all pairs of nodes of a graph whose nodes are
+/// numbered from 0.
+pub struct AllPairs<C> {
+    distances: Vec<Vec<Option<C>>>,
+    /// The node before the last one on the shortest path between each pair.
+    predecessors: Vec<Vec<Option<usize>>>,
+}
+
+impl<C: Copy> AllPairs<C> {
+    /// Returns the length of the shortest path from `from` to `to`, or `None`
+    /// if there is no such path.
+    pub fn distance(&self, from: usize, to: usize) -> Option<C> {
+        self.distances[from][to]
+    }
+
+    /// Returns the nodes on the shortest path from `from` to `to`, both included.
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        self.distances[from][to]?;
+
+        let mut path = vec![to];
+        let mut node = to;
+
+        while node != from {
+            node = self.predecessors[from][node]?;
+            path.push(node);
+        }
+
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// The Floyd–Warshall algorithm, for `node_count` nodes connected by directed
+/// `edges` given as `(from, to, length)`.
+pub fn floyd_warshall<C>(
+    node_count: usize,
+    edges: impl IntoIterator<Item = (usize, usize, C)>,
+) -> AllPairs<C>
+where
+    C: Copy + Ord + Add<Output = C> + Zero,
+{
+    let mut distances = vec![vec![None; node_count]; node_count];
+    let mut predecessors = vec![vec![None; node_count]; node_count];
+
+    for (node, distances) in distances.iter_mut().enumerate() {
+        distances[node] = Some(C::zero());
+    }
+
+    for (from, to, length) in edges {
+        if distances[from][to].is_none_or(|distance| length < distance) {
+            distances[from][to] = Some(length);
+            predecessors[from][to] = Some(from);
+        }
+    }
+
+    for k in 0..node_count {
+        for i in 0..node_count {
+            let Some(first_leg) = distances[i][k] else {
+                continue;
+            };
+
+            for j in 0..node_count {
+                let Some(second_leg) = distances[k][j] else {
+                    continue;
+                };
+                let new_distance = first_leg + second_leg;
+
+                if distances[i][j].is_none_or(|distance| new_distance < distance) {
+                    distances[i][j] = Some(new_distance);
+                    predecessors[i][j] = predecessors[k][j];
+                }
+            }
+        }
+    }
+
+    AllPairs {
+        distances,
+        predecessors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point2;
+    use proptest::prelude::*;
+
+    static MAZE: &str = r"#########
+#S..#...#
+##.##.#.#
+#...#.#E#
+#.#...#.#
+#########";
+
+    fn open_tiles() -> Vec<Point2<i32>> {
+        MAZE.lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|&(_, tile)| tile != '#')
+                    .map(move |(x, _)| Point2::new(x as i32, y as i32))
+            })
+            .collect()
+    }
+
+    fn tile(tile: char) -> Point2<i32> {
+        let (y, line) = MAZE
+            .lines()
+            .enumerate()
+            .find(|(_, line)| line.contains(tile))
+            .unwrap();
+
+        Point2::new(line.find(tile).unwrap() as i32, y as i32)
+    }
+
+    fn steps(point: &Point2<i32>) -> Vec<Point2<i32>> {
+        let open_tiles = open_tiles();
+
+        point
+            .neighbors4()
+            .filter(|neighbor| open_tiles.contains(neighbor))
+            .collect()
+    }
+
+    #[test]
+    fn bfs_path() {
+        let goal = tile('E');
+        let tree = bfs([tile('S')], steps, |&point| point == goal);
+
+        assert_eq!(tree.goal(), Some(&goal));
+        assert_eq!(tree.goal_cost(), Some(14));
+
+        let path = tree.goal_path().unwrap();
+
+        assert_eq!(path.len(), 15);
+        assert_eq!((path[0], path[14]), (tile('S'), goal));
+        assert!(path
+            .windows(2)
+            .all(|step| step[0].manhattan_distance(step[1]) == 1));
+    }
+
+    #[test]
+    fn multi_source_bfs() {
+        let starts = [tile('S'), Point2::new(7, 1)];
+        let tree = bfs(starts, steps, |_| false);
+
+        assert_eq!(tree.goal(), None);
+        assert_eq!(tree.nodes().count(), open_tiles().len());
+        assert_eq!(tree.cost(&tile('E')), Some(2));
+        assert_eq!(
+            tree.path(&tile('E')),
+            Some(vec![Point2::new(7, 1), Point2::new(7, 2), tile('E')])
+        );
+    }
+
+    #[test]
+    fn weighted_searches() {
+        // Stepping down costs 5.
+        let weighted_steps = |point: &Point2<i32>| {
+            let point = *point;
+
+            steps(&point).into_iter().map(move |neighbor| {
+                let cost = if neighbor.y > point.y { 5 } else { 1 };
+                (neighbor, cost)
+            })
+        };
+        let goal = tile('E');
+
+        let dijkstra = dijkstra([tile('S')], weighted_steps, |&point| point == goal);
+        let astar = astar(
+            [tile('S')],
+            weighted_steps,
+            |point| point.manhattan_distance(goal),
+            |&point| point == goal,
+        );
+
+        assert_eq!(dijkstra.goal_cost(), Some(34));
+        assert_eq!(astar.goal_cost(), Some(34));
+        assert!(astar.nodes().count() <= dijkstra.nodes().count());
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let tree = bfs([tile('S')], steps, |&point| point == Point2::new(0, 0));
+
+        assert_eq!(tree.goal_path(), None);
+        assert_eq!(tree.path(&Point2::new(0, 0)), None);
+    }
+
+    #[test]
+    fn all_pairs() {
+        let all_pairs = floyd_warshall(4, [(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1)]);
+
+        assert_eq!(all_pairs.distance(0, 3), Some(4));
+        assert_eq!(all_pairs.path(0, 3), Some(vec![0, 2, 1, 3]));
+        assert_eq!(all_pairs.path(2, 2), Some(vec![2]));
+        assert_eq!(all_pairs.distance(3, 0), None);
+        assert_eq!(all_pairs.path(3, 0), None);
+    }
+
+    fn graph() -> impl Strategy<Value = (usize, Vec<(usize, usize, u32)>)> {
+        (1..12usize).prop_flat_map(|node_count| {
+            (
+                Just(node_count),
+                prop::collection::vec((0..node_count, 0..node_count, 0..20u32), 0..40),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn dijkstra_matches_floyd_warshall((node_count, edges) in graph()) {
+            let all_pairs = floyd_warshall(node_count, edges.iter().copied());
+
+            for from in 0..node_count {
+                let tree = dijkstra(
+                    [from],
+                    |&node| {
+                        edges
+                            .iter()
+                            .filter(move |edge| edge.0 == node)
+                            .map(|&(_, to, length)| (to, length))
+                    },
+                    |_| false,
+                );
+
+                for to in 0..node_count {
+                    prop_assert_eq!(tree.cost(&to), all_pairs.distance(from, to));
+
+                    if let Some(path) = all_pairs.path(from, to) {
+                        let length: u32 = path
+                            .windows(2)
+                            .map(|step| {
+                                edges
+                                    .iter()
+                                    .filter(|edge| (edge.0, edge.1) == (step[0], step[1]))
+                                    .map(|edge| edge.2)
+                                    .min()
+                                    .unwrap()
+                            })
+                            .sum();
+
+                        prop_assert_eq!(Some(length), all_pairs.distance(from, to));
+                    }
+                }
+            }
+        }
+    }
+}
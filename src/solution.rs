@@ -0,0 +1,308 @@
+use crate::arithmetic::Overflow;
+use crate::error::ParseError;
+use crate::visualize::Frames;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub const DEFAULT_VARIANT: &str = "default";
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(match self {
+            Part::One => 1,
+            Part::Two => 2,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Part {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            part => Err(D::Error::custom(format!("expected 1 or 2, found {part}"))),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = ();
+
+    fn from_str(part: &str) -> Result<Self, Self::Err> {
+        match part {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Answer {
+    Integer(i64),
+    Text(String),
+    /// The solver finished without finding an answer, e.g. no marker in day 6.
+    NotFound,
+    /// The answer did not fit in the integers of the `checked` arithmetic.
+    Overflow,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(integer) => write!(f, "{integer}"),
+            Answer::Text(text) => write!(f, "{text}"),
+            Answer::NotFound => write!(f, "not found"),
+            Answer::Overflow => write!(f, "overflow"),
+        }
+    }
+}
+
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Answer::Integer(integer) => serializer.serialize_i64(*integer),
+            Answer::Text(text) => serializer.serialize_str(text),
+            Answer::NotFound | Answer::Overflow => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Integers beyond the range of `i64` are answered as text, like big integers.
+macro_rules! impl_from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Answer {
+                fn from(integer: $integer) -> Self {
+                    i64::try_from(integer)
+                        .map_or_else(|_| Answer::Text(integer.to_string()), Answer::Integer)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i32, i64, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(answer: Option<T>) -> Self {
+        answer.map_or(Answer::NotFound, Into::into)
+    }
+}
+
+impl<T: Into<Answer>> From<Result<T, Overflow>> for Answer {
+    fn from(answer: Result<T, Overflow>) -> Self {
+        answer.map_or(Answer::Overflow, Into::into)
+    }
+}
+
+/// Integers beyond the range of `i64` are answered as text.
+#[cfg(feature = "bigint")]
+impl From<num_bigint::BigInt> for Answer {
+    fn from(integer: num_bigint::BigInt) -> Self {
+        i64::try_from(&integer).map_or_else(|_| Answer::Text(integer.to_string()), Answer::Integer)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl From<num_bigint::BigUint> for Answer {
+    fn from(natural: num_bigint::BigUint) -> Self {
+        num_bigint::BigInt::from(natural).into()
+    }
+}
+
+/// A day of the calendar: how to parse its input and how to solve both parts.
+pub trait Solution: Sync {
+    type Input;
+
+    fn day(&self) -> u8;
+
+    /// The title of the puzzle.
+    fn name(&self) -> &'static str;
+
+    /// The implementations available for `part`, the default one first.
+    fn variants(&self, _part: Part) -> &'static [&'static str] {
+        &[DEFAULT_VARIANT]
+    }
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(&self, input: &Self::Input) -> Answer;
+
+    fn part2(&self, input: &Self::Input) -> Answer;
+
+    /// Solves `part` with the given variant, or returns `None` if there is no such variant.
+    fn solve(&self, input: &Self::Input, part: Part, variant: &str) -> Option<Answer> {
+        if !self.variants(part).contains(&variant) {
+            return None;
+        }
+
+        Some(match part {
+            Part::One => self.part1(input),
+            Part::Two => self.part2(input),
+        })
+    }
+
+    /// Pictures of the simulation solving `part`, for the days that have one.
+    fn frames<'a>(&self, _input: &'a Self::Input, _part: Part) -> Option<Frames<'a>> {
+        None
+    }
+}
+
+/// An object-safe view of a [`Solution`], used by the registry.
+pub trait DynSolution: Sync {
+    fn day(&self) -> u8;
+
+    fn name(&self) -> &'static str;
+
+    fn variants(&self, part: Part) -> &'static [&'static str];
+
+    fn parse<'a>(&'a self, input: &str) -> Result<Box<dyn ParsedInput + 'a>, ParseError>;
+}
+
+/// The input of a day after parsing, ready to be solved any number of times.
+pub trait ParsedInput {
+    fn solve(&self, part: Part, variant: &str) -> Option<Answer>;
+
+    fn frames(&self, part: Part) -> Option<Frames<'_>>;
+}
+
+struct Parsed<'a, S: Solution> {
+    solution: &'a S,
+    input: S::Input,
+}
+
+impl<S: Solution> ParsedInput for Parsed<'_, S> {
+    fn solve(&self, part: Part, variant: &str) -> Option<Answer> {
+        self.solution.solve(&self.input, part, variant)
+    }
+
+    fn frames(&self, part: Part) -> Option<Frames<'_>> {
+        self.solution.frames(&self.input, part)
+    }
+}
+
+impl<S: Solution> DynSolution for S {
+    fn day(&self) -> u8 {
+        Solution::day(self)
+    }
+
+    fn name(&self) -> &'static str {
+        Solution::name(self)
+    }
+
+    fn variants(&self, part: Part) -> &'static [&'static str] {
+        Solution::variants(self, part)
+    }
+
+    fn parse<'a>(&'a self, input: &str) -> Result<Box<dyn ParsedInput + 'a>, ParseError> {
+        Ok(Box::new(Parsed {
+            solution: self,
+            input: Solution::parse(self, input)?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{solution, SOLUTIONS};
+
+    #[test]
+    fn registry_covers_every_day() {
+        for (index, solution) in SOLUTIONS.iter().enumerate() {
+            assert_eq!(solution.day() as usize, index + 1);
+            assert!(!solution.name().is_empty());
+            assert!(!solution.variants(Part::One).is_empty());
+        }
+
+        assert!(solution(0).is_none());
+        assert!(solution(26).is_none());
+    }
+
+    #[test]
+    fn solve_by_day_number() {
+        let input = solution(1).unwrap().parse("1000\n2000\n\n4000").unwrap();
+
+        assert_eq!(
+            input.solve(Part::One, DEFAULT_VARIANT),
+            Some(Answer::Integer(4_000))
+        );
+        assert_eq!(input.solve(Part::One, "naive"), None);
+    }
+
+    #[test]
+    fn variants() {
+        let day8 = solution(8).unwrap();
+        let input = day8.parse("30373\n25512\n65332\n33549\n35390").unwrap();
+
+        assert_eq!(day8.variants(Part::Two), ["clever", "naive"]);
+
+        for variant in day8.variants(Part::Two) {
+            assert_eq!(input.solve(Part::Two, variant), Some(Answer::Integer(8)));
+        }
+    }
+
+    #[test]
+    fn answer_from_large_integers() {
+        assert_eq!(Answer::from(i64::MAX as u64), Answer::Integer(i64::MAX));
+        assert_eq!(
+            Answer::from(u64::MAX),
+            Answer::Text("18446744073709551615".to_string())
+        );
+        assert_eq!(
+            Answer::from(usize::MAX),
+            Answer::Text(usize::MAX.to_string())
+        );
+        assert_eq!(Answer::from(-3_i32), Answer::Integer(-3));
+    }
+
+    #[test]
+    fn serialize_answer() {
+        assert_eq!(serde_json::to_string(&Answer::Integer(-3)).unwrap(), "-3");
+        assert_eq!(
+            serde_json::to_string(&Answer::Text("CMZ\nMCD".to_string())).unwrap(),
+            r#""CMZ\nMCD""#
+        );
+        assert_eq!(serde_json::to_string(&Answer::NotFound).unwrap(), "null");
+    }
+
+    #[test]
+    fn missing_part() {
+        let day25 = solution(25).unwrap();
+
+        assert!(day25.variants(Part::Two).is_empty());
+        assert_eq!(
+            day25.parse("1=").unwrap().solve(Part::Two, DEFAULT_VARIANT),
+            None
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! An interactive terminal stepper for the simulation days, to pause, step
+//! forward and back, jump to a tick and inspect the cells of the frames of a
+//! [`Visualize`](crate::visualize::Visualize) simulation.
+
+use crate::geometry::Point2;
+use crate::visualize::{Cell, Frame, Frames};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// How many of the last visited frames are kept for stepping back without
+/// replaying the simulation.
+pub const HISTORY: usize = 1_000;
+
+const HELP: &str = "←/→ step  g/G jump  h/j/k/l move  space play  q quit  (prefix a count: 25→)";
+
+/// The frames of a simulation, visited in any order. The last frames visited
+/// are kept, and earlier ones are found again by replaying the simulation from
+/// its start.
+pub struct Timeline<'a> {
+    replay: Box<dyn Fn() -> Frames<'a> + 'a>,
+    frames: Frames<'a>,
+    kept: VecDeque<Frame>,
+    first_kept: usize,
+    capacity: usize,
+    tick: usize,
+    last_tick: Option<usize>,
+}
+
+impl<'a> Timeline<'a> {
+    /// Returns a timeline at the first frame of `replay`, keeping `capacity`
+    /// frames, or `None` if the simulation has no frames.
+    pub fn new(replay: impl Fn() -> Frames<'a> + 'a, capacity: usize) -> Option<Self> {
+        let mut frames = replay();
+        let first_frame = frames.next()?;
+
+        Some(Timeline {
+            replay: Box::new(replay),
+            frames,
+            kept: VecDeque::from([first_frame]),
+            first_kept: 0,
+            capacity: capacity.max(1),
+            tick: 0,
+            last_tick: None,
+        })
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Returns the tick of the last frame, once the simulation has been run to its end.
+    pub fn last_tick(&self) -> Option<usize> {
+        self.last_tick
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.kept[self.tick - self.first_kept]
+    }
+
+    /// Moves to `tick`, or to the last tick if the simulation ends before it,
+    /// and returns the tick moved to.
+    pub fn go_to(&mut self, tick: usize) -> usize {
+        let tick = self.last_tick.map_or(tick, |last_tick| tick.min(last_tick));
+
+        if tick < self.first_kept {
+            // Keeps the frames before `tick` too, to step back from it.
+            self.first_kept = (tick + 1).saturating_sub(self.capacity);
+            self.frames = (self.replay)();
+            self.kept.clear();
+
+            if self.first_kept > 0 {
+                self.frames.nth(self.first_kept - 1);
+            }
+        }
+
+        while self.first_kept + self.kept.len() <= tick {
+            let Some(frame) = self.frames.next() else {
+                self.last_tick = Some(self.first_kept + self.kept.len() - 1);
+                break;
+            };
+
+            if self.kept.len() == self.capacity {
+                self.kept.pop_front();
+                self.first_kept += 1;
+            }
+
+            self.kept.push_back(frame);
+        }
+
+        self.tick = tick.min(self.first_kept + self.kept.len() - 1);
+        self.tick
+    }
+
+    /// Moves `ticks` forward, or back if negative.
+    pub fn step(&mut self, ticks: isize) -> usize {
+        self.go_to(self.tick.saturating_add_signed(ticks))
+    }
+}
+
+/// Whether to keep stepping after a key.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+/// The state of the stepper: where it is in the timeline, which cell is
+/// inspected and what has been typed.
+pub struct Stepper<'a> {
+    title: String,
+    timeline: Timeline<'a>,
+    cursor: Point2<usize>,
+    count: Option<usize>,
+    playing: bool,
+}
+
+impl<'a> Stepper<'a> {
+    pub fn new(title: impl Into<String>, timeline: Timeline<'a>) -> Self {
+        Stepper {
+            title: title.into(),
+            timeline,
+            cursor: Point2::new(0, 0),
+            count: None,
+            playing: false,
+        }
+    }
+
+    pub fn timeline(&self) -> &Timeline<'a> {
+        &self.timeline
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Returns the simulation coordinates of the inspected cell, and the cell.
+    pub fn inspected(&self) -> (Point2<i32>, Option<Cell>) {
+        let frame = self.timeline.frame();
+        let point = frame.origin() + Point2::new(self.cursor.x as i32, self.cursor.y as i32);
+
+        (point, frame.get(point))
+    }
+
+    /// Acts on a key press. Digits make up the count of the next command.
+    pub fn handle(&mut self, key: KeyEvent) -> Flow {
+        if let KeyCode::Char(digit @ '0'..='9') = key.code {
+            let digit = digit.to_digit(10).unwrap() as usize;
+            self.count = Some(
+                self.count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit),
+            );
+            return Flow::Continue;
+        }
+
+        let count = self.count.take();
+        let times = count.unwrap_or(1);
+        let ticks = isize::try_from(times).unwrap_or(isize::MAX);
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Flow::Quit
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Flow::Quit,
+            KeyCode::Char(' ') => self.playing = !self.playing,
+            KeyCode::Right | KeyCode::Char('n') => {
+                self.timeline.step(ticks);
+            }
+            KeyCode::Left | KeyCode::Char('p') => {
+                self.timeline.step(-ticks);
+            }
+            KeyCode::Char('g') => {
+                self.timeline.go_to(count.unwrap_or(0));
+            }
+            KeyCode::Char('G') => {
+                self.timeline.go_to(usize::MAX);
+            }
+            KeyCode::Char('h') => self.cursor.x = self.cursor.x.saturating_sub(times),
+            KeyCode::Char('k') => self.cursor.y = self.cursor.y.saturating_sub(times),
+            KeyCode::Char('l') => self.cursor.x = self.cursor.x.saturating_add(times),
+            KeyCode::Char('j') => self.cursor.y = self.cursor.y.saturating_add(times),
+            _ => {}
+        }
+
+        self.clamp_cursor();
+        Flow::Continue
+    }
+
+    /// Moves one tick forward while playing, and pauses at the end.
+    pub fn play(&mut self) {
+        let tick = self.timeline.tick();
+
+        if self.timeline.step(1) == tick {
+            self.playing = false;
+        }
+    }
+
+    fn clamp_cursor(&mut self) {
+        let frame = self.timeline.frame();
+        self.cursor.x = self.cursor.x.min(frame.width().saturating_sub(1));
+        self.cursor.y = self.cursor.y.min(frame.height().saturating_sub(1));
+    }
+
+    /// Returns the line under the frame: the tick, the inspected cell and the
+    /// count typed so far.
+    pub fn status(&self) -> String {
+        let tick = match self.timeline.last_tick() {
+            Some(last_tick) => format!("tick {} of {last_tick}", self.timeline.tick()),
+            None => format!("tick {}", self.timeline.tick()),
+        };
+        let state = if self.playing { "playing" } else { "paused" };
+        let (point, cell) = self.inspected();
+        let cell = cell.map_or_else(String::new, |cell| {
+            let color = cell.color;
+            format!(
+                " {:?} #{:02x}{:02x}{:02x}",
+                cell.glyph, color.0, color.1, color.2
+            )
+        });
+        let count = self
+            .count
+            .map_or_else(String::new, |count| format!(" | {count}_"));
+
+        format!(
+            "{} | {tick} | {state} | ({}, {}){cell}{count}",
+            self.title, point.x, point.y
+        )
+    }
+
+    /// Draws the part of the frame around the inspected cell that fits in
+    /// `columns` by `rows`, with the status and help lines below it.
+    pub fn draw(&self, columns: u16, rows: u16, out: &mut impl Write) -> io::Result<()> {
+        let frame = self.timeline.frame();
+        let columns = usize::from(columns);
+        let rows = usize::from(rows).saturating_sub(2);
+
+        // Scrolls so that the inspected cell stays in the middle, where possible.
+        let scroll = |cursor: usize, visible: usize, size: usize| {
+            cursor
+                .saturating_sub(visible / 2)
+                .min(size.saturating_sub(visible))
+        };
+        let left = scroll(self.cursor.x, columns, frame.width());
+        let top = scroll(self.cursor.y, rows, frame.height());
+
+        queue!(out, MoveTo(0, 0))?;
+
+        for (y, row) in frame.rows().enumerate().skip(top).take(rows) {
+            for (x, cell) in row.iter().enumerate().skip(left).take(columns) {
+                let color = cell.color;
+                queue!(
+                    out,
+                    SetForegroundColor(Color::Rgb {
+                        r: color.0,
+                        g: color.1,
+                        b: color.2,
+                    })
+                )?;
+
+                if Point2::new(x, y) == self.cursor {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Reverse),
+                        Print(cell.glyph),
+                        SetAttribute(Attribute::NoReverse)
+                    )?;
+                } else {
+                    queue!(out, Print(cell.glyph))?;
+                }
+            }
+
+            queue!(
+                out,
+                ResetColor,
+                Clear(ClearType::UntilNewLine),
+                Print("\r\n")
+            )?;
+        }
+
+        let line = |text: &str| text.chars().take(columns).collect::<String>();
+        queue!(
+            out,
+            Clear(ClearType::FromCursorDown),
+            Print(line(&self.status())),
+            Print("\r\n"),
+            Print(line(HELP))
+        )
+    }
+}
+
+/// Runs the stepper in the terminal until it is quit, moving one tick every
+/// `delay` while playing.
+pub fn run(stepper: &mut Stepper, delay: Duration, out: &mut impl Write) -> io::Result<()> {
+    let screen = Screen::enter(out)?;
+
+    event_loop(stepper, delay, screen.out)
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped,
+/// even after an error or a panic.
+struct Screen<'a, W: Write> {
+    out: &'a mut W,
+}
+
+impl<'a, W: Write> Screen<'a, W> {
+    fn enter(out: &'a mut W) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let screen = Screen { out };
+        execute!(screen.out, EnterAlternateScreen, Hide)?;
+
+        Ok(screen)
+    }
+}
+
+impl<W: Write> Drop for Screen<'_, W> {
+    fn drop(&mut self) {
+        // Nothing more can be done about a terminal that fails to be restored.
+        let _ = execute!(self.out, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn event_loop(stepper: &mut Stepper, delay: Duration, out: &mut impl Write) -> io::Result<()> {
+    loop {
+        let (columns, rows) = terminal::size()?;
+        stepper.draw(columns, rows, out)?;
+        out.flush()?;
+
+        if stepper.is_playing() && !event::poll(delay)? {
+            stepper.play();
+            continue;
+        }
+
+        // Other events, e.g. a resized terminal, only redraw the screen.
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && stepper.handle(key) == Flow::Quit {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::BoundingBox;
+    use crate::visualize::{BACKGROUND, GOLD};
+    use std::cell::Cell as Counter;
+
+    const TICKS: i32 = 10;
+
+    /// A dot moving right by one tile every tick.
+    fn frames() -> Frames<'static> {
+        let bounds = BoundingBox {
+            min: Point2::new(0, -1),
+            max: Point2::new(TICKS - 1, 0),
+        };
+
+        Box::new((0..TICKS).map(move |tick| {
+            let mut frame = Frame::new(bounds, Cell::new('.', BACKGROUND));
+            frame.draw(Point2::new(tick, 0), Cell::new('#', GOLD));
+            frame
+        }))
+    }
+
+    fn dot(timeline: &Timeline) -> usize {
+        timeline.frame().to_string().find('#').unwrap() - TICKS as usize - 1
+    }
+
+    fn press(stepper: &mut Stepper, keys: &str) -> Flow {
+        keys.chars()
+            .map(|key| {
+                let code = match key {
+                    '>' => KeyCode::Right,
+                    '<' => KeyCode::Left,
+                    key => KeyCode::Char(key),
+                };
+                stepper.handle(KeyEvent::from(code))
+            })
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn rewinds_by_replaying() {
+        let replays = Counter::new(0);
+        let mut timeline = Timeline::new(
+            || {
+                replays.set(replays.get() + 1);
+                frames()
+            },
+            3,
+        )
+        .unwrap();
+
+        assert_eq!(timeline.go_to(6), 6);
+        assert_eq!(timeline.step(-2), 4);
+        assert_eq!((dot(&timeline), replays.get()), (4, 1));
+
+        assert_eq!(timeline.step(-3), 1);
+        assert_eq!((dot(&timeline), replays.get()), (1, 2));
+        assert_eq!(timeline.step(-1), 0);
+        assert_eq!(replays.get(), 2);
+
+        assert_eq!(timeline.last_tick(), None);
+        assert_eq!(timeline.go_to(100), 9);
+        assert_eq!(timeline.last_tick(), Some(9));
+        assert_eq!(timeline.step(1), 9);
+        assert_eq!(dot(&timeline), 9);
+    }
+
+    #[test]
+    fn keys() {
+        let mut stepper = Stepper::new("Day 0 part 1", Timeline::new(frames, HISTORY).unwrap());
+
+        press(&mut stepper, "12>");
+        assert_eq!(stepper.timeline().tick(), 9);
+        press(&mut stepper, "3<");
+        assert_eq!(stepper.timeline().tick(), 6);
+        press(&mut stepper, "g");
+        assert_eq!(stepper.timeline().tick(), 0);
+        press(&mut stepper, "4g");
+        assert_eq!(stepper.timeline().tick(), 4);
+
+        press(&mut stepper, "4lj");
+        assert_eq!(
+            stepper.inspected(),
+            (Point2::new(4, 0), Some(Cell::new('#', GOLD)))
+        );
+        assert_eq!(
+            stepper.status(),
+            "Day 0 part 1 | tick 4 of 9 | paused | (4, 0) '#' #ffff66"
+        );
+
+        press(&mut stepper, "20j2");
+        assert_eq!(stepper.inspected().0, Point2::new(4, 0));
+        assert!(stepper.status().ends_with(" | 2_"));
+
+        press(&mut stepper, " ");
+        assert!(stepper.is_playing());
+        (0..10).for_each(|_| stepper.play());
+        assert!(!stepper.is_playing());
+        assert_eq!(stepper.timeline().tick(), 9);
+
+        assert_eq!(press(&mut stepper, "q"), Flow::Quit);
+    }
+
+    /// Leaves out the escape sequences of colours, attributes and cursor moves.
+    fn text(out: Vec<u8>) -> String {
+        let out = String::from_utf8(out).unwrap();
+        let mut chars = out.chars();
+        let mut text = String::new();
+
+        while let Some(char) = chars.next() {
+            if char == '\x1b' {
+                chars.next();
+                chars.find(|char| ('@'..='~').contains(char));
+            } else {
+                text.push(char);
+            }
+        }
+
+        text
+    }
+
+    #[test]
+    fn draws_around_the_cursor() {
+        let mut stepper = Stepper::new("Day 0 part 1", Timeline::new(frames, HISTORY).unwrap());
+        press(&mut stepper, "9l7>");
+
+        let mut out = Vec::new();
+        stepper.draw(4, 4, &mut out).unwrap();
+
+        // Only the four rightmost columns fit, with the cursor in the last one.
+        assert_eq!(text(out), "....\r\n.#..\r\nDay \r\n←/→ ");
+    }
+}
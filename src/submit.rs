@@ -0,0 +1,566 @@
+//! Submitting answers to adventofcode.com, with a local history of every
+//! attempt so that answers already known to be wrong are not sent again.
+
+use crate::inputs::{self, BASE_URL, YEAR};
+use crate::solution::Part;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// What the server said about a submitted answer.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint which way.
+    Wrong,
+    /// The answer was not checked, as the previous one was submitted too recently.
+    Wait {
+        seconds: u64,
+    },
+    /// The part is solved already, or cannot be solved yet.
+    WrongLevel,
+    /// A response this client does not know, as text.
+    Unrecognized(String),
+}
+
+impl Verdict {
+    /// Reads the verdict from the HTML page the server answers with.
+    pub fn parse(page: &str) -> Self {
+        let text = article_text(page);
+
+        if text.contains("That's the right answer") {
+            Verdict::Correct
+        } else if text.contains("That's not the right answer") {
+            if text.contains("too high") {
+                Verdict::TooHigh
+            } else if text.contains("too low") {
+                Verdict::TooLow
+            } else {
+                Verdict::Wrong
+            }
+        } else if text.contains("You gave an answer too recently") {
+            Verdict::Wait {
+                seconds: time_left(&text).unwrap_or(60),
+            }
+        } else if text.contains("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else {
+            Verdict::Unrecognized(text)
+        }
+    }
+
+    fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong)
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::Wait { seconds } => write!(f, "not checked, wait {seconds} seconds"),
+            Verdict::WrongLevel => write!(f, "not checked, the part is solved or locked"),
+            Verdict::Unrecognized(text) => write!(f, "not recognized: {text}"),
+        }
+    }
+}
+
+/// Returns the text of the `<article>` of a page, which holds the message,
+/// without tags and with runs of whitespace made single spaces.
+fn article_text(page: &str) -> String {
+    let article = match (page.find("<article"), page.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &page[start..end],
+        _ => page,
+    };
+
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for character in article.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(character),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reads the time to wait from e.g. "You have 4m 59s left to wait."
+fn time_left(text: &str) -> Option<u64> {
+    let start = text.find("You have ")? + "You have ".len();
+    let end = start + text[start..].find(" left to wait")?;
+
+    text[start..end]
+        .split_whitespace()
+        .map(|amount| {
+            let unit = amount.chars().last()?;
+            let number: u64 = amount[..amount.len() - unit.len_utf8()].parse().ok()?;
+
+            match unit {
+                'h' => Some(number * 3600),
+                'm' => Some(number * 60),
+                's' => Some(number),
+                _ => None,
+            }
+        })
+        .sum()
+}
+
+/// One submitted answer.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Attempt {
+    pub day: u8,
+    pub part: Part,
+    pub answer: String,
+    pub verdict: Verdict,
+    /// Seconds since the Unix epoch.
+    pub submitted_at: u64,
+}
+
+/// Why an answer is not worth submitting.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Refusal {
+    /// The part is solved, with this answer.
+    Solved(String),
+    /// The same answer was submitted before and was wrong.
+    KnownWrong(Verdict),
+    /// An answer on the other side of this one was too high or too low.
+    OutOfRange { answer: String, verdict: Verdict },
+    /// The server asked to wait before submitting again.
+    Wait(Duration),
+}
+
+impl Display for Refusal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Refusal::Solved(answer) => write!(f, "already solved, the answer is {answer}"),
+            Refusal::KnownWrong(verdict) => write!(f, "already submitted, it was {verdict}"),
+            Refusal::OutOfRange { answer, verdict } => {
+                write!(f, "{answer} was {verdict} already, so this answer is too")
+            }
+            Refusal::Wait(time_left) => {
+                write!(f, "wait {} more seconds to submit", time_left.as_secs())
+            }
+        }
+    }
+}
+
+/// Every answer submitted, stored as JSON lines so that attempts are appended.
+#[derive(Debug)]
+pub struct History {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+impl History {
+    /// Reads the history from `path`, which need not exist yet.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, SubmitError> {
+        let path = path.into();
+
+        let attempts = match fs::read_to_string(&path) {
+            Ok(attempts) => attempts
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(line_index, line)| {
+                    serde_json::from_str(line)
+                        .map_err(|error| SubmitError::History(path.clone(), line_index + 1, error))
+                })
+                .collect::<Result<_, _>>()?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(SubmitError::Io(path, error)),
+        };
+
+        Ok(History { path, attempts })
+    }
+
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    pub fn record(&mut self, attempt: Attempt) -> Result<(), SubmitError> {
+        let line = serde_json::to_string(&attempt).expect("attempts serialize to JSON");
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|error| SubmitError::Io(dir.to_path_buf(), error))?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{line}"))
+            .map_err(|error| SubmitError::Io(self.path.clone(), error))?;
+
+        self.attempts.push(attempt);
+
+        Ok(())
+    }
+
+    /// Tells why `answer` should not be submitted for `part` of `day` at
+    /// `now`, in seconds since the Unix epoch, if the history knows a reason.
+    pub fn refusal(&self, day: u8, part: Part, answer: &str, now: u64) -> Option<Refusal> {
+        let attempts = || {
+            self.attempts
+                .iter()
+                .filter(move |attempt| attempt.day == day && attempt.part == part)
+        };
+
+        if let Some(attempt) = attempts().find(|attempt| attempt.verdict == Verdict::Correct) {
+            return Some(Refusal::Solved(attempt.answer.clone()));
+        }
+
+        if let Some(attempt) =
+            attempts().find(|attempt| attempt.answer == answer && attempt.verdict.is_wrong())
+        {
+            return Some(Refusal::KnownWrong(attempt.verdict.clone()));
+        }
+
+        if let Ok(number) = answer.parse::<i64>() {
+            let out_of_range = attempts().find(|attempt| {
+                attempt
+                    .answer
+                    .parse::<i64>()
+                    .is_ok_and(|known| match attempt.verdict {
+                        Verdict::TooHigh => number >= known,
+                        Verdict::TooLow => number <= known,
+                        _ => false,
+                    })
+            });
+
+            if let Some(attempt) = out_of_range {
+                return Some(Refusal::OutOfRange {
+                    answer: attempt.answer.clone(),
+                    verdict: attempt.verdict.clone(),
+                });
+            }
+        }
+
+        // The server limits the submissions of an account, whatever the puzzle,
+        // so the latest wait applies to every day and part.
+        self.attempts
+            .iter()
+            .filter_map(|attempt| match attempt.verdict {
+                Verdict::Wait { seconds } => Some((attempt.submitted_at, seconds)),
+                _ => None,
+            })
+            .max_by_key(|&(submitted_at, _)| submitted_at)
+            .map(|(submitted_at, seconds)| submitted_at + seconds)
+            .filter(|&wait_until| wait_until > now)
+            .map(|wait_until| Refusal::Wait(Duration::from_secs(wait_until - now)))
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Io(PathBuf, io::Error),
+    /// A line of the history file could not be read.
+    History(PathBuf, usize, serde_json::Error),
+    Refused(Refusal),
+    /// The server did not accept the session token.
+    InvalidSession,
+    Status(u16),
+    Transport(String),
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            SubmitError::History(path, line_number, error) => {
+                write!(f, "{}:{line_number}: {error}", path.display())
+            }
+            SubmitError::Refused(refusal) => write!(f, "not submitted: {refusal}"),
+            SubmitError::InvalidSession => write!(f, "the session token was not accepted"),
+            SubmitError::Status(status) => write!(f, "the server answered with status {status}"),
+            SubmitError::Transport(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for SubmitError {}
+
+/// Submits answers and keeps the history of them.
+pub struct Submitter {
+    session: String,
+    base_url: String,
+    agent: ureq::Agent,
+    history: History,
+}
+
+impl Submitter {
+    pub fn new(session: impl Into<String>, history: History) -> Self {
+        Submitter {
+            session: session.into(),
+            base_url: BASE_URL.to_string(),
+            agent: inputs::agent(None),
+            history,
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Sets how the Advent of Code team can reach whoever submits the answers.
+    pub fn with_contact(mut self, contact: Option<&str>) -> Self {
+        self.agent = inputs::agent(contact);
+        self
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn history_path(&self) -> &Path {
+        &self.history.path
+    }
+
+    /// Submits `answer` for `part` of `day` and records the verdict, unless
+    /// the history already shows the answer to be wrong.
+    pub fn submit(&mut self, day: u8, part: Part, answer: &str) -> Result<Verdict, SubmitError> {
+        let now = inputs::since_epoch().as_secs();
+
+        if let Some(refusal) = self.history.refusal(day, part, answer, now) {
+            return Err(SubmitError::Refused(refusal));
+        }
+
+        let response = self
+            .agent
+            .post(&format!("{}/{YEAR}/day/{day}/answer", self.base_url))
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+
+        let page = match response {
+            Ok(response) => response
+                .into_string()
+                .map_err(|error| SubmitError::Transport(error.to_string()))?,
+            Err(ureq::Error::Status(400, _)) => return Err(SubmitError::InvalidSession),
+            Err(ureq::Error::Status(status, _)) => return Err(SubmitError::Status(status)),
+            Err(ureq::Error::Transport(error)) => {
+                return Err(SubmitError::Transport(error.to_string()))
+            }
+        };
+
+        let verdict = Verdict::parse(&page);
+
+        self.history.record(Attempt {
+            day,
+            part,
+            answer: answer.to_string(),
+            verdict: verdict.clone(),
+            submitted_at: now,
+        })?;
+
+        Ok(verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(message: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en-us\">\n<head><title>Day 1 - Advent of Code \
+             2022</title></head>\n<body>\n<main>\n<article><p>{message}</p></article>\n\
+             </main>\n</body>\n</html>"
+        )
+    }
+
+    fn history(name: &str) -> History {
+        let path = std::env::temp_dir().join(format!(
+            "aoc2022-submissions-{name}-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        History::load(path).unwrap()
+    }
+
+    fn attempt(answer: &str, verdict: Verdict) -> Attempt {
+        Attempt {
+            day: 1,
+            part: Part::One,
+            answer: answer.to_string(),
+            verdict,
+            submitted_at: 1_000,
+        }
+    }
+
+    #[test]
+    fn verdicts() {
+        assert_eq!(
+            Verdict::parse(&page(
+                "That's the right answer!  You are <span class=\"day-success\">one gold \
+                 star</span> closer to collecting enough star fruit."
+            )),
+            Verdict::Correct
+        );
+        assert_eq!(
+            Verdict::parse(&page(
+                "That's not the right answer; your answer is too high.  If you're stuck, make \
+                 sure you're using the full input data. Please wait one minute before trying \
+                 again. <a href=\"/2022/day/1\">[Return to Day 1]</a>"
+            )),
+            Verdict::TooHigh
+        );
+        assert_eq!(
+            Verdict::parse(&page(
+                "That's not the right answer; your answer is too low.  Please wait one minute."
+            )),
+            Verdict::TooLow
+        );
+        assert_eq!(
+            Verdict::parse(&page(
+                "That's not the right answer.  If you're stuck, make sure you're using the \
+                 full input data."
+            )),
+            Verdict::Wrong
+        );
+        assert_eq!(
+            Verdict::parse(&page(
+                "You gave an answer too recently; you have to wait after submitting an answer \
+                 before trying again.  You have 4m 59s left to wait."
+            )),
+            Verdict::Wait { seconds: 299 }
+        );
+        assert_eq!(
+            Verdict::parse(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            )),
+            Verdict::WrongLevel
+        );
+        assert_eq!(
+            Verdict::parse("<html>Down for maintenance</html>"),
+            Verdict::Unrecognized("Down for maintenance".to_string())
+        );
+    }
+
+    #[test]
+    fn refusals() {
+        let mut history = history("refusals");
+
+        for attempt in [
+            attempt("69000", Verdict::TooHigh),
+            attempt("24000", Verdict::TooLow),
+            attempt("45000", Verdict::Wait { seconds: 60 }),
+        ] {
+            history.record(attempt).unwrap();
+        }
+
+        let refusal = |answer| history.refusal(1, Part::One, answer, 1_030);
+
+        assert_eq!(
+            refusal("69000"),
+            Some(Refusal::KnownWrong(Verdict::TooHigh))
+        );
+        assert_eq!(
+            refusal("70000"),
+            Some(Refusal::OutOfRange {
+                answer: "69000".to_string(),
+                verdict: Verdict::TooHigh
+            })
+        );
+        assert!(matches!(refusal("23999"), Some(Refusal::OutOfRange { .. })));
+        assert_eq!(
+            refusal("45000"),
+            Some(Refusal::Wait(Duration::from_secs(30)))
+        );
+        assert_eq!(history.refusal(1, Part::One, "45000", 1_060), None);
+        assert_eq!(history.refusal(1, Part::Two, "69000", 1_060), None);
+
+        history.record(attempt("45000", Verdict::Correct)).unwrap();
+
+        assert_eq!(
+            History::load(&history.path)
+                .unwrap()
+                .refusal(1, Part::One, "46000", 2_000),
+            Some(Refusal::Solved("45000".to_string()))
+        );
+    }
+
+    #[test]
+    fn waits_apply_to_every_puzzle() {
+        let mut history = history("waits");
+
+        history
+            .record(Attempt {
+                day: 3,
+                part: Part::Two,
+                ..attempt("70", Verdict::Wait { seconds: 300 })
+            })
+            .unwrap();
+
+        assert_eq!(
+            history.refusal(1, Part::One, "45000", 1_100),
+            Some(Refusal::Wait(Duration::from_secs(200)))
+        );
+        assert_eq!(
+            history.refusal(3, Part::One, "45000", 1_299),
+            Some(Refusal::Wait(Duration::from_secs(1)))
+        );
+
+        // A shorter wait after it is the one the server counts from.
+        history
+            .record(Attempt {
+                submitted_at: 1_010,
+                ..attempt("46000", Verdict::Wait { seconds: 30 })
+            })
+            .unwrap();
+
+        assert_eq!(
+            history.refusal(3, Part::Two, "71", 1_030),
+            Some(Refusal::Wait(Duration::from_secs(10)))
+        );
+        assert_eq!(history.refusal(3, Part::Two, "71", 1_040), None);
+    }
+
+    #[test]
+    fn submits_to_the_server() {
+        let (base_url, server) = inputs::mock_server(vec![(
+            200,
+            "<main><article><p>That's not the right answer; your answer is too low.</p>\
+             </article></main>",
+        )]);
+        let mut submitter =
+            Submitter::new("53616c7465645f5f", history("server")).with_base_url(base_url);
+
+        assert_eq!(
+            submitter.submit(1, Part::Two, "41000").unwrap(),
+            Verdict::TooLow
+        );
+        // Known to be too low, so this one never reaches the server.
+        assert!(matches!(
+            submitter.submit(1, Part::Two, "40000"),
+            Err(SubmitError::Refused(Refusal::OutOfRange { .. }))
+        ));
+
+        let requests = server.join().unwrap();
+
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("POST /2022/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[0].contains("session=53616c7465645f5f"));
+        assert!(requests[0].ends_with("level=2&answer=41000"));
+
+        let history = History::load(submitter.history_path()).unwrap();
+
+        assert_eq!(history.attempts().len(), 1);
+        assert_eq!(history.attempts()[0].answer, "41000");
+        assert_eq!(history.attempts()[0].verdict, Verdict::TooLow);
+    }
+}
@@ -0,0 +1,254 @@
+//! Counters and phase timings recorded by the slow solvers, to see where their
+//! time goes: how many states a search explores, how many it prunes, how large
+//! its stack grows and how long each of its phases takes.
+//!
+//! Recording only happens with the `trace` feature; without it every function
+//! here does nothing and [`take`] returns an empty trace. The trace is kept per
+//! thread, so days solved in parallel do not mix their counters.
+
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// Whether the solvers record their traces in this build.
+pub const ENABLED: bool = cfg!(feature = "trace");
+
+/// What a solver recorded, keyed by counter and phase name.
+#[derive(Clone, Default, Eq, PartialEq, Debug, Serialize)]
+pub struct Trace {
+    pub counters: BTreeMap<&'static str, u64>,
+    #[serde(rename = "phases_ns", serialize_with = "nanoseconds")]
+    pub phases: BTreeMap<&'static str, Duration>,
+}
+
+fn nanoseconds<S: Serializer>(
+    phases: &BTreeMap<&'static str, Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        phases
+            .iter()
+            .map(|(name, time)| (name, u64::try_from(time.as_nanos()).unwrap_or(u64::MAX))),
+    )
+}
+
+impl Trace {
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.phases.is_empty()
+    }
+}
+
+/// One line per counter and phase, in the style of the timings of `run`.
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut lines = self
+            .counters
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .chain(
+                self.phases
+                    .iter()
+                    .map(|(name, time)| format!("{name} phase: {time:?}")),
+            )
+            .peekable();
+
+        while let Some(line) = lines.next() {
+            write!(f, "{line}")?;
+
+            if lines.peek().is_some() {
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "trace")]
+mod recording {
+    use super::Trace;
+    use std::cell::RefCell;
+    use std::time::Instant;
+
+    thread_local! {
+        static CURRENT: RefCell<Trace> = RefCell::default();
+    }
+
+    pub fn count(name: &'static str, amount: u64) {
+        CURRENT.with_borrow_mut(|trace| *trace.counters.entry(name).or_default() += amount);
+    }
+
+    pub fn maximum(name: &'static str, value: u64) {
+        CURRENT.with_borrow_mut(|trace| {
+            let maximum = trace.counters.entry(name).or_default();
+            *maximum = (*maximum).max(value);
+        });
+    }
+
+    pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let time = start.elapsed();
+
+        CURRENT.with_borrow_mut(|trace| *trace.phases.entry(name).or_default() += time);
+
+        result
+    }
+
+    pub fn take() -> Trace {
+        CURRENT.take()
+    }
+
+    #[derive(Copy, Clone, Default, Debug)]
+    pub struct Counter(u64);
+
+    impl Counter {
+        pub const fn new() -> Self {
+            Counter(0)
+        }
+
+        pub fn add(&mut self, amount: u64) {
+            self.0 += amount;
+        }
+
+        pub fn raise(&mut self, value: u64) {
+            self.0 = self.0.max(value);
+        }
+
+        pub fn count(self, name: &'static str) {
+            count(name, self.0);
+        }
+
+        pub fn maximum(self, name: &'static str) {
+            maximum(name, self.0);
+        }
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod recording {
+    use super::Trace;
+
+    pub fn count(_name: &'static str, _amount: u64) {}
+
+    pub fn maximum(_name: &'static str, _value: u64) {}
+
+    pub fn phase<T>(_name: &'static str, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    pub fn take() -> Trace {
+        Trace::default()
+    }
+
+    #[derive(Copy, Clone, Default, Debug)]
+    pub struct Counter;
+
+    impl Counter {
+        pub const fn new() -> Self {
+            Counter
+        }
+
+        pub fn add(&mut self, _amount: u64) {}
+
+        pub fn raise(&mut self, _value: u64) {}
+
+        pub fn count(self, _name: &'static str) {}
+
+        pub fn maximum(self, _name: &'static str) {}
+    }
+}
+
+/// A count kept in a local variable of a hot loop and recorded when the loop
+/// is done, which costs nothing without the `trace` feature: `add` counts up,
+/// `raise` keeps the largest value, and `count` or `maximum` record it.
+pub use recording::Counter;
+
+/// Adds `amount` to the counter `name`.
+pub fn count(name: &'static str, amount: u64) {
+    recording::count(name, amount);
+}
+
+/// Raises the counter `name` to `value` if it is lower, e.g. for a largest stack size.
+pub fn maximum(name: &'static str, value: u64) {
+    recording::maximum(name, value);
+}
+
+/// Runs `f` and adds the time it takes to the phase `name`.
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    recording::phase(name, f)
+}
+
+/// Returns what this thread recorded since the last call and starts afresh.
+pub fn take() -> Trace {
+    recording::take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let trace = Trace {
+            counters: BTreeMap::from([("max stack size", 4), ("states explored", 120)]),
+            phases: BTreeMap::from([("search", Duration::from_millis(3))]),
+        };
+
+        assert_eq!(
+            trace.to_string(),
+            "max stack size: 4\nstates explored: 120\nsearch phase: 3ms"
+        );
+        assert_eq!(
+            serde_json::to_string(&trace).unwrap(),
+            r#"{"counters":{"max stack size":4,"states explored":120},"phases_ns":{"search":3000000}}"#
+        );
+    }
+
+    #[test]
+    fn records() {
+        take();
+
+        count("states explored", 2);
+        count("states explored", 3);
+        maximum("max stack size", 7);
+        maximum("max stack size", 5);
+        assert_eq!(phase("search", || 42), 42);
+
+        let trace = take();
+
+        if ENABLED {
+            assert_eq!(trace.counters["states explored"], 5);
+            assert_eq!(trace.counters["max stack size"], 7);
+            assert!(trace.phases.contains_key("search"));
+        } else {
+            assert!(trace.is_empty());
+        }
+
+        assert!(take().is_empty());
+    }
+
+    #[test]
+    fn counters() {
+        take();
+
+        let (mut explored, mut largest) = (Counter::new(), Counter::new());
+        explored.add(2);
+        explored.add(3);
+        largest.raise(7);
+        largest.raise(5);
+        explored.count("states explored");
+        largest.maximum("max stack size");
+
+        let trace = take();
+
+        if ENABLED {
+            assert_eq!(trace.counters["states explored"], 5);
+            assert_eq!(trace.counters["max stack size"], 7);
+        } else {
+            assert!(trace.is_empty());
+            assert_eq!(std::mem::size_of::<Counter>(), 0);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::runner;
+use crate::solution::{Answer, DynSolution, Part};
+use serde::de::Error;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// Known-good answers, read from a TOML file with one table per day:
+///
+/// ```toml
+/// [day5]
+/// part1 = "CMZ"
+/// part2 = "MCD"
+///
+/// [day6]
+/// part1 = 7
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ExpectedAnswers(BTreeMap<(u8, Part), Answer>);
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DayAnswers {
+    part1: Option<ExpectedAnswer>,
+    part2: Option<ExpectedAnswer>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpectedAnswer {
+    Integer(i64),
+    Text(String),
+}
+
+impl From<ExpectedAnswer> for Answer {
+    fn from(expected_answer: ExpectedAnswer) -> Self {
+        match expected_answer {
+            ExpectedAnswer::Integer(integer) => Answer::Integer(integer),
+            // Multi-line answers such as the day 10 screen are easiest to write as
+            // TOML multi-line strings, which end with a line break.
+            ExpectedAnswer::Text(text) => Answer::Text(text.trim_end().to_string()),
+        }
+    }
+}
+
+impl ExpectedAnswers {
+    pub fn parse(answers: &str) -> Result<Self, toml::de::Error> {
+        let mut expected_answers = BTreeMap::new();
+
+        for (key, day_answers) in toml::from_str::<BTreeMap<String, DayAnswers>>(answers)? {
+            let day = key
+                .strip_prefix("day")
+                .and_then(|day| day.parse::<u8>().ok())
+                .filter(|day| (1..=25).contains(day))
+                .ok_or_else(|| {
+                    toml::de::Error::custom(format!("expected day1 to day25, found {key:?}"))
+                })?;
+
+            for (part, expected_answer) in [
+                (Part::One, day_answers.part1),
+                (Part::Two, day_answers.part2),
+            ] {
+                if let Some(expected_answer) = expected_answer {
+                    expected_answers.insert((day, part), expected_answer.into());
+                }
+            }
+        }
+
+        Ok(ExpectedAnswers(expected_answers))
+    }
+
+    /// The days with at least one expected answer, in order.
+    pub fn days(&self) -> Vec<u8> {
+        let mut days = self.0.keys().map(|&(day, _)| day).collect::<Vec<_>>();
+        days.dedup();
+        days
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&Answer> {
+        self.0.get(&(day, part))
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Outcome {
+    Pass,
+    Mismatch {
+        expected: Answer,
+        actual: Answer,
+    },
+    /// The part could not be solved at all, e.g. because the input is missing or malformed.
+    Fail(String),
+}
+
+/// The outcome of checking one part of a day against its expected answer.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub outcome: Outcome,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Day {} - Part {}: ", self.day, self.part)?;
+
+        match &self.outcome {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Mismatch { expected, actual } => write!(
+                f,
+                "mismatch, expected {}, found {}",
+                quoted(expected),
+                quoted(actual)
+            ),
+            Outcome::Fail(reason) => write!(f, "fail, {reason}"),
+        }
+    }
+}
+
+fn quoted(answer: &Answer) -> String {
+    match answer {
+        Answer::Text(text) => format!("{text:?}"),
+        _ => answer.to_string(),
+    }
+}
+
+/// Solves every part of the day that has an expected answer with its default
+/// variant and compares the results.
+pub fn verify(
+    solution: &dyn DynSolution,
+    input: &str,
+    expected_answers: &ExpectedAnswers,
+) -> Vec<Check> {
+    let day = solution.day();
+    let expected_parts = Part::ALL
+        .into_iter()
+        .filter_map(|part| Some((part, expected_answers.get(day, part)?)))
+        .collect::<Vec<_>>();
+
+    let selection = expected_parts
+        .iter()
+        .flat_map(|&(part, _)| runner::select(solution, Some(part), None))
+        .collect::<Vec<_>>();
+
+    let runs = match runner::run(solution, input, &selection) {
+        Ok(runs) => runs,
+        Err(error) => return failed(day, expected_answers, &error.to_string()),
+    };
+
+    expected_parts
+        .into_iter()
+        .map(|(part, expected)| {
+            let outcome = match runs.iter().find(|run| run.part == part) {
+                Some(run) if &run.answer == expected => Outcome::Pass,
+                Some(run) => Outcome::Mismatch {
+                    expected: expected.clone(),
+                    actual: run.answer.clone(),
+                },
+                None => Outcome::Fail(format!("day {day} has no part {part}")),
+            };
+
+            Check { day, part, outcome }
+        })
+        .collect()
+}
+
+/// Reports every expected answer of the day as failed for the same reason.
+pub fn failed(day: u8, expected_answers: &ExpectedAnswers, reason: &str) -> Vec<Check> {
+    Part::ALL
+        .into_iter()
+        .filter(|&part| expected_answers.get(day, part).is_some())
+        .map(|part| Check {
+            day,
+            part,
+            outcome: Outcome::Fail(reason.to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    static TEST_ANSWERS: &str = r#"
+[day5]
+part1 = "CMZ"
+part2 = "MCD"
+
+[day6]
+part1 = 7
+part2 = 20
+
+[day10]
+part2 = """
+##..##
+"""
+"#;
+
+    #[test]
+    fn parse_answers() {
+        let expected_answers = ExpectedAnswers::parse(TEST_ANSWERS).unwrap();
+
+        assert_eq!(expected_answers.days(), [5, 6, 10]);
+        assert_eq!(
+            expected_answers.get(5, Part::Two),
+            Some(&Answer::Text("MCD".to_string()))
+        );
+        assert_eq!(
+            expected_answers.get(10, Part::Two),
+            Some(&Answer::Text("##..##".to_string()))
+        );
+        assert_eq!(expected_answers.get(10, Part::One), None);
+    }
+
+    #[test]
+    fn parse_malformed_answers() {
+        assert!(ExpectedAnswers::parse("[day26]\npart1 = 1").is_err());
+        assert!(ExpectedAnswers::parse("[day1]\npart3 = 1").is_err());
+        assert!(ExpectedAnswers::parse("[day1]\npart1 = 1.5").is_err());
+    }
+
+    #[test]
+    fn pass_and_mismatch() {
+        let expected_answers = ExpectedAnswers::parse(TEST_ANSWERS).unwrap();
+        let checks = verify(
+            solution(6).unwrap(),
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            &expected_answers,
+        );
+
+        assert_eq!(checks[0].outcome, Outcome::Pass);
+        assert_eq!(
+            checks[1].outcome,
+            Outcome::Mismatch {
+                expected: Answer::Integer(20),
+                actual: Answer::Integer(19)
+            }
+        );
+        assert_eq!(
+            checks[1].to_string(),
+            "Day 6 - Part 2: mismatch, expected 20, found 19"
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        let expected_answers = ExpectedAnswers::parse(TEST_ANSWERS).unwrap();
+        let checks = verify(solution(6).unwrap(), "mjq1", &expected_answers);
+
+        assert_eq!(checks.len(), 2);
+        assert!(checks
+            .iter()
+            .all(|check| matches!(check.outcome, Outcome::Fail(_))));
+    }
+}
@@ -0,0 +1,319 @@
+//! Step-by-step pictures of the simulation days, with exporters to terminal
+//! playback, PPM image streams and animated GIFs.
+
+use crate::geometry::{BoundingBox, Grid, Point2};
+use crate::solution::Part;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+pub const BACKGROUND: Rgb = Rgb(15, 15, 35);
+pub const GRAY: Rgb = Rgb(96, 96, 112);
+pub const WHITE: Rgb = Rgb(204, 204, 204);
+pub const GOLD: Rgb = Rgb(255, 255, 102);
+pub const GREEN: Rgb = Rgb(0, 204, 0);
+pub const RED: Rgb = Rgb(255, 64, 64);
+pub const BLUE: Rgb = Rgb(96, 160, 255);
+pub const SAND: Rgb = Rgb(230, 190, 110);
+
+/// A tile of a frame, drawn as a character in the terminal and as a square of
+/// colour in images.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Cell {
+    pub glyph: char,
+    pub color: Rgb,
+}
+
+impl Cell {
+    pub const fn new(glyph: char, color: Rgb) -> Self {
+        Cell { glyph, color }
+    }
+}
+
+pub const EMPTY: Cell = Cell::new(' ', BACKGROUND);
+
+/// One picture of a simulation, covering a fixed rectangle of its coordinates.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Frame {
+    origin: Point2<i32>,
+    cells: Grid<Cell>,
+}
+
+impl Frame {
+    /// Returns a frame of the tiles inside `bounds`, all filled with `cell`.
+    pub fn new(bounds: BoundingBox<Point2<i32>>, cell: Cell) -> Self {
+        Frame {
+            origin: bounds.min,
+            cells: Grid::new(bounds.width() as usize, bounds.height() as usize, cell),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    /// Returns the simulation coordinates of the top left tile.
+    pub fn origin(&self) -> Point2<i32> {
+        self.origin
+    }
+
+    /// Returns the tile at `point`, in simulation coordinates.
+    pub fn get(&self, point: Point2<i32>) -> Option<Cell> {
+        self.index(point)
+            .and_then(|index| self.cells.get(index))
+            .copied()
+    }
+
+    /// Draws `cell` at `point`. Points outside the frame are left out.
+    pub fn draw(&mut self, point: Point2<i32>, cell: Cell) {
+        if let Some(tile) = self
+            .index(point)
+            .and_then(|index| self.cells.get_mut(index))
+        {
+            *tile = cell;
+        }
+    }
+
+    fn index(&self, point: Point2<i32>) -> Option<Point2<usize>> {
+        let offset = point - self.origin;
+
+        match (usize::try_from(offset.x), usize::try_from(offset.y)) {
+            (Ok(x), Ok(y)) => Some(Point2::new(x, y)),
+            _ => None,
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.rows()
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, row) in self.rows().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+
+            for cell in row {
+                write!(f, "{}", cell.glyph)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub type Frames<'a> = Box<dyn Iterator<Item = Frame> + 'a>;
+
+/// A simulation that can be watched as it runs.
+pub trait Visualize {
+    /// Returns the initial state of the simulation for `part` and the state
+    /// after every step. All frames have the same size.
+    fn frames(&self, part: Part) -> Frames<'_>;
+}
+
+/// Plays the frames in a terminal, redrawing the screen every `delay`.
+pub fn play(
+    frames: impl Iterator<Item = Frame>,
+    delay: Duration,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for frame in frames {
+        // Moves the cursor home and clears the screen before every frame.
+        writeln!(out, "\x1b[H\x1b[2J{frame}")?;
+        out.flush()?;
+        thread::sleep(delay);
+    }
+
+    Ok(())
+}
+
+/// Writes the frames as a stream of binary PPM images, `scale` pixels per tile,
+/// which e.g. `ffmpeg -f ppm_pipe` can turn into a video.
+pub fn write_ppm(
+    frames: impl Iterator<Item = Frame>,
+    scale: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for frame in frames {
+        write!(
+            out,
+            "P6\n{} {}\n255\n",
+            frame.width() * scale,
+            frame.height() * scale
+        )?;
+
+        for row in frame.rows() {
+            let pixels: Vec<u8> = row
+                .iter()
+                .flat_map(|cell| [cell.color.0, cell.color.1, cell.color.2].repeat(scale))
+                .collect();
+
+            for _ in 0..scale {
+                out.write_all(&pixels)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the frames as an animated GIF that loops forever, `scale` pixels
+/// per tile and `delay` between frames.
+pub fn write_gif(
+    mut frames: impl Iterator<Item = Frame>,
+    scale: usize,
+    delay: Duration,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(first_frame) = frames.next() else {
+        return Ok(());
+    };
+
+    let dimension = |tiles: usize| {
+        u16::try_from(tiles * scale)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large for a GIF"))
+    };
+    let width = dimension(first_frame.width())?;
+    let height = dimension(first_frame.height())?;
+
+    let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    let delay = u16::try_from(delay.as_millis() / 10).unwrap_or(u16::MAX);
+
+    for frame in std::iter::once(first_frame).chain(frames) {
+        let mut palette: HashMap<Rgb, u8> = HashMap::new();
+        let mut pixels = Vec::with_capacity(usize::from(width) * usize::from(height));
+
+        for row in frame.rows() {
+            let mut row_pixels = Vec::with_capacity(row.len() * scale);
+
+            for cell in row {
+                let index = match palette.get(&cell.color) {
+                    Some(&index) => index,
+                    None => {
+                        let index = u8::try_from(palette.len()).map_err(|_| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                "more than 256 colours in a frame",
+                            )
+                        })?;
+                        palette.insert(cell.color, index);
+                        index
+                    }
+                };
+
+                row_pixels.extend(std::iter::repeat_n(index, scale));
+            }
+
+            for _ in 0..scale {
+                pixels.extend_from_slice(&row_pixels);
+            }
+        }
+
+        let mut colors = vec![0; palette.len() * 3];
+
+        for (color, index) in palette {
+            let index = usize::from(index) * 3;
+            colors[index..index + 3].copy_from_slice(&[color.0, color.1, color.2]);
+        }
+
+        let mut gif_frame = gif::Frame::from_palette_pixels(width, height, pixels, colors, None);
+        gif_frame.delay = delay;
+
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> Vec<Frame> {
+        let bounds = BoundingBox {
+            min: Point2::new(-1, -1),
+            max: Point2::new(1, 0),
+        };
+
+        (0..3)
+            .map(|step| {
+                let mut frame = Frame::new(bounds, Cell::new('.', BACKGROUND));
+                frame.draw(Point2::new(step - 1, 0), Cell::new('#', GOLD));
+                frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draw() {
+        let mut frame = frames().remove(1);
+        frame.draw(Point2::new(2, 0), Cell::new('x', RED));
+        frame.draw(Point2::new(-2, -1), Cell::new('x', RED));
+
+        assert_eq!((frame.width(), frame.height()), (3, 2));
+        assert_eq!(frame.to_string(), "...\n.#.");
+        assert_eq!(frame.origin(), Point2::new(-1, -1));
+        assert_eq!(frame.get(Point2::new(0, 0)), Some(Cell::new('#', GOLD)));
+        assert_eq!(frame.get(Point2::new(2, 0)), None);
+    }
+
+    #[test]
+    fn play_frames() {
+        let mut out = Vec::new();
+        play(frames().into_iter(), Duration::ZERO, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(out.matches("\x1b[2J").count(), 3);
+        assert!(out.ends_with("...\n..#\n"));
+    }
+
+    #[test]
+    fn ppm() {
+        let mut out = Vec::new();
+        write_ppm(frames().into_iter(), 2, &mut out).unwrap();
+
+        let header = b"P6\n6 4\n255\n";
+        let image_size = header.len() + 6 * 4 * 3;
+
+        assert_eq!(out.len(), 3 * image_size);
+        assert!(out.starts_with(header));
+        assert_eq!(&out[header.len()..header.len() + 3], &[15, 15, 35]);
+    }
+
+    #[test]
+    fn gif() {
+        let mut out = Vec::new();
+        write_gif(frames().into_iter(), 2, Duration::from_millis(50), &mut out).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = decoder.read_info(out.as_slice()).unwrap();
+
+        assert_eq!((decoder.width(), decoder.height()), (6, 4));
+
+        let mut frame_count = 0;
+
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 5);
+            frame_count += 1;
+        }
+
+        assert_eq!(frame_count, 3);
+    }
+}